@@ -0,0 +1,38 @@
+//! Builds a small tree in a `ScopedArena` and prints it.
+#![cfg_attr(feature = "nightly", feature(allocator_api))]
+
+#[cfg(feature = "nightly")]
+mod tree;
+
+#[cfg(feature = "nightly")]
+fn main() {
+    use std::mem::MaybeUninit;
+
+    use noop_allocator::bump::ScopedArena;
+    use tree::Node;
+
+    let mut buf = [MaybeUninit::<u8>::uninit(); 2048];
+    let mut arena = ScopedArena::new(&mut buf);
+
+    arena.scope(|arena| {
+        let mut root = Node::new_in(1, arena).unwrap();
+        for i in 0..3 {
+            let child = root.add_child(10 + i).unwrap();
+            for j in 0..2 {
+                child.add_child(100 + 10 * i + j).unwrap();
+            }
+        }
+
+        root.visit(&mut |depth, value| println!("{:indent$}{value}", "", indent = 2 * depth));
+        println!(
+            "{} nodes, depth {}, {} bytes used",
+            root.count(),
+            root.depth(),
+            arena.used()
+        );
+    });
+    println!("{} bytes used after the scope", arena.used());
+}
+
+#[cfg(not(feature = "nightly"))]
+fn main() {}
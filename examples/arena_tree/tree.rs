@@ -0,0 +1,67 @@
+//! A tree whose nodes and child lists are allocated in a bump arena.
+//!
+//! Every node is a `Box<Node, &BumpAllocator>` and every list of children a
+//! `Vec<_, &BumpAllocator>`, all sharing one `&'a BumpAllocator<'a>`. Since
+//! `BumpAllocator` allocates through `&self` and is covariant in the lifetime
+//! of its buffer, a single lifetime `'a` ties the nodes to both the arena and
+//! its buffer. Dropping the tree frees nothing; the memory is reclaimed when a
+//! `ScopedArena` resets.
+use std::alloc::AllocError;
+
+use noop_allocator::bump::BumpAllocator;
+
+/// The allocator shared by all nodes of a tree.
+pub type Arena<'a> = &'a BumpAllocator<'a>;
+
+/// An owned node, allocated in the arena.
+pub type NodeBox<'a> = Box<Node<'a>, Arena<'a>>;
+
+pub struct Node<'a> {
+    pub value: u32,
+    pub children: Vec<NodeBox<'a>, Arena<'a>>,
+}
+
+impl<'a> Node<'a> {
+    /// Allocates a node without children in `arena`.
+    pub fn new_in(value: u32, arena: Arena<'a>) -> Result<NodeBox<'a>, AllocError> {
+        let node = Node {
+            value,
+            children: Vec::new_in(arena),
+        };
+        Box::try_new_in(node, arena)
+    }
+
+    /// Allocates a new child node and appends it to this node's children,
+    /// growing the list of children in the arena if needed.
+    ///
+    /// On failure, the tree is left unchanged.
+    pub fn add_child(&mut self, value: u32) -> Result<&mut Node<'a>, AllocError> {
+        let arena = *self.children.allocator();
+        self.children.try_reserve(1).map_err(|_| AllocError)?;
+        self.children.push(Node::new_in(value, arena)?);
+        Ok(self.children.last_mut().unwrap())
+    }
+
+    /// Returns the number of nodes in this subtree.
+    pub fn count(&self) -> usize {
+        1 + self.children.iter().map(|c| c.count()).sum::<usize>()
+    }
+
+    /// Returns the number of levels in this subtree.
+    pub fn depth(&self) -> usize {
+        1 + self.children.iter().map(|c| c.depth()).max().unwrap_or(0)
+    }
+
+    /// Calls `f` with the depth and value of every node in this subtree, in
+    /// pre-order.
+    pub fn visit(&self, f: &mut impl FnMut(usize, u32)) {
+        self.visit_at(0, f);
+    }
+
+    fn visit_at(&self, depth: usize, f: &mut impl FnMut(usize, u32)) {
+        f(depth, self.value);
+        for child in &self.children {
+            child.visit_at(depth + 1, f);
+        }
+    }
+}
@@ -0,0 +1,501 @@
+//! Storage types to borrow from, for use with the allocators and owning types
+//! in this crate.
+use core::{alloc::Layout, mem::MaybeUninit};
+
+/// An uninhabited type marking an alignment, for use in bounds on
+/// [`AlignedBuffer`].
+///
+/// `Align<A>` implements [`SupportedAlignment`] for every power of two `A` from
+/// 1 to 4096.
+pub enum Align<const A: usize> {}
+
+/// Implemented by [`Align<A>`] for the alignments supported by
+/// [`AlignedBuffer`].
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a supported buffer alignment",
+    label = "unsupported alignment",
+    note = "the alignment must be a power of two from 1 to 4096"
+)]
+pub trait SupportedAlignment: sealed::Sealed {
+    /// A zero-sized type with the alignment `A`.
+    #[doc(hidden)]
+    type Archetype: Copy;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! supported_alignments {
+    ($($name:ident = $align:literal),* $(,)?) => {
+        $(
+            #[doc(hidden)]
+            #[derive(Clone, Copy)]
+            #[repr(align($align))]
+            pub struct $name;
+
+            impl sealed::Sealed for Align<$align> {}
+            impl SupportedAlignment for Align<$align> {
+                type Archetype = $name;
+            }
+        )*
+    };
+}
+
+supported_alignments! {
+    Align1 = 1,
+    Align2 = 2,
+    Align4 = 4,
+    Align8 = 8,
+    Align16 = 16,
+    Align32 = 32,
+    Align64 = 64,
+    Align128 = 128,
+    Align256 = 256,
+    Align512 = 512,
+    Align1024 = 1024,
+    Align2048 = 2048,
+    Align4096 = 4096,
+}
+
+/// A buffer of `N` bytes, aligned to `ALIGN`.
+///
+/// This replaces hand-written `#[repr(align(...))]` wrapper structs around byte
+/// arrays. `ALIGN` must be a power of two from 1 to 4096; other values fail to
+/// compile, with an error naming the unsupported alignment.
+///
+/// # Examples
+#[cfg_attr(feature = "nightly", doc = "```rust")]
+#[cfg_attr(not(feature = "nightly"), doc = "```rust,ignore")]
+/// #![feature(allocator_api)]
+/// use noop_allocator::{buffer::AlignedBuffer, bump::BumpAllocator};
+/// let mut buf = AlignedBuffer::<256, 64>::uninit();
+/// let bytes = buf.as_uninit_bytes();
+/// assert_eq!(bytes.as_ptr() as usize % 64, 0);
+/// let bump = BumpAllocator::new(bytes);
+/// let v: Vec<u64, _> = Vec::with_capacity_in(10, &bump);
+/// assert_eq!(v.capacity(), 10);
+#[doc = "```"]
+///
+/// The constructors are `const`, so buffers can be declared in statics:
+/// ```rust
+/// use noop_allocator::buffer::AlignedBuffer;
+/// static ZEROED: AlignedBuffer<64, 16> = AlignedBuffer::zeroed();
+/// assert_eq!(ZEROED.as_ptr() as usize % 16, 0);
+/// assert_eq!(ZEROED.len(), 64);
+/// ```
+///
+/// Every supported alignment:
+/// ```rust
+/// use noop_allocator::buffer::AlignedBuffer;
+/// macro_rules! check {
+///     ($($align:literal)*) => {$(
+///         let mut buf = AlignedBuffer::<3, $align>::zeroed();
+///         let bytes = buf.as_uninit_bytes();
+///         assert_eq!(bytes.len(), 3);
+///         assert_eq!(bytes.as_ptr() as usize % $align, 0, "align {}", $align);
+///     )*};
+/// }
+/// check!(1 2 4 8 16 32 64 128 256 512 1024 2048 4096);
+/// ```
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct AlignedBuffer<const N: usize, const ALIGN: usize>
+where
+    Align<ALIGN>: SupportedAlignment,
+{
+    _align: [<Align<ALIGN> as SupportedAlignment>::Archetype; 0],
+    bytes: [MaybeUninit<u8>; N],
+}
+
+impl<const N: usize, const ALIGN: usize> AlignedBuffer<N, ALIGN>
+where
+    Align<ALIGN>: SupportedAlignment,
+{
+    /// Creates a new, uninitialized `AlignedBuffer`.
+    pub const fn uninit() -> Self {
+        Self {
+            _align: [],
+            bytes: [MaybeUninit::uninit(); N],
+        }
+    }
+
+    /// Creates a new `AlignedBuffer` filled with zero bytes.
+    pub const fn zeroed() -> Self {
+        Self {
+            _align: [],
+            bytes: [MaybeUninit::new(0); N],
+        }
+    }
+
+    /// Returns the bytes of the buffer, aligned to `ALIGN`.
+    pub fn as_uninit_bytes(&mut self) -> &mut [MaybeUninit<u8>] {
+        &mut self.bytes
+    }
+
+    /// Returns a pointer to the start of the buffer, aligned to `ALIGN`.
+    pub const fn as_ptr(&self) -> *const u8 {
+        self.bytes.as_ptr().cast()
+    }
+
+    /// Returns a mutable pointer to the start of the buffer, aligned to
+    /// `ALIGN`.
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.bytes.as_mut_ptr().cast()
+    }
+
+    /// Returns the size of the buffer, `N`.
+    #[allow(clippy::len_without_is_empty)]
+    pub const fn len(&self) -> usize {
+        N
+    }
+}
+
+impl<const N: usize, const ALIGN: usize> Default for AlignedBuffer<N, ALIGN>
+where
+    Align<ALIGN>: SupportedAlignment,
+{
+    fn default() -> Self {
+        Self::uninit()
+    }
+}
+
+impl<const N: usize, const ALIGN: usize> AsMut<[MaybeUninit<u8>]> for AlignedBuffer<N, ALIGN>
+where
+    Align<ALIGN>: SupportedAlignment,
+{
+    fn as_mut(&mut self) -> &mut [MaybeUninit<u8>] {
+        self.as_uninit_bytes()
+    }
+}
+
+impl<const N: usize, const ALIGN: usize> core::fmt::Debug for AlignedBuffer<N, ALIGN>
+where
+    Align<ALIGN>: SupportedAlignment,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AlignedBuffer")
+            .field("len", &N)
+            .field("align", &ALIGN)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An [`AlignedBuffer`] for use in a `static`, which can be mutably borrowed
+/// once.
+///
+/// Usually declared with [`aligned_buf!`][crate::aligned_buf].
+///
+/// This type is only available on targets with native atomics, or with the
+/// `portable-atomic` feature enabled.
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+pub struct StaticAlignedBuffer<const N: usize, const ALIGN: usize>
+where
+    Align<ALIGN>: SupportedAlignment,
+{
+    buf: core::cell::UnsafeCell<AlignedBuffer<N, ALIGN>>,
+    taken: crate::sync::AtomicBool,
+}
+
+// SAFETY: The buffer is only accessed through the single `&'static mut`
+// handed out by `take`.
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+unsafe impl<const N: usize, const ALIGN: usize> Sync for StaticAlignedBuffer<N, ALIGN> where
+    Align<ALIGN>: SupportedAlignment
+{
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<const N: usize, const ALIGN: usize> StaticAlignedBuffer<N, ALIGN>
+where
+    Align<ALIGN>: SupportedAlignment,
+{
+    /// Creates a new, uninitialized `StaticAlignedBuffer`.
+    pub const fn new() -> Self {
+        Self {
+            buf: core::cell::UnsafeCell::new(AlignedBuffer::uninit()),
+            taken: crate::sync::AtomicBool::new(false),
+        }
+    }
+
+    /// Returns the bytes of the buffer the first time it is called, and `None`
+    /// afterwards.
+    #[allow(clippy::mut_from_ref)]
+    pub fn take(&'static self) -> Option<&'static mut [MaybeUninit<u8>]> {
+        if self.taken.swap(true, crate::sync::Ordering::Acquire) {
+            return None;
+        }
+        Some(unsafe { (*self.buf.get()).as_uninit_bytes() })
+    }
+
+    /// Returns `true` if [`take`][Self::take] has been called.
+    pub fn is_taken(&self) -> bool {
+        self.taken.load(crate::sync::Ordering::Relaxed)
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<const N: usize, const ALIGN: usize> Default for StaticAlignedBuffer<N, ALIGN>
+where
+    Align<ALIGN>: SupportedAlignment,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<const N: usize, const ALIGN: usize> core::fmt::Debug for StaticAlignedBuffer<N, ALIGN>
+where
+    Align<ALIGN>: SupportedAlignment,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("StaticAlignedBuffer")
+            .field("len", &N)
+            .field("align", &ALIGN)
+            .field("taken", &self.taken.load(crate::sync::Ordering::Relaxed))
+            .finish()
+    }
+}
+
+/// Storage for `N` values of type `T`, with the size and alignment of `[T; N]`.
+///
+/// This avoids computing the size and alignment of a byte buffer by hand. It
+/// can be borrowed as `[MaybeUninit<T>]` (e.g. for an
+/// [`OwningSlice`][crate::owning_slice::OwningSlice], through
+/// [`AsMut`]) or as bytes (e.g. for the allocators in this crate).
+///
+/// # Examples
+#[cfg_attr(feature = "nightly", doc = "```rust")]
+#[cfg_attr(not(feature = "nightly"), doc = "```rust,ignore")]
+/// #![feature(allocator_api)]
+/// use noop_allocator::{buffer::BufferFor, owning_slice, slab::SlabAllocator};
+/// use std::alloc::Layout;
+///
+/// let mut buf = BufferFor::<u64, 4>::uninit();
+/// let mut vec = owning_slice::empty_from_storage(&mut buf);
+/// vec.extend([1, 2, 3, 4]);
+/// assert_eq!(vec.capacity(), 4);
+/// drop(vec);
+///
+/// let mut buf = BufferFor::<[u32; 4], 8>::uninit();
+/// let layout = Layout::new::<[u32; 4]>();
+/// let slab = SlabAllocator::new(buf.as_uninit_bytes(), layout);
+/// assert_eq!(slab.block_count(), 8);
+#[doc = "```"]
+///
+/// The layout follows that of `T`, including for over-aligned and zero-sized
+/// types:
+/// ```rust
+/// use noop_allocator::buffer::BufferFor;
+/// use std::mem::{align_of, size_of};
+/// #[repr(align(64))]
+/// struct Line(u8);
+/// assert_eq!(size_of::<BufferFor<Line, 3>>(), 192);
+/// assert_eq!(align_of::<BufferFor<Line, 3>>(), 64);
+/// let mut buf = BufferFor::<Line, 3>::uninit();
+/// assert_eq!(buf.as_uninit_bytes().len(), 192);
+/// assert_eq!(buf.as_uninit_bytes().as_ptr() as usize % 64, 0);
+///
+/// assert_eq!(size_of::<BufferFor<(), 5>>(), 0);
+/// let mut buf = BufferFor::<(), 5>::uninit();
+/// assert_eq!(buf.as_uninit_slice().len(), 5);
+/// assert_eq!(buf.as_uninit_bytes().len(), 0);
+/// ```
+#[repr(transparent)]
+pub struct BufferFor<T, const N: usize>([MaybeUninit<T>; N]);
+
+impl<T, const N: usize> BufferFor<T, N> {
+    /// Creates a new, uninitialized `BufferFor<T, N>`.
+    pub const fn uninit() -> Self {
+        Self([const { MaybeUninit::uninit() }; N])
+    }
+
+    /// Returns the buffer as `N` uninitialized `T`s.
+    pub fn as_uninit_slice(&mut self) -> &mut [MaybeUninit<T>; N] {
+        &mut self.0
+    }
+
+    /// Returns the bytes of the buffer, aligned for `T`.
+    pub fn as_uninit_bytes(&mut self) -> &mut [MaybeUninit<u8>] {
+        unsafe {
+            core::slice::from_raw_parts_mut(
+                self.0.as_mut_ptr().cast(),
+                core::mem::size_of::<[T; N]>(),
+            )
+        }
+    }
+}
+
+impl<T, const N: usize> Default for BufferFor<T, N> {
+    fn default() -> Self {
+        Self::uninit()
+    }
+}
+
+impl<T, const N: usize> AsMut<[MaybeUninit<T>]> for BufferFor<T, N> {
+    fn as_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        &mut self.0
+    }
+}
+
+impl<T, const N: usize> core::fmt::Debug for BufferFor<T, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BufferFor")
+            .field("type", &core::any::type_name::<T>())
+            .field("len", &N)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Storage for a single `Rc<T, A>` or `Arc<T, A>` allocation: the reference
+/// counts followed by a `T`.
+///
+/// `Rc::new_in` allocates the counts and the value together, so a buffer sized
+/// for just a `T` is too small. The size and alignment of `BufferForRc<T>`
+/// match that allocation, so a bump allocator over its bytes has room for
+/// exactly one `Rc<T, _>`. The layout of the `Rc` allocation is an
+/// implementation detail of the standard library; if it changes, allocating
+/// fails rather than being unsound.
+///
+/// As with any `Rc`, a reference cycle keeps its strong count above zero, so
+/// the values in it are never dropped. With a borrowed buffer, no memory is
+/// leaked beyond the buffer itself, but nothing reports the skipped
+/// destructors either: break cycles with `Weak` references, or clear them
+/// explicitly, before the buffer goes out of scope.
+///
+/// # Examples
+#[cfg_attr(
+    all(
+        feature = "nightly",
+        any(feature = "portable-atomic", target_has_atomic = "ptr")
+    ),
+    doc = "```rust"
+)]
+#[cfg_attr(
+    not(all(
+        feature = "nightly",
+        any(feature = "portable-atomic", target_has_atomic = "ptr")
+    )),
+    doc = "```rust,ignore"
+)]
+/// #![feature(allocator_api)]
+/// use noop_allocator::{buffer::BufferForRc, bump::AtomicBumpAllocator};
+/// use std::rc::Rc;
+///
+/// #[repr(align(32))]
+/// struct Node(u8);
+/// let mut buf = BufferForRc::<Node>::uninit();
+/// let bump = AtomicBumpAllocator::new(buf.as_uninit_bytes());
+/// let a = Rc::new_in(Node(1), &bump);
+/// let b = Rc::clone(&a);
+/// assert_eq!(b.0, 1);
+/// assert_eq!(bump.remaining(), 0);
+/// ```
+#[repr(C)]
+pub struct BufferForRc<T> {
+    counts: [MaybeUninit<usize>; 2],
+    value: MaybeUninit<T>,
+}
+
+impl<T> BufferForRc<T> {
+    /// Creates a new, uninitialized `BufferForRc<T>`.
+    pub const fn uninit() -> Self {
+        Self {
+            counts: [MaybeUninit::uninit(); 2],
+            value: MaybeUninit::uninit(),
+        }
+    }
+
+    /// Returns the bytes of the buffer, aligned for the `Rc` allocation.
+    pub fn as_uninit_bytes(&mut self) -> &mut [MaybeUninit<u8>] {
+        unsafe {
+            core::slice::from_raw_parts_mut(
+                (self as *mut Self).cast(),
+                core::mem::size_of::<Self>(),
+            )
+        }
+    }
+}
+
+impl<T> Default for BufferForRc<T> {
+    fn default() -> Self {
+        Self::uninit()
+    }
+}
+
+impl<T> core::fmt::Debug for BufferForRc<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BufferForRc")
+            .field("type", &core::any::type_name::<T>())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Declares an aligned byte buffer.
+///
+/// `aligned_buf!(N, ALIGN)` is an expression creating an uninitialized
+/// [`AlignedBuffer<N, ALIGN>`][AlignedBuffer]:
+#[cfg_attr(feature = "nightly", doc = "```rust")]
+#[cfg_attr(not(feature = "nightly"), doc = "```rust,ignore")]
+/// #![feature(allocator_api)]
+/// use noop_allocator::{aligned_buf, bump::BumpAllocator};
+/// let mut buf = aligned_buf!(256, 32);
+/// let bytes = buf.as_uninit_bytes();
+/// assert_eq!(bytes.len(), 256);
+/// assert_eq!(bytes.as_ptr() as usize % 32, 0);
+/// let bump = BumpAllocator::new(bytes);
+/// # let _ = bump;
+#[doc = "```"]
+///
+/// `aligned_buf!(static NAME: N bytes, align ALIGN);` declares a `static`
+/// [`StaticAlignedBuffer<N, ALIGN>`][StaticAlignedBuffer], whose bytes can be
+/// borrowed once with [`take`][StaticAlignedBuffer::take]. Attributes and a
+/// visibility may precede `static`. This form is only available on targets with
+/// native atomics, or with the `portable-atomic` feature enabled.
+/// ```rust
+/// use noop_allocator::aligned_buf;
+/// aligned_buf!(pub static DMA_BUF: 1024 bytes, align 64);
+///
+/// let bytes = DMA_BUF.take().unwrap();
+/// assert_eq!(bytes.len(), 1024);
+/// assert_eq!(bytes.as_ptr() as usize % 64, 0);
+/// assert!(DMA_BUF.take().is_none());
+/// ```
+///
+/// As with `AlignedBuffer`, the alignment must be a power of two from 1 to
+/// 4096.
+#[macro_export]
+macro_rules! aligned_buf {
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $size:tt bytes, align $align:tt $(;)?) => {
+        $(#[$attr])*
+        $vis static $name: $crate::buffer::StaticAlignedBuffer<{ $size }, { $align }> =
+            $crate::buffer::StaticAlignedBuffer::new();
+    };
+    ($size:expr, $align:expr $(,)?) => {
+        $crate::buffer::AlignedBuffer::<{ $size }, { $align }>::uninit()
+    };
+}
+
+/// Returns the `(start, end)` offsets of an allocation of `layout` placed at or
+/// after `offset` in a buffer starting at `base` with length `capacity`, or
+/// `None` if it does not fit.
+#[cfg(any(
+    feature = "nightly",
+    feature = "portable-atomic",
+    target_has_atomic = "ptr"
+))]
+pub(crate) fn fit(
+    base: usize,
+    capacity: usize,
+    offset: usize,
+    layout: Layout,
+) -> Option<(usize, usize)> {
+    let addr = base.checked_add(offset)?;
+    let padding = addr.wrapping_neg() & (layout.align() - 1);
+    let start = offset.checked_add(padding)?;
+    let end = start.checked_add(layout.size())?;
+    (end <= capacity).then_some((start, end))
+}
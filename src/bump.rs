@@ -0,0 +1,1156 @@
+//! Bump allocators over a mutably borrowed byte buffer.
+//!
+//! [`BumpAllocator<'a>`][BumpAllocator] and
+//! [`AtomicBumpAllocator<'a>`][AtomicBumpAllocator] hand out consecutive
+//! regions of a borrowed `[MaybeUninit<u8>]`, and never reuse memory:
+//! [`deallocate`][Allocator::deallocate] is a no-op, like for
+//! [`NoopAllocator`][crate::NoopAllocator].
+//!
+//! Use them by reference, e.g. `Vec::new_in(&bump)`, so that several
+//! collections can share one buffer.
+use crate::buffer::fit;
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+use crate::buffer::{Align, StaticAlignedBuffer, SupportedAlignment};
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+use crate::sync::{AtomicUsize, Ordering};
+use core::{
+    alloc::{AllocError, Allocator, Layout},
+    cell::Cell,
+    fmt,
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ops::Deref,
+    ptr::NonNull,
+};
+
+#[cfg(feature = "alloc")]
+use crate::{NoopAllocator, owning_ref::OwningRef};
+
+/// The byte written into the redzone after each allocation of a
+/// [`BumpAllocator`] or [`ScopedArena`] when the `canary` feature is enabled.
+#[cfg(feature = "canary")]
+pub const CANARY: u8 = 0xCA;
+
+/// The default size of the redzone after each allocation when the `canary`
+/// feature is enabled. See [`BumpAllocator::with_redzone`].
+#[cfg(feature = "canary")]
+pub const DEFAULT_REDZONE: usize = 8;
+
+/// The size of the `[prev, size, align]` header stored (unaligned) before each
+/// allocation when the `canary` feature is enabled. `prev` is the offset of the
+/// previous header, or `usize::MAX` for the first allocation.
+#[cfg(feature = "canary")]
+const HEADER: usize = core::mem::size_of::<[usize; 3]>();
+
+/// Usage statistics of a buffer-backed allocator, for right-sizing its buffer.
+///
+/// Returned by [`BumpAllocator::report`], [`AtomicBumpAllocator::report`], and
+/// [`StackAllocator::report`][crate::stack::StackAllocator::report]. The
+/// [`Display`][fmt::Display] implementation is meant for logging, e.g. at
+/// shutdown.
+///
+/// Requires the `stats` feature.
+#[cfg(feature = "stats")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct UsageReport {
+    /// The total size of the buffer in bytes.
+    pub capacity: usize,
+    /// The number of bytes in use when the report was made.
+    pub used: usize,
+    /// The maximum number of bytes that were ever in use at once.
+    pub high_water_mark: usize,
+    /// The number of successful allocations.
+    pub allocations_served: usize,
+}
+
+#[cfg(feature = "stats")]
+impl fmt::Display for UsageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} bytes in use, peak {} bytes, {} allocations served",
+            self.used, self.capacity, self.high_water_mark, self.allocations_served
+        )
+    }
+}
+
+/// The error type returned by the value-placing methods of [`BumpAllocator`],
+/// such as [`BumpAllocator::alloc`], when there is not enough space left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Exhausted {
+    /// The layout of the allocation that did not fit.
+    pub layout: Layout,
+}
+
+impl fmt::Display for Exhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "arena exhausted: not enough space for {} bytes aligned to {}",
+            self.layout.size(),
+            self.layout.align()
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Exhausted {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(
+            f,
+            "arena exhausted: not enough space for {=usize} bytes aligned to {=usize}",
+            self.layout.size(),
+            self.layout.align()
+        )
+    }
+}
+
+/// A bump [`Allocator`] over a mutably borrowed byte buffer.
+///
+/// Each allocation is placed directly after the previous one (plus any padding
+/// needed for alignment). [`deallocate`][BumpAllocator::deallocate] is a
+/// no-op, so memory is only reclaimed when the `BumpAllocator` itself is
+/// dropped and the buffer borrow ends.
+///
+/// This type is not `Sync`; see [`AtomicBumpAllocator`] for a variant that can
+/// be shared between threads.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::bump::BumpAllocator;
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 256];
+/// let bump = BumpAllocator::new(&mut buf);
+///
+/// let a = Box::new_in(1u32, &bump);
+/// let mut v = Vec::with_capacity_in(4, &bump);
+/// v.extend_from_slice(&[1u16, 2, 3, 4]);
+/// assert_eq!(*a, 1);
+/// assert_eq!(v, [1, 2, 3, 4]);
+/// assert!(bump.remaining() <= 256 - 4 - 8);
+/// ```
+///
+/// # Canaries
+///
+/// With the `canary` feature, each non-zero-sized allocation is preceded by a
+/// small header and followed by a redzone of [`CANARY`] bytes
+/// ([`DEFAULT_REDZONE`] bytes long, or as given to
+/// [`with_redzone`][Self::with_redzone]). The redzone is checked when the
+/// allocation is deallocated, when a [`ScopedArena`] is reset past it, and by
+/// [`check_canaries`][Self::check_canaries], which panic with the layout of the
+/// allocation whose redzone was overwritten. The headers and redzones count
+/// towards [`used`][Self::used], so they reduce [`remaining`][Self::remaining].
+#[cfg_attr(feature = "canary", doc = "```rust,should_panic")]
+#[cfg_attr(not(feature = "canary"), doc = "```rust,ignore")]
+/// #![feature(allocator_api)]
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::bump::BumpAllocator;
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 64];
+/// let bump = BumpAllocator::with_redzone(&mut buf, 4);
+/// let mut a = Box::new_in([0u8; 4], &bump);
+/// bump.check_canaries();
+///
+/// // A buggy write one past the end of the allocation.
+/// unsafe { a.as_mut_ptr().add(4).write(0) };
+/// drop(a); // panics: the canary after `a` was overwritten
+#[doc = "```"]
+///
+/// Without the `canary` and `stats` features, there is no overhead:
+#[cfg_attr(not(any(feature = "canary", feature = "stats")), doc = "```rust")]
+#[cfg_attr(any(feature = "canary", feature = "stats"), doc = "```rust,ignore")]
+/// #![feature(allocator_api)]
+/// # use std::mem::{MaybeUninit, size_of};
+/// use noop_allocator::bump::BumpAllocator;
+/// assert_eq!(size_of::<BumpAllocator<'_>>(), 3 * size_of::<usize>());
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 8];
+/// let bump = BumpAllocator::new(&mut buf);
+/// let a = Box::new_in([0u8; 8], &bump);
+/// assert_eq!(bump.remaining(), 0);
+/// ```
+pub struct BumpAllocator<'a> {
+    start: NonNull<u8>,
+    capacity: usize,
+    offset: Cell<usize>,
+    #[cfg(feature = "canary")]
+    redzone: usize,
+    #[cfg(feature = "canary")]
+    last: Cell<usize>,
+    #[cfg(feature = "stats")]
+    peak: Cell<usize>,
+    #[cfg(feature = "stats")]
+    served: Cell<usize>,
+    _marker: PhantomData<&'a mut [MaybeUninit<u8>]>,
+}
+
+// SAFETY: A `BumpAllocator` is equivalent to the `&mut [MaybeUninit<u8>]` it
+// was created from.
+unsafe impl Send for BumpAllocator<'_> {}
+
+impl<'a> BumpAllocator<'a> {
+    /// Creates a new `BumpAllocator<'a>` allocating from `buf`.
+    pub fn new(buf: &'a mut [MaybeUninit<u8>]) -> Self {
+        Self {
+            capacity: buf.len(),
+            start: NonNull::from(buf).cast(),
+            offset: Cell::new(0),
+            #[cfg(feature = "canary")]
+            redzone: DEFAULT_REDZONE,
+            #[cfg(feature = "canary")]
+            last: Cell::new(usize::MAX),
+            #[cfg(feature = "stats")]
+            peak: Cell::new(0),
+            #[cfg(feature = "stats")]
+            served: Cell::new(0),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Creates a new `BumpAllocator<'a>` allocating from `buf`, with a redzone
+    /// of `redzone` bytes after each allocation.
+    #[cfg(feature = "canary")]
+    pub fn with_redzone(buf: &'a mut [MaybeUninit<u8>], redzone: usize) -> Self {
+        Self {
+            redzone,
+            ..Self::new(buf)
+        }
+    }
+
+    /// Checks the redzones of all live allocations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a redzone has been overwritten.
+    #[cfg(feature = "canary")]
+    pub fn check_canaries(&self) {
+        let mut header = self.last.get();
+        while header != usize::MAX {
+            header = self.check_canary(header);
+        }
+    }
+
+    /// Checks the redzone of the allocation whose header is at offset `header`,
+    /// and returns the offset of the previous header.
+    #[cfg(feature = "canary")]
+    fn check_canary(&self, header: usize) -> usize {
+        let [prev, size, align] =
+            unsafe { self.start.add(header).cast::<[usize; 3]>().read_unaligned() };
+        let redzone = unsafe {
+            core::slice::from_raw_parts(
+                self.start.add(header + HEADER + size).as_ptr(),
+                self.redzone,
+            )
+        };
+        assert!(
+            redzone.iter().all(|&b| b == CANARY),
+            "canary overwritten after an allocation of {} bytes aligned to {}",
+            size,
+            align
+        );
+        prev
+    }
+
+    /// Checks and forgets the redzones of all allocations at or after `offset`.
+    #[cfg(feature = "canary")]
+    fn release_canaries(&self, offset: usize) {
+        let mut header = self.last.get();
+        while header != usize::MAX && header >= offset {
+            header = self.check_canary(header);
+        }
+        self.last.set(header);
+    }
+
+    /// Returns the total size of the borrowed buffer in bytes.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of bytes that have been handed out so far, including
+    /// alignment padding.
+    pub fn used(&self) -> usize {
+        self.offset.get()
+    }
+
+    /// Returns the number of bytes that have not been handed out yet.
+    ///
+    /// Note that an allocation may need some of these bytes for alignment
+    /// padding.
+    pub fn remaining(&self) -> usize {
+        self.capacity - self.offset.get()
+    }
+
+    /// Returns the maximum number of bytes that were ever in use at once,
+    /// including alignment padding.
+    ///
+    /// This only differs from [`used`][Self::used] for a [`ScopedArena`] that
+    /// has been reset. Requires the `stats` feature.
+    #[cfg(feature = "stats")]
+    pub fn high_water_mark(&self) -> usize {
+        self.peak.get()
+    }
+
+    /// Returns the number of successful allocations. Requires the `stats`
+    /// feature.
+    #[cfg(feature = "stats")]
+    pub fn allocations_served(&self) -> usize {
+        self.served.get()
+    }
+
+    /// Returns the usage statistics of this allocator. Requires the `stats`
+    /// feature.
+    ///
+    /// # Examples
+    #[cfg_attr(all(feature = "stats", not(feature = "canary")), doc = "```rust")]
+    #[cfg_attr(
+        not(all(feature = "stats", not(feature = "canary"))),
+        doc = "```rust,ignore"
+    )]
+    /// #![feature(allocator_api)]
+    /// use noop_allocator::{
+    ///     buffer::AlignedBuffer,
+    ///     bump::{ScopedArena, UsageReport},
+    /// };
+    /// let mut buf = AlignedBuffer::<256, 8>::uninit();
+    /// let mut arena = ScopedArena::new(buf.as_uninit_bytes());
+    ///
+    /// arena.scope(|arena| {
+    ///     let a = Box::new_in([0u8; 100], &*arena);
+    ///     let b = Box::new_in(1u64, &*arena); // 4 bytes of padding
+    ///     assert_eq!(arena.used(), 112);
+    /// });
+    /// let c = Box::new_in([0u8; 40], &arena);
+    /// assert_eq!(
+    ///     arena.report(),
+    ///     UsageReport {
+    ///         capacity: 256,
+    ///         used: 40,
+    ///         high_water_mark: 112,
+    ///         allocations_served: 3,
+    ///     }
+    /// );
+    /// assert_eq!(
+    ///     arena.report().to_string(),
+    ///     "40/256 bytes in use, peak 112 bytes, 3 allocations served"
+    /// );
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn report(&self) -> UsageReport {
+        UsageReport {
+            capacity: self.capacity,
+            used: self.used(),
+            high_water_mark: self.high_water_mark(),
+            allocations_served: self.allocations_served(),
+        }
+    }
+
+    /// Records a successful allocation ending at `end`.
+    #[cfg_attr(not(feature = "stats"), allow(unused_variables))]
+    fn record(&self, end: usize) {
+        #[cfg(feature = "stats")]
+        {
+            self.peak.set(self.peak.get().max(end));
+            self.served.set(self.served.get() + 1);
+        }
+    }
+
+    fn alloc_layout(&self, layout: Layout) -> Result<NonNull<u8>, Exhausted> {
+        self.allocate(layout)
+            .map(NonNull::cast)
+            .map_err(|AllocError| Exhausted { layout })
+    }
+
+    /// Moves `value` into the arena and returns a mutable reference to it.
+    ///
+    /// The value is never dropped; use [`alloc_boxed`][Self::alloc_boxed] if it
+    /// needs to be. If there is not enough space, `value` is dropped and `Err`
+    /// is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::mem::MaybeUninit;
+    /// # use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use noop_allocator::bump::BumpAllocator;
+    /// static DROPS: AtomicUsize = AtomicUsize::new(0);
+    /// struct Counted(u32);
+    /// impl Drop for Counted {
+    ///     fn drop(&mut self) {
+    ///         DROPS.fetch_add(1, Ordering::Relaxed);
+    ///     }
+    /// }
+    ///
+    /// let mut buf = [MaybeUninit::<u8>::uninit(); 256];
+    /// let bump = BumpAllocator::new(&mut buf);
+    ///
+    /// let a = bump.alloc(Counted(1)).unwrap();
+    /// let b = bump.alloc_boxed(Counted(2)).unwrap();
+    /// a.0 += 10;
+    /// assert_eq!((a.0, b.0), (11, 2));
+    ///
+    /// // The boxed value is dropped, the referenced value is not.
+    /// drop(b);
+    /// assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+    /// drop(bump);
+    /// assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+    /// ```
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T>(&self, value: T) -> Result<&mut T, Exhausted> {
+        let ptr = self.alloc_layout(Layout::new::<T>())?.cast::<T>();
+        unsafe {
+            ptr.write(value);
+            Ok(&mut *ptr.as_ptr())
+        }
+    }
+
+    /// Moves `value` into the arena and returns an [`OwningRef`] to it, which
+    /// drops `value` when it is dropped.
+    ///
+    /// If there is not enough space, `value` is dropped and `Err` is returned.
+    #[cfg(feature = "alloc")]
+    pub fn alloc_boxed<T>(&self, value: T) -> Result<OwningRef<'_, T>, Exhausted> {
+        let ptr = self.alloc_layout(Layout::new::<T>())?.cast::<T>();
+        unsafe {
+            ptr.write(value);
+            Ok(OwningRef::from_raw_in(ptr.as_ptr(), NoopAllocator::new()))
+        }
+    }
+
+    /// Copies `src` into the arena and returns a mutable reference to the
+    /// copy.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::mem::MaybeUninit;
+    /// use noop_allocator::bump::BumpAllocator;
+    /// let mut buf = [MaybeUninit::<u8>::uninit(); 256];
+    /// let bump = BumpAllocator::new(&mut buf);
+    ///
+    /// let s = bump.alloc_str("hello").unwrap();
+    /// s.make_ascii_uppercase();
+    /// let xs = bump.alloc_slice_copy(&[1u8, 2, 3]).unwrap();
+    /// let ys = bump.alloc_slice_fill_with(4, |i| i as u8 * 2).unwrap();
+    /// assert_eq!(s, "HELLO");
+    /// assert_eq!(xs, [1, 2, 3]);
+    /// assert_eq!(ys, [0, 2, 4, 6]);
+    ///
+    /// let err = bump.alloc_slice_copy(&[0u8; 256]).unwrap_err();
+    /// assert_eq!(err.layout.size(), 256);
+    /// ```
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_copy<T: Copy>(&self, src: &[T]) -> Result<&mut [T], Exhausted> {
+        let ptr = self.alloc_layout(Layout::for_value(src))?.cast::<T>();
+        unsafe {
+            ptr.copy_from_nonoverlapping(NonNull::from(src).cast(), src.len());
+            Ok(core::slice::from_raw_parts_mut(ptr.as_ptr(), src.len()))
+        }
+    }
+
+    /// Copies `src` into the arena and returns a mutable reference to the
+    /// copy.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_str(&self, src: &str) -> Result<&mut str, Exhausted> {
+        let bytes = self.alloc_slice_copy(src.as_bytes())?;
+        Ok(unsafe { core::str::from_utf8_unchecked_mut(bytes) })
+    }
+
+    /// Allocates a slice of `len` elements in the arena, initializing the
+    /// element at each index `i` to `f(i)`, and returns a mutable reference to
+    /// it.
+    ///
+    /// The elements are never dropped, including if `f` panics.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size of the slice would overflow `isize::MAX`.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_fill_with<T>(
+        &self,
+        len: usize,
+        mut f: impl FnMut(usize) -> T,
+    ) -> Result<&mut [T], Exhausted> {
+        let layout = Layout::array::<T>(len).expect("capacity overflow");
+        let ptr = self.alloc_layout(layout)?.cast::<T>();
+        for i in 0..len {
+            unsafe { ptr.add(i).write(f(i)) };
+        }
+        Ok(unsafe { core::slice::from_raw_parts_mut(ptr.as_ptr(), len) })
+    }
+}
+
+unsafe impl Allocator for BumpAllocator<'_> {
+    #[cfg(not(feature = "canary"))]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let (start, end) = fit(
+            self.start.as_ptr() as usize,
+            self.capacity,
+            self.offset.get(),
+            layout,
+        )
+        .ok_or(AllocError)?;
+        self.offset.set(end);
+        self.record(end);
+        let ptr = unsafe { self.start.add(start) };
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    #[cfg(feature = "canary")]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if layout.size() == 0 {
+            self.record(self.offset.get());
+            return Ok(NonNull::slice_from_raw_parts(layout.dangling_ptr(), 0));
+        }
+        let (start, end) = fit(
+            self.start.as_ptr() as usize,
+            self.capacity,
+            self.offset.get().checked_add(HEADER).ok_or(AllocError)?,
+            layout,
+        )
+        .ok_or(AllocError)?;
+        let redzone_end = end
+            .checked_add(self.redzone)
+            .filter(|&redzone_end| redzone_end <= self.capacity)
+            .ok_or(AllocError)?;
+        let header = start - HEADER;
+        unsafe {
+            self.start
+                .add(header)
+                .cast::<[usize; 3]>()
+                .write_unaligned([self.last.get(), layout.size(), layout.align()]);
+            self.start.add(end).write_bytes(CANARY, self.redzone);
+        }
+        self.last.set(header);
+        self.offset.set(redzone_end);
+        self.record(redzone_end);
+        let ptr = unsafe { self.start.add(start) };
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    #[cfg(not(feature = "canary"))]
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
+        // intentionally empty
+    }
+
+    #[cfg(feature = "canary")]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if layout.size() != 0 {
+            self.check_canary(ptr.offset_from(self.start) as usize - HEADER);
+        }
+    }
+}
+
+/// A thread-safe bump [`Allocator`] over a mutably borrowed byte buffer.
+///
+/// This behaves like [`BumpAllocator`], but the offset is an [`AtomicUsize`]
+/// updated with a compare-exchange loop, so a shared `&AtomicBumpAllocator`
+/// can be used to allocate from several threads at once, e.g. with
+/// [`std::thread::scope`].
+///
+/// This type is only available on targets with native atomic compare-exchange,
+/// or with the `portable-atomic` feature enabled.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::bump::AtomicBumpAllocator;
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 4096];
+/// let bump = AtomicBumpAllocator::new(&mut buf);
+///
+/// let mut regions: Vec<(usize, usize)> = std::thread::scope(|s| {
+///     let handles: Vec<_> = (0..4)
+///         .map(|i| {
+///             let bump = &bump;
+///             s.spawn(move || {
+///                 let mut regions = vec![];
+///                 for j in 0..32 {
+///                     let mut v = Vec::with_capacity_in(3, bump);
+///                     v.extend([i, j, 42u64]);
+///                     let start = v.as_ptr() as usize;
+///                     regions.push((start, start + 24));
+///                     std::mem::forget(v);
+///                 }
+///                 regions
+///             })
+///         })
+///         .collect();
+///     handles
+///         .into_iter()
+///         .flat_map(|h| h.join().unwrap())
+///         .collect()
+/// });
+///
+/// assert_eq!(regions.len(), 128);
+/// regions.sort();
+/// for pair in regions.windows(2) {
+///     assert!(pair[0].1 <= pair[1].0, "regions overlap");
+/// }
+/// assert!(bump.remaining() <= 4096 - 128 * 24);
+/// ```
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+pub struct AtomicBumpAllocator<'a> {
+    start: NonNull<u8>,
+    capacity: usize,
+    offset: AtomicUsize,
+    #[cfg(feature = "stats")]
+    served: AtomicUsize,
+    _marker: PhantomData<&'a mut [MaybeUninit<u8>]>,
+}
+
+// SAFETY: An `AtomicBumpAllocator` is equivalent to the `&mut
+// [MaybeUninit<u8>]` it was created from, and only hands out disjoint regions
+// of it through `&self`.
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+unsafe impl Send for AtomicBumpAllocator<'_> {}
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+unsafe impl Sync for AtomicBumpAllocator<'_> {}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<'a> AtomicBumpAllocator<'a> {
+    /// Creates a new `AtomicBumpAllocator<'a>` allocating from `buf`.
+    pub fn new(buf: &'a mut [MaybeUninit<u8>]) -> Self {
+        Self {
+            capacity: buf.len(),
+            start: NonNull::from(buf).cast(),
+            offset: AtomicUsize::new(0),
+            #[cfg(feature = "stats")]
+            served: AtomicUsize::new(0),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the total size of the borrowed buffer in bytes.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of bytes that have been handed out so far, including
+    /// alignment padding.
+    pub fn used(&self) -> usize {
+        self.offset.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of bytes that have not been handed out yet.
+    ///
+    /// Note that an allocation may need some of these bytes for alignment
+    /// padding.
+    pub fn remaining(&self) -> usize {
+        self.capacity - self.offset.load(Ordering::Relaxed)
+    }
+
+    /// Returns the maximum number of bytes that were ever in use at once.
+    ///
+    /// An `AtomicBumpAllocator` cannot be reset, so this is the same as
+    /// [`used`][Self::used]. Requires the `stats` feature.
+    #[cfg(feature = "stats")]
+    pub fn high_water_mark(&self) -> usize {
+        self.used()
+    }
+
+    /// Returns the number of successful allocations. Requires the `stats`
+    /// feature.
+    ///
+    /// The counter is updated separately from the offset, so while other
+    /// threads are allocating, it may lag behind [`used`][Self::used].
+    #[cfg(feature = "stats")]
+    pub fn allocations_served(&self) -> usize {
+        self.served.load(Ordering::Relaxed)
+    }
+
+    /// Returns the usage statistics of this allocator. Requires the `stats`
+    /// feature.
+    ///
+    /// While other threads are allocating, the fields are read at slightly
+    /// different times, so they are approximate.
+    #[cfg(feature = "stats")]
+    pub fn report(&self) -> UsageReport {
+        UsageReport {
+            capacity: self.capacity,
+            used: self.used(),
+            high_water_mark: self.high_water_mark(),
+            allocations_served: self.allocations_served(),
+        }
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+unsafe impl Allocator for AtomicBumpAllocator<'_> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let base = self.start.as_ptr() as usize;
+        let mut current = self.offset.load(Ordering::Relaxed);
+        let start = loop {
+            let (start, end) = fit(base, self.capacity, current, layout).ok_or(AllocError)?;
+            match self.offset.compare_exchange_weak(
+                current,
+                end,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break start,
+                Err(actual) => current = actual,
+            }
+        };
+        #[cfg(feature = "stats")]
+        self.served.fetch_add(1, Ordering::Relaxed);
+        let ptr = unsafe { self.start.add(start) };
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
+        // intentionally empty
+    }
+}
+
+/// A static byte buffer that can be claimed once as a
+/// [`BumpAllocator<'static>`][BumpAllocator].
+///
+/// A `&'static mut` to a `static` cannot be created without `unsafe`, since
+/// nothing stops two places from doing it. A `StaticArena` wraps a
+/// [`StaticAlignedBuffer`], which guards its bytes with an atomic flag instead:
+/// the first call to [`claim`][Self::claim] returns the allocator, and every
+/// later call returns `None`. The buffer is aligned to `ALIGN`, which must be a
+/// power of two from 1 to 4096.
+///
+/// Because the allocator borrows the buffer for `'static`, collections using it
+/// by value are `'static` too, so they can be stored in other statics or moved
+/// to spawned threads.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// use noop_allocator::bump::{BumpAllocator, StaticArena};
+///
+/// static ARENA: StaticArena<8192> = StaticArena::new();
+///
+/// let bump = ARENA.claim().unwrap();
+/// assert!(ARENA.claim().is_none());
+/// assert_eq!(bump.capacity(), 8192);
+///
+/// let mut v: Vec<u32, BumpAllocator<'static>> = Vec::with_capacity_in(16, bump);
+/// v.extend(0..16);
+/// std::thread::spawn(move || assert_eq!(v.iter().sum::<u32>(), 120))
+///     .join()
+///     .unwrap();
+/// ```
+///
+/// The alignment of the buffer can be raised with the second parameter:
+///
+/// ```rust
+/// use noop_allocator::bump::StaticArena;
+/// static ARENA: StaticArena<256, 4096> = StaticArena::new();
+/// let bytes = ARENA.claim_bytes().unwrap();
+/// assert_eq!(bytes.as_ptr() as usize % 4096, 0);
+/// ```
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+pub struct StaticArena<const N: usize, const ALIGN: usize = 16>
+where
+    Align<ALIGN>: SupportedAlignment,
+{
+    buf: StaticAlignedBuffer<N, ALIGN>,
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<const N: usize, const ALIGN: usize> StaticArena<N, ALIGN>
+where
+    Align<ALIGN>: SupportedAlignment,
+{
+    /// Creates a new, unclaimed `StaticArena` of `N` bytes.
+    pub const fn new() -> Self {
+        Self {
+            buf: StaticAlignedBuffer::new(),
+        }
+    }
+
+    /// Claims the buffer, returning a [`BumpAllocator`] over it, or `None` if
+    /// it was already claimed.
+    pub fn claim(&'static self) -> Option<BumpAllocator<'static>> {
+        self.buf.take().map(BumpAllocator::new)
+    }
+
+    /// Claims the buffer, returning it as bytes, or `None` if it was already
+    /// claimed.
+    ///
+    /// Use this to build an allocator other than [`BumpAllocator`] over the
+    /// buffer. This is [`StaticAlignedBuffer::take`].
+    pub fn claim_bytes(&'static self) -> Option<&'static mut [MaybeUninit<u8>]> {
+        self.buf.take()
+    }
+
+    /// Returns `true` if the buffer has been claimed.
+    pub fn is_claimed(&self) -> bool {
+        self.buf.is_taken()
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<const N: usize, const ALIGN: usize> Default for StaticArena<N, ALIGN>
+where
+    Align<ALIGN>: SupportedAlignment,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A position in a [`ScopedArena`] that it can later be reset to.
+///
+/// Created by [`ScopedArena::checkpoint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mark(usize);
+
+/// A bump [`Allocator`] over a mutably borrowed byte buffer that can be reset
+/// to a previous [`Mark`], e.g. at the end of each frame.
+///
+/// Allocating only requires `&ScopedArena`, but resetting requires `&mut
+/// ScopedArena`, so the borrow checker ensures that no allocation made after
+/// the mark is still alive when the arena is reset.
+///
+/// `ScopedArena` dereferences to [`BumpAllocator`], so its value-placing
+/// methods such as [`alloc`][BumpAllocator::alloc] can be used directly.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::bump::ScopedArena;
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 64];
+/// let mut arena = ScopedArena::new(&mut buf);
+///
+/// for frame in 0..100u64 {
+///     arena.scope(|arena| {
+///         let mut v = Vec::with_capacity_in(4, &*arena);
+///         v.extend([frame; 4]);
+///         assert_eq!(v.iter().sum::<u64>(), frame * 4);
+///     });
+///     assert_eq!(arena.used(), 0);
+/// }
+///
+/// let mark = arena.checkpoint();
+/// let b = Box::new_in(42u32, &arena);
+/// assert_eq!(*b, 42);
+/// drop(b);
+/// arena.reset_to(mark);
+/// assert_eq!(arena.used(), 0);
+/// ```
+///
+/// Allocations cannot escape a scope:
+///
+/// ```rust,compile_fail
+/// #![feature(allocator_api)]
+/// # use std::mem::MaybeUninit;
+/// # use noop_allocator::bump::ScopedArena;
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 64];
+/// let mut arena = ScopedArena::new(&mut buf);
+/// let escaped = arena.scope(|arena| Box::new_in(42u32, &*arena));
+/// ```
+///
+/// The arena cannot be reset while an allocation from it is alive:
+///
+/// ```rust,compile_fail,E0502
+/// #![feature(allocator_api)]
+/// # use std::mem::MaybeUninit;
+/// # use noop_allocator::bump::ScopedArena;
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 64];
+/// let mut arena = ScopedArena::new(&mut buf);
+/// let mark = arena.checkpoint();
+/// let b = Box::new_in(42u32, &arena);
+/// arena.reset_to(mark);
+/// drop(b);
+/// ```
+///
+/// Including by entering a nested scope:
+///
+/// ```rust,compile_fail,E0502
+/// #![feature(allocator_api)]
+/// # use std::mem::MaybeUninit;
+/// # use noop_allocator::bump::ScopedArena;
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 64];
+/// let mut arena = ScopedArena::new(&mut buf);
+/// arena.scope(|outer| {
+///     let b = Box::new_in(42u32, &*outer);
+///     outer.scope(|inner| {});
+///     drop(b);
+/// });
+/// ```
+pub struct ScopedArena<'a> {
+    bump: BumpAllocator<'a>,
+}
+
+impl<'a> ScopedArena<'a> {
+    /// Creates a new `ScopedArena<'a>` allocating from `buf`.
+    pub fn new(buf: &'a mut [MaybeUninit<u8>]) -> Self {
+        Self {
+            bump: BumpAllocator::new(buf),
+        }
+    }
+
+    /// Creates a new `ScopedArena<'a>` allocating from `buf`, with a redzone of
+    /// `redzone` bytes after each allocation. See
+    /// [`BumpAllocator::with_redzone`].
+    #[cfg(feature = "canary")]
+    pub fn with_redzone(buf: &'a mut [MaybeUninit<u8>], redzone: usize) -> Self {
+        Self {
+            bump: BumpAllocator::with_redzone(buf, redzone),
+        }
+    }
+
+    /// Returns the total size of the borrowed buffer in bytes.
+    pub fn capacity(&self) -> usize {
+        self.bump.capacity()
+    }
+
+    /// Returns the number of bytes that have been handed out since the arena
+    /// was created or last reset, including alignment padding.
+    pub fn used(&self) -> usize {
+        self.bump.used()
+    }
+
+    /// Returns the number of bytes that have not been handed out yet.
+    pub fn remaining(&self) -> usize {
+        self.bump.remaining()
+    }
+
+    /// Returns a [`Mark`] for the current position of the arena.
+    pub fn checkpoint(&self) -> Mark {
+        Mark(self.bump.offset.get())
+    }
+
+    /// Resets the arena to `mark`, making the memory allocated after it
+    /// available again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mark` is past the end of the buffer, which can only happen if
+    /// it was created by a different arena.
+    ///
+    /// With the `canary` feature, also panics if the redzone of an allocation
+    /// made after `mark` has been overwritten.
+    pub fn reset_to(&mut self, mark: Mark) {
+        assert!(mark.0 <= self.bump.capacity, "mark is out of bounds");
+        #[cfg(feature = "canary")]
+        self.bump.release_canaries(mark.0);
+        self.bump.offset.set(mark.0);
+    }
+
+    /// Runs `f` with this arena, then resets the arena to where it was before
+    /// `f` was called, even if `f` panics.
+    pub fn scope<R>(&mut self, f: impl FnOnce(&mut ScopedArena<'a>) -> R) -> R {
+        struct ResetOnDrop<'r, 'a>(&'r mut ScopedArena<'a>, Mark);
+        impl Drop for ResetOnDrop<'_, '_> {
+            fn drop(&mut self) {
+                self.0.reset_to(self.1);
+            }
+        }
+
+        let mark = self.checkpoint();
+        let guard = ResetOnDrop(self, mark);
+        f(guard.0)
+    }
+}
+
+impl<'a> Deref for ScopedArena<'a> {
+    type Target = BumpAllocator<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.bump
+    }
+}
+
+unsafe impl Allocator for ScopedArena<'_> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.bump.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.bump.deallocate(ptr, layout)
+    }
+}
+
+/// A scratch arena for per-frame allocations that detects, in debug builds,
+/// handles retained across a [`reset`][ScratchBuffer::reset].
+///
+/// Values are placed in the arena with [`alloc`][ScratchBuffer::alloc], which
+/// returns a [`ScratchHandle`] rather than a reference, so the arena can be
+/// reset while handles still exist. Each reset starts a new generation; in
+/// debug builds, every handle records the generation it was allocated in, and
+/// [`get`][ScratchBuffer::get], [`get_mut`][ScratchBuffer::get_mut], and
+/// [`validate`][ScratchBuffer::validate] panic with both generations when
+/// given a handle from an earlier one. In release builds, handles carry no
+/// generation and the checks compile away, so using a stale handle is
+/// undefined behavior; that is why the accessors are `unsafe`.
+///
+/// Values in the arena are never dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::bump::ScratchBuffer;
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 256];
+/// let mut scratch = ScratchBuffer::new(&mut buf);
+///
+/// // Frame 1.
+/// let mut position = scratch.alloc([1.0f32, 2.0]).unwrap();
+/// unsafe { scratch.get_mut(&mut position)[0] += 1.0 };
+/// assert_eq!(unsafe { scratch.get(&position) }, &[2.0, 2.0]);
+/// let retained = position; // a bug: kept for the next frame
+///
+/// // Frame 2.
+/// scratch.reset();
+/// assert_eq!(scratch.generation(), 1);
+/// let velocity = scratch.alloc([0.5f32, 0.5]).unwrap();
+/// scratch.validate(&velocity);
+/// ```
+///
+/// In debug builds, the retained handle is detected:
+#[cfg_attr(debug_assertions, doc = "```rust")]
+#[cfg_attr(not(debug_assertions), doc = "```rust,ignore")]
+/// # use std::mem::MaybeUninit;
+/// # use std::panic::{AssertUnwindSafe, catch_unwind};
+/// use noop_allocator::bump::ScratchBuffer;
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 256];
+/// let mut scratch = ScratchBuffer::new(&mut buf);
+///
+/// let retained = scratch.alloc(1u32).unwrap();
+/// scratch.reset();
+/// let result = catch_unwind(AssertUnwindSafe(|| scratch.validate(&retained)));
+/// let message = *result.unwrap_err().downcast::<String>().unwrap();
+/// assert_eq!(
+///     message,
+///     "stale scratch handle: allocated in generation 0, used in generation 1"
+/// );
+/// ```
+pub struct ScratchBuffer<'a> {
+    arena: ScopedArena<'a>,
+    generation: u64,
+}
+
+/// A value in a [`ScratchBuffer`], created by [`ScratchBuffer::alloc`].
+///
+/// In debug builds, the handle records the generation of the buffer it was
+/// allocated in.
+pub struct ScratchHandle<'a, T> {
+    ptr: NonNull<T>,
+    #[cfg(debug_assertions)]
+    generation: u64,
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<T> fmt::Debug for ScratchHandle<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut f = f.debug_struct("ScratchHandle");
+        f.field("ptr", &self.ptr);
+        #[cfg(debug_assertions)]
+        f.field("generation", &self.generation);
+        f.finish()
+    }
+}
+
+impl<'a> ScratchBuffer<'a> {
+    /// Creates a new `ScratchBuffer<'a>` allocating from `buf`, at generation
+    /// 0.
+    pub fn new(buf: &'a mut [MaybeUninit<u8>]) -> Self {
+        Self {
+            arena: ScopedArena::new(buf),
+            generation: 0,
+        }
+    }
+
+    /// Returns the current generation, i.e. the number of times the buffer has
+    /// been reset.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns the number of bytes used in the current generation, including
+    /// alignment padding.
+    pub fn used(&self) -> usize {
+        self.arena.used()
+    }
+
+    /// Returns the number of bytes that have not been handed out yet in the
+    /// current generation.
+    pub fn remaining(&self) -> usize {
+        self.arena.remaining()
+    }
+
+    /// Makes the whole buffer available again and starts a new generation.
+    ///
+    /// Handles allocated before the reset become stale.
+    pub fn reset(&mut self) {
+        self.arena.reset_to(Mark(0));
+        self.generation += 1;
+    }
+
+    /// Moves `value` into the buffer and returns a handle to it.
+    ///
+    /// If there is not enough space, `value` is dropped and `Err` is returned.
+    pub fn alloc<T>(&self, value: T) -> Result<ScratchHandle<'a, T>, Exhausted> {
+        let ptr = NonNull::from(self.arena.alloc(value)?);
+        Ok(ScratchHandle {
+            ptr,
+            #[cfg(debug_assertions)]
+            generation: self.generation,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Checks that `handle` was allocated in the current generation.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics if `handle` is stale. In release builds, this
+    /// does nothing.
+    #[track_caller]
+    #[cfg_attr(not(debug_assertions), allow(unused_variables))]
+    pub fn validate<T>(&self, handle: &ScratchHandle<'a, T>) {
+        #[cfg(debug_assertions)]
+        if handle.generation != self.generation {
+            panic!(
+                "stale scratch handle: allocated in generation {}, used in generation {}",
+                handle.generation, self.generation
+            );
+        }
+    }
+
+    /// Returns a reference to the value of `handle`.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must have been allocated by this buffer in the current
+    /// generation. This is checked in debug builds, see
+    /// [`validate`][Self::validate].
+    #[track_caller]
+    pub unsafe fn get<'s, T>(&'s self, handle: &'s ScratchHandle<'a, T>) -> &'s T {
+        self.validate(handle);
+        handle.ptr.as_ref()
+    }
+
+    /// Returns a mutable reference to the value of `handle`.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must have been allocated by this buffer in the current
+    /// generation. This is checked in debug builds, see
+    /// [`validate`][Self::validate].
+    #[track_caller]
+    pub unsafe fn get_mut<'s, T>(&'s self, handle: &'s mut ScratchHandle<'a, T>) -> &'s mut T {
+        self.validate(handle);
+        handle.ptr.as_mut()
+    }
+}
+
+impl fmt::Debug for ScratchBuffer<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScratchBuffer")
+            .field("generation", &self.generation)
+            .field("used", &self.used())
+            .field("capacity", &self.arena.capacity())
+            .finish()
+    }
+}
@@ -0,0 +1,146 @@
+//! An allocator over several mutably borrowed byte buffers, used in order.
+use core::{
+    alloc::{AllocError, Allocator, Layout},
+    mem::MaybeUninit,
+    ptr::NonNull,
+};
+
+use crate::stack::StackAllocator;
+
+/// An [`Allocator`] over `N` mutably borrowed byte buffers ("regions"), which
+/// allocates from the first region that has space.
+///
+/// Each region is managed like a [`StackAllocator`]. Allocations stay in the
+/// region they were allocated from when they are shrunk, and when they are
+/// grown if there is space in that region; otherwise they are moved to the
+/// first region with space.
+///
+/// This is useful e.g. to prefer a small, fast memory region and overflow into
+/// a larger, slower one.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::chain::ChainAllocator;
+/// let mut fast = [MaybeUninit::<u8>::uninit(); 32];
+/// let mut slow = [MaybeUninit::<u8>::uninit(); 256];
+/// let chain = ChainAllocator::new([&mut fast, &mut slow]);
+///
+/// let a = Box::new_in([1u8; 8], &chain);
+/// assert_eq!(chain.region_of(a.as_ptr()), Some(0));
+///
+/// // Doesn't fit in the first region, so it overflows into the second.
+/// let mut v = Vec::with_capacity_in(32, &chain);
+/// v.extend(0..32u32);
+/// assert_eq!(chain.region_of(v.as_ptr().cast()), Some(1));
+/// let [_, slow_remaining] = chain.remaining();
+///
+/// // Shrinking keeps it in the second region, and reclaims space there.
+/// v.truncate(2);
+/// v.shrink_to_fit();
+/// assert_eq!(chain.region_of(v.as_ptr().cast()), Some(1));
+/// assert_eq!(v, [0, 1]);
+/// assert_eq!(chain.remaining()[1], slow_remaining + 30 * 4);
+/// ```
+pub struct ChainAllocator<'a, const N: usize> {
+    regions: [StackAllocator<'a>; N],
+}
+
+impl<'a, const N: usize> ChainAllocator<'a, N> {
+    /// Creates a new `ChainAllocator<'a, N>` allocating from `regions`, in
+    /// order.
+    pub fn new(regions: [&'a mut [MaybeUninit<u8>]; N]) -> Self {
+        Self {
+            regions: regions.map(StackAllocator::new),
+        }
+    }
+
+    /// Returns the number of bytes that have not been handed out yet in each
+    /// region.
+    pub fn remaining(&self) -> [usize; N] {
+        core::array::from_fn(|i| self.regions[i].remaining())
+    }
+
+    /// Returns the index of the region that `ptr` points into, if any.
+    pub fn region_of(&self, ptr: *const u8) -> Option<usize> {
+        let ptr = NonNull::new(ptr.cast_mut())?;
+        self.regions.iter().position(|region| region.contains(ptr))
+    }
+
+    /// Returns the region that the allocation at `ptr` with `layout` is in.
+    /// Zero-sized allocations are not in any region, and are handed out (and
+    /// released) by the first one.
+    fn region(&self, ptr: NonNull<u8>, layout: Layout) -> &StackAllocator<'a> {
+        if layout.size() == 0 {
+            return &self.regions[0];
+        }
+        let index = self.region_of(ptr.as_ptr());
+        &self.regions[index.expect("pointer was not allocated by this allocator")]
+    }
+
+    /// Moves an allocation to the first region with space for `new_layout`.
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        zeroed: bool,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let new_ptr = if zeroed {
+            self.allocate_zeroed(new_layout)?
+        } else {
+            self.allocate(new_layout)?
+        };
+        let count = old_layout.size().min(new_layout.size());
+        new_ptr.cast::<u8>().copy_from_nonoverlapping(ptr, count);
+        self.deallocate(ptr, old_layout);
+        Ok(new_ptr)
+    }
+}
+
+unsafe impl<const N: usize> Allocator for ChainAllocator<'_, N> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.regions
+            .iter()
+            .find_map(|region| region.allocate(layout).ok())
+            .ok_or(AllocError)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.region(ptr, layout).deallocate(ptr, layout)
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.region(ptr, old_layout)
+            .grow(ptr, old_layout, new_layout)
+            .or_else(|AllocError| self.reallocate(ptr, old_layout, new_layout, false))
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.region(ptr, old_layout)
+            .grow_zeroed(ptr, old_layout, new_layout)
+            .or_else(|AllocError| self.reallocate(ptr, old_layout, new_layout, true))
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.region(ptr, old_layout)
+            .shrink(ptr, old_layout, new_layout)
+    }
+}
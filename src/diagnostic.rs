@@ -0,0 +1,985 @@
+//! Variants of [`NoopAllocator`] that report rejected allocation requests.
+use core::{
+    alloc::{AllocError, Allocator, Layout},
+    cell::Cell,
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ptr::NonNull,
+};
+
+use crate::NoopAllocator;
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+use crate::sync::{AtomicUsize, Ordering};
+
+/// An [`Allocator`] that behaves like [`NoopAllocator`], except that it panics
+/// instead of returning `Err`.
+///
+/// When a collection using a `NoopAllocator` needs to allocate, the failure
+/// usually aborts the process via
+/// [`handle_alloc_error`][alloc::alloc::handle_alloc_error] with no context.
+/// With a `PanicAllocator`, the panic message includes the operation and the
+/// requested layout instead, and can be caught in tests to assert that a code
+/// path never allocates.
+///
+/// Zero-sized allocations still succeed.
+///
+/// The allocator methods are `#[track_caller]`, but collections generally call
+/// them from inside their own (non-`track_caller`) growth paths, so the
+/// reported location usually points into `alloc` rather than user code.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::{mem::MaybeUninit, panic};
+/// use noop_allocator::diagnostic::PanicAllocator;
+/// let mut buf = [MaybeUninit::<u32>::uninit(); 2];
+/// let mut v = unsafe {
+///     Vec::from_raw_parts_in(buf.as_mut_ptr().cast::<u32>(), 0, 2, PanicAllocator::new())
+/// };
+/// v.push(1);
+/// v.push(2);
+///
+/// let err = panic::catch_unwind(panic::AssertUnwindSafe(|| v.push(3))).unwrap_err();
+/// let msg = err.downcast_ref::<String>().unwrap();
+/// assert!(msg.contains("grow"), "{msg}");
+/// assert!(msg.contains("from 8 to 16 bytes"), "{msg}");
+/// assert!(msg.contains("alignment 4"), "{msg}");
+/// assert_eq!(v, [1, 2]);
+///
+/// let err = panic::catch_unwind(|| Box::new_in(0u64, PanicAllocator::new())).unwrap_err();
+/// let msg = err.downcast_ref::<String>().unwrap();
+/// assert!(msg.contains("allocate 8 bytes with alignment 8"), "{msg}");
+///
+/// // Zero-sized allocations succeed silently.
+/// let unit = Box::new_in((), PanicAllocator::new());
+/// let empty: Vec<u32, _> = Vec::with_capacity_in(0, PanicAllocator::new());
+/// ```
+#[repr(transparent)]
+pub struct PanicAllocator<'a>(PhantomData<&'a ()>);
+
+impl<'a> PanicAllocator<'a> {
+    /// Creates a new `PanicAllocator<'a>`.
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl Default for PanicAllocator<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl Allocator for PanicAllocator<'_> {
+    #[track_caller]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        match NoopAllocator::new().allocate(layout) {
+            Ok(ptr) => Ok(ptr),
+            Err(AllocError) => panic!(
+                "PanicAllocator: attempted to allocate {} bytes with alignment {}",
+                layout.size(),
+                layout.align()
+            ),
+        }
+    }
+
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
+        // intentionally empty
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        match NoopAllocator::new().grow(ptr, old_layout, new_layout) {
+            Ok(ptr) => Ok(ptr),
+            Err(AllocError) => panic!(
+                "PanicAllocator: attempted to grow an allocation from {} to {} bytes with \
+                 alignment {}",
+                old_layout.size(),
+                new_layout.size(),
+                new_layout.align()
+            ),
+        }
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        match NoopAllocator::new().grow_zeroed(ptr, old_layout, new_layout) {
+            Ok(ptr) => Ok(ptr),
+            Err(AllocError) => panic!(
+                "PanicAllocator: attempted to grow an allocation from {} to {} bytes with \
+                 alignment {}",
+                old_layout.size(),
+                new_layout.size(),
+                new_layout.align()
+            ),
+        }
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        match NoopAllocator::new().shrink(ptr, old_layout, new_layout) {
+            Ok(ptr) => Ok(ptr),
+            Err(AllocError) => panic!(
+                "PanicAllocator: attempted to shrink an allocation from {} to {} bytes with \
+                 alignment {}",
+                old_layout.size(),
+                new_layout.size(),
+                new_layout.align()
+            ),
+        }
+    }
+}
+
+/// An [`Allocator`] that behaves like [`NoopAllocator`], but counts the
+/// allocation requests it rejects.
+///
+/// Every rejected [`allocate`][Allocator::allocate] or
+/// [`grow`][Allocator::grow] call increments [`rejections`][Self::rejections]
+/// and adds the requested size in bytes (the new size, for `grow`) to
+/// [`bytes_requested`][Self::bytes_requested]. The counters are atomic, so a
+/// `&CountingNoopAllocator` can be given to collections and inspected or
+/// [`reset`][Self::reset] while they are still alive.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::diagnostic::CountingNoopAllocator;
+/// let counter = CountingNoopAllocator::new();
+///
+/// // Zero-sized allocations are not rejected.
+/// let mut v: Vec<u32, _> = Vec::new_in(&counter);
+/// assert_eq!((counter.rejections(), counter.bytes_requested()), (0, 0));
+///
+/// assert!(v.try_reserve_exact(4).is_err());
+/// assert_eq!((counter.rejections(), counter.bytes_requested()), (1, 16));
+///
+/// let mut buf = [MaybeUninit::<u32>::uninit(); 2];
+/// let mut w = unsafe { Vec::from_raw_parts_in(buf.as_mut_ptr().cast::<u32>(), 0, 2, &counter) };
+/// w.extend([1, 2]);
+/// assert!(w.try_reserve_exact(1).is_err());
+/// assert_eq!((counter.rejections(), counter.bytes_requested()), (2, 28));
+///
+/// counter.reset();
+/// assert!(v.try_reserve(1).is_err());
+/// assert_eq!(counter.rejections(), 1);
+/// assert!(counter.bytes_requested() >= 4);
+/// ```
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+pub struct CountingNoopAllocator<'a> {
+    rejections: AtomicUsize,
+    bytes_requested: AtomicUsize,
+    _marker: PhantomData<&'a ()>,
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<'a> CountingNoopAllocator<'a> {
+    /// Creates a new `CountingNoopAllocator<'a>` with both counters at zero.
+    pub const fn new() -> Self {
+        Self {
+            rejections: AtomicUsize::new(0),
+            bytes_requested: AtomicUsize::new(0),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of rejected allocation requests since creation or
+    /// the last [`reset`][Self::reset].
+    pub fn rejections(&self) -> usize {
+        self.rejections.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total size in bytes of rejected allocation requests since
+    /// creation or the last [`reset`][Self::reset].
+    pub fn bytes_requested(&self) -> usize {
+        self.bytes_requested.load(Ordering::Relaxed)
+    }
+
+    /// Resets both counters to zero.
+    pub fn reset(&self) {
+        self.rejections.store(0, Ordering::Relaxed);
+        self.bytes_requested.store(0, Ordering::Relaxed);
+    }
+
+    fn reject(&self, size: usize) -> AllocError {
+        self.rejections.fetch_add(1, Ordering::Relaxed);
+        self.bytes_requested.fetch_add(size, Ordering::Relaxed);
+        AllocError
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl Default for CountingNoopAllocator<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+unsafe impl Allocator for CountingNoopAllocator<'_> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        NoopAllocator::new()
+            .allocate(layout)
+            .map_err(|_| self.reject(layout.size()))
+    }
+
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
+        // intentionally empty
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        NoopAllocator::new()
+            .grow(ptr, old_layout, new_layout)
+            .map_err(|_| self.reject(new_layout.size()))
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        NoopAllocator::new()
+            .grow_zeroed(ptr, old_layout, new_layout)
+            .map_err(|_| self.reject(new_layout.size()))
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        NoopAllocator::new().shrink(ptr, old_layout, new_layout)
+    }
+}
+
+/// The [`Allocator`] operation that was rejected, as reported to the hook of a
+/// [`HookedNoopAllocator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum AllocOp {
+    /// [`Allocator::allocate`] or [`Allocator::allocate_zeroed`].
+    Allocate,
+    /// [`Allocator::grow`].
+    Grow,
+    /// [`Allocator::grow_zeroed`].
+    GrowZeroed,
+    /// [`Allocator::shrink`].
+    Shrink,
+}
+
+/// An [`Allocator`] that behaves like [`NoopAllocator`], but calls a hook
+/// whenever it rejects an allocation request.
+///
+/// The hook is called with the requested layout (the new layout, for `grow`,
+/// `grow_zeroed`, and `shrink`) and the rejected [`AllocOp`], just before the
+/// `Err` is returned. It cannot change the outcome: it only gets the layout and
+/// operation, not the allocator or the pointer, and the request fails
+/// regardless of what it does.
+///
+/// A `HookedNoopAllocator` only stores the hook, so it is zero-sized if the
+/// hook is (e.g. a closure that captures nothing, or only references to
+/// statics).
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::{alloc::Layout, cell::RefCell, mem::MaybeUninit};
+/// use noop_allocator::diagnostic::{AllocOp, HookedNoopAllocator};
+/// let calls = RefCell::new(Vec::new());
+/// let alloc = HookedNoopAllocator::new(|layout: Layout, op: AllocOp| {
+///     calls.borrow_mut().push((layout.size(), op));
+/// });
+///
+/// let mut buf = [MaybeUninit::<u16>::uninit(); 2];
+/// let mut v = unsafe { Vec::from_raw_parts_in(buf.as_mut_ptr().cast::<u16>(), 0, 2, &alloc) };
+/// v.extend([1, 2]);
+/// assert!(calls.borrow().is_empty());
+/// assert!(v.try_reserve_exact(1).is_err());
+///
+/// let w: Vec<u16, _> = Vec::new_in(&alloc);
+/// assert!(Box::try_new_in(0u64, &alloc).is_err());
+/// assert_eq!(
+///     *calls.borrow(),
+///     [(6, AllocOp::Grow), (8, AllocOp::Allocate)]
+/// );
+///
+/// // Hooks that capture nothing take up no space.
+/// fn breakpoint(_: Layout, _: AllocOp) {}
+/// assert_eq!(
+///     std::mem::size_of_val(&HookedNoopAllocator::new(breakpoint)),
+///     0
+/// );
+/// ```
+pub struct HookedNoopAllocator<'a, F: Fn(Layout, AllocOp)> {
+    hook: F,
+    _marker: PhantomData<&'a ()>,
+}
+
+impl<'a, F: Fn(Layout, AllocOp)> HookedNoopAllocator<'a, F> {
+    /// Creates a new `HookedNoopAllocator<'a, F>` calling `hook` on every
+    /// rejected request.
+    pub const fn new(hook: F) -> Self {
+        Self {
+            hook,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Consumes the allocator, returning the hook.
+    pub fn into_hook(self) -> F {
+        self.hook
+    }
+
+    fn reject(&self, layout: Layout, op: AllocOp) -> AllocError {
+        (self.hook)(layout, op);
+        AllocError
+    }
+}
+
+unsafe impl<F: Fn(Layout, AllocOp)> Allocator for HookedNoopAllocator<'_, F> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        NoopAllocator::new()
+            .allocate(layout)
+            .map_err(|_| self.reject(layout, AllocOp::Allocate))
+    }
+
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
+        // intentionally empty
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        NoopAllocator::new()
+            .grow(ptr, old_layout, new_layout)
+            .map_err(|_| self.reject(new_layout, AllocOp::Grow))
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        NoopAllocator::new()
+            .grow_zeroed(ptr, old_layout, new_layout)
+            .map_err(|_| self.reject(new_layout, AllocOp::GrowZeroed))
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        NoopAllocator::new()
+            .shrink(ptr, old_layout, new_layout)
+            .map_err(|_| self.reject(new_layout, AllocOp::Shrink))
+    }
+}
+
+/// A rejected allocation request, as recorded by a [`TracingNoopAllocator`].
+///
+/// This is `#[repr(C)]` (and [`AllocOp`] is `#[repr(u8)]`), so a trace can be
+/// read directly from memory with a debugger or from a core dump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct TraceEntry {
+    /// The requested size in bytes (the new size, for `grow` and `shrink`).
+    pub size: usize,
+    /// The requested alignment.
+    pub align: usize,
+    /// The rejected operation.
+    pub op: AllocOp,
+}
+
+/// An [`Allocator`] that behaves like [`NoopAllocator`], but records each
+/// rejected request into a borrowed ring buffer of [`TraceEntry`]s.
+///
+/// When the ring buffer is full, the oldest entry is overwritten. The buffer is
+/// written in place: the entry for the `n`th rejection (counting from 0) is at
+/// index `n % capacity`, so a debugger can find the newest entry from
+/// [`total`][Self::total].
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::diagnostic::{AllocOp, TraceEntry, TracingNoopAllocator};
+/// let mut ring = [MaybeUninit::uninit(); 3];
+/// let tracer = TracingNoopAllocator::new(&mut ring);
+///
+/// for size in 1..=5 {
+///     assert!(Box::<[u8], _>::try_new_uninit_slice_in(size, &tracer).is_err());
+/// }
+/// assert_eq!(tracer.total(), 5);
+///
+/// let entries: Vec<TraceEntry> = tracer.entries().collect();
+/// let sizes: Vec<usize> = entries.iter().map(|entry| entry.size).collect();
+/// assert_eq!(sizes, [3, 4, 5]);
+/// assert!(
+///     entries
+///         .iter()
+///         .all(|entry| entry.align == 1 && entry.op == AllocOp::Allocate)
+/// );
+///
+/// tracer.clear();
+/// assert_eq!(tracer.entries().count(), 0);
+/// ```
+pub struct TracingNoopAllocator<'a> {
+    ring: &'a [Cell<MaybeUninit<TraceEntry>>],
+    total: Cell<usize>,
+}
+
+impl<'a> TracingNoopAllocator<'a> {
+    /// Creates a new `TracingNoopAllocator<'a>` recording into `ring`.
+    pub fn new(ring: &'a mut [MaybeUninit<TraceEntry>]) -> Self {
+        Self {
+            ring: Cell::from_mut(ring).as_slice_of_cells(),
+            total: Cell::new(0),
+        }
+    }
+
+    /// Returns the number of rejected requests since creation or the last
+    /// [`clear`][Self::clear], including those whose entries were overwritten.
+    pub fn total(&self) -> usize {
+        self.total.get()
+    }
+
+    /// Returns the retained entries, oldest first.
+    pub fn entries(&self) -> impl Iterator<Item = TraceEntry> + '_ {
+        let total = self.total.get();
+        let len = total.min(self.ring.len());
+        (total - len..total).map(|n| {
+            // SAFETY: The last `len` entries have been written.
+            unsafe { self.ring[n % self.ring.len()].get().assume_init() }
+        })
+    }
+
+    /// Forgets all recorded entries.
+    pub fn clear(&self) {
+        self.total.set(0);
+    }
+
+    fn reject(&self, layout: Layout, op: AllocOp) -> AllocError {
+        let total = self.total.get();
+        if !self.ring.is_empty() {
+            self.ring[total % self.ring.len()].set(MaybeUninit::new(TraceEntry {
+                size: layout.size(),
+                align: layout.align(),
+                op,
+            }));
+        }
+        self.total.set(total.wrapping_add(1));
+        AllocError
+    }
+}
+
+unsafe impl Allocator for TracingNoopAllocator<'_> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        NoopAllocator::new()
+            .allocate(layout)
+            .map_err(|_| self.reject(layout, AllocOp::Allocate))
+    }
+
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
+        // intentionally empty
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        NoopAllocator::new()
+            .grow(ptr, old_layout, new_layout)
+            .map_err(|_| self.reject(new_layout, AllocOp::Grow))
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        NoopAllocator::new()
+            .grow_zeroed(ptr, old_layout, new_layout)
+            .map_err(|_| self.reject(new_layout, AllocOp::GrowZeroed))
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        NoopAllocator::new()
+            .shrink(ptr, old_layout, new_layout)
+            .map_err(|_| self.reject(new_layout, AllocOp::Shrink))
+    }
+}
+
+/// An [`Allocator`] that behaves like [`NoopAllocator`], but checks that the
+/// pointers it is given belong to a particular region.
+///
+/// With debug assertions enabled, [`deallocate`][Allocator::deallocate],
+/// [`grow`][Allocator::grow], [`grow_zeroed`][Allocator::grow_zeroed], and
+/// [`shrink`][Allocator::shrink] panic unless `ptr..ptr + old_layout.size()`
+/// lies within the region and `ptr` is aligned to `old_layout.align()`. This
+/// catches collections built over the wrong buffer. Without debug assertions,
+/// the checks are removed and the operations are the same as
+/// `NoopAllocator`'s.
+///
+/// Only the address range of the region is stored, so creating a
+/// `RegionNoopAllocator` does not borrow it.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::diagnostic::RegionNoopAllocator;
+/// let mut buf = [MaybeUninit::<u32>::uninit(); 4];
+/// let alloc = RegionNoopAllocator::new(&raw const buf);
+/// let mut v = unsafe { Vec::from_raw_parts_in(buf.as_mut_ptr().cast::<u32>(), 0, 4, alloc) };
+/// v.extend([1, 2]);
+/// v.shrink_to_fit();
+/// assert_eq!(v.capacity(), 2);
+/// drop(v);
+/// ```
+///
+/// Deallocating a pointer from another buffer panics:
+///
+/// ```rust,should_panic
+/// #![feature(allocator_api)]
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::diagnostic::RegionNoopAllocator;
+/// let buf = [MaybeUninit::<u32>::uninit(); 4];
+/// let mut other = [MaybeUninit::<u32>::uninit(); 4];
+/// let alloc = RegionNoopAllocator::new(&raw const buf);
+/// let v = unsafe { Vec::from_raw_parts_in(other.as_mut_ptr().cast::<u32>(), 0, 4, alloc) };
+/// drop(v);
+/// ```
+pub struct RegionNoopAllocator<'a> {
+    start: usize,
+    end: usize,
+    _marker: PhantomData<&'a ()>,
+}
+
+impl<'a> RegionNoopAllocator<'a> {
+    /// Creates a new `RegionNoopAllocator<'a>` accepting pointers into the
+    /// memory of `region`.
+    pub fn new<T>(region: *const [T]) -> Self {
+        let start = region as *const T as usize;
+        Self {
+            start,
+            end: start + region.len() * core::mem::size_of::<T>(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the address range of the region.
+    pub fn region(&self) -> core::ops::Range<usize> {
+        self.start..self.end
+    }
+
+    #[track_caller]
+    fn check(&self, ptr: NonNull<u8>, layout: Layout) {
+        if layout.size() == 0 {
+            return;
+        }
+        let addr = ptr.as_ptr() as usize;
+        debug_assert!(
+            addr >= self.start && addr <= self.end && layout.size() <= self.end - addr,
+            "pointer {:#x} with size {} is outside of the region {:#x}..{:#x}",
+            addr,
+            layout.size(),
+            self.start,
+            self.end
+        );
+        debug_assert!(
+            addr & (layout.align() - 1) == 0,
+            "pointer {:#x} is not aligned to {}",
+            addr,
+            layout.align()
+        );
+    }
+}
+
+unsafe impl Allocator for RegionNoopAllocator<'_> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        NoopAllocator::new().allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.check(ptr, layout);
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.check(ptr, old_layout);
+        NoopAllocator::new().grow(ptr, old_layout, new_layout)
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.check(ptr, old_layout);
+        NoopAllocator::new().grow_zeroed(ptr, old_layout, new_layout)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.check(ptr, old_layout);
+        NoopAllocator::new().shrink(ptr, old_layout, new_layout)
+    }
+}
+
+/// Emits a `log` record or `tracing` event at the given level, depending on
+/// which feature is enabled.
+#[cfg(any(feature = "log", feature = "tracing"))]
+macro_rules! emit {
+    ($level:ident, $($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        log::$level!($($arg)*);
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($arg)*);
+    }};
+}
+
+/// An [`Allocator`] that wraps another allocator, and logs every call at trace
+/// level and every failed call at warn level.
+///
+/// Requires the `log` or `tracing` feature (which are mutually exclusive), and
+/// emits records through the corresponding crate. The records are built with
+/// [`format_args!`] only, so logging does not allocate (though the installed
+/// logger or subscriber might).
+///
+/// # Examples
+///
+/// With the `log` feature:
+#[cfg_attr(feature = "log", doc = "```rust")]
+#[cfg_attr(not(feature = "log"), doc = "```rust,ignore")]
+/// #![feature(allocator_api)]
+/// # use std::{mem::MaybeUninit, sync::Mutex};
+/// use noop_allocator::diagnostic::LoggingNoopAllocator;
+/// static WARNINGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+/// struct Capture;
+/// impl log::Log for Capture {
+///     fn enabled(&self, _: &log::Metadata<'_>) -> bool {
+///         true
+///     }
+///     fn log(&self, record: &log::Record<'_>) {
+///         if record.level() == log::Level::Warn {
+///             WARNINGS.lock().unwrap().push(record.args().to_string());
+///         }
+///     }
+///     fn flush(&self) {}
+/// }
+/// log::set_logger(&Capture).unwrap();
+/// log::set_max_level(log::LevelFilter::Trace);
+///
+/// let mut buf = [MaybeUninit::<u32>::uninit(); 2];
+/// let mut v = unsafe {
+///     Vec::from_raw_parts_in(
+///         buf.as_mut_ptr().cast::<u32>(),
+///         0,
+///         2,
+///         LoggingNoopAllocator::new(Default::default()),
+///     )
+/// };
+/// v.extend([1, 2]);
+/// assert!(WARNINGS.lock().unwrap().is_empty());
+/// // `v.push(3)` would abort after logging; `try_reserve` logs the same.
+/// assert!(v.try_reserve_exact(1).is_err());
+/// assert_eq!(
+///     *WARNINGS.lock().unwrap(),
+///     ["allocator rejected Grow from 8 to 12 bytes with alignment 4"]
+/// );
+/// ```
+#[cfg(any(feature = "log", feature = "tracing"))]
+pub struct LoggingAllocator<A: Allocator> {
+    inner: A,
+}
+
+/// A [`LoggingAllocator`] around a [`NoopAllocator`].
+#[cfg(any(feature = "log", feature = "tracing"))]
+pub type LoggingNoopAllocator<'a> = LoggingAllocator<NoopAllocator<'a>>;
+
+#[cfg(any(feature = "log", feature = "tracing"))]
+impl<A: Allocator> LoggingAllocator<A> {
+    /// Creates a new `LoggingAllocator<A>` wrapping `inner`.
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+
+    /// Returns a reference to the wrapped allocator.
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// Consumes the wrapper, returning the wrapped allocator.
+    pub fn into_inner(self) -> A {
+        self.inner
+    }
+
+    fn log_allocate(
+        &self,
+        op: AllocOp,
+        layout: Layout,
+        result: Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        match result {
+            Ok(ptr) => emit!(
+                trace,
+                "allocator {:?} {} bytes with alignment {} at {:p}",
+                op,
+                layout.size(),
+                layout.align(),
+                ptr.as_ptr() as *const u8
+            ),
+            Err(AllocError) => emit!(
+                warn,
+                "allocator rejected {:?} of {} bytes with alignment {}",
+                op,
+                layout.size(),
+                layout.align()
+            ),
+        }
+        result
+    }
+
+    fn log_realloc(
+        &self,
+        op: AllocOp,
+        old_layout: Layout,
+        new_layout: Layout,
+        result: Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        match result {
+            Ok(ptr) => emit!(
+                trace,
+                "allocator {:?} from {} to {} bytes with alignment {} at {:p}",
+                op,
+                old_layout.size(),
+                new_layout.size(),
+                new_layout.align(),
+                ptr.as_ptr() as *const u8
+            ),
+            Err(AllocError) => emit!(
+                warn,
+                "allocator rejected {:?} from {} to {} bytes with alignment {}",
+                op,
+                old_layout.size(),
+                new_layout.size(),
+                new_layout.align()
+            ),
+        }
+        result
+    }
+}
+
+#[cfg(any(feature = "log", feature = "tracing"))]
+unsafe impl<A: Allocator> Allocator for LoggingAllocator<A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.log_allocate(AllocOp::Allocate, layout, self.inner.allocate(layout))
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.log_allocate(
+            AllocOp::Allocate,
+            layout,
+            self.inner.allocate_zeroed(layout),
+        )
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        emit!(
+            trace,
+            "allocator Deallocate {} bytes with alignment {} at {:p}",
+            layout.size(),
+            layout.align(),
+            ptr
+        );
+        self.inner.deallocate(ptr, layout)
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let result = self.inner.grow(ptr, old_layout, new_layout);
+        self.log_realloc(AllocOp::Grow, old_layout, new_layout, result)
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let result = self.inner.grow_zeroed(ptr, old_layout, new_layout);
+        self.log_realloc(AllocOp::GrowZeroed, old_layout, new_layout, result)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let result = self.inner.shrink(ptr, old_layout, new_layout);
+        self.log_realloc(AllocOp::Shrink, old_layout, new_layout, result)
+    }
+}
+
+/// An [`Allocator`] that behaves like [`NoopAllocator`], but logs each
+/// rejected request with [`defmt::warn!`].
+///
+/// Requires the `defmt` feature. Logging goes through `defmt`'s global logger,
+/// so it does not allocate and follows the logger's interrupt-safety rules.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// #![feature(allocator_api)]
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::diagnostic::DefmtNoopAllocator;
+/// let mut buf = [MaybeUninit::<u32>::uninit(); 2];
+/// let mut v = unsafe {
+///     Vec::from_raw_parts_in(
+///         buf.as_mut_ptr().cast::<u32>(),
+///         0,
+///         2,
+///         DefmtNoopAllocator::new(),
+///     )
+/// };
+/// v.extend([1, 2]);
+/// // Logs "allocator rejected Grow from 8 to 12 bytes with alignment 4".
+/// assert!(v.try_reserve_exact(1).is_err());
+/// ```
+#[cfg(feature = "defmt")]
+#[repr(transparent)]
+pub struct DefmtNoopAllocator<'a>(PhantomData<&'a ()>);
+
+#[cfg(feature = "defmt")]
+impl<'a> DefmtNoopAllocator<'a> {
+    /// Creates a new `DefmtNoopAllocator<'a>`.
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl Default for DefmtNoopAllocator<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "defmt")]
+unsafe impl Allocator for DefmtNoopAllocator<'_> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        NoopAllocator::new().allocate(layout).inspect_err(|_| {
+            defmt::warn!(
+                "allocator rejected {} of {=usize} bytes with alignment {=usize}",
+                AllocOp::Allocate,
+                layout.size(),
+                layout.align()
+            )
+        })
+    }
+
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
+        // intentionally empty
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        NoopAllocator::new()
+            .grow(ptr, old_layout, new_layout)
+            .inspect_err(|_| warn_realloc(AllocOp::Grow, old_layout, new_layout))
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        NoopAllocator::new()
+            .grow_zeroed(ptr, old_layout, new_layout)
+            .inspect_err(|_| warn_realloc(AllocOp::GrowZeroed, old_layout, new_layout))
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        NoopAllocator::new()
+            .shrink(ptr, old_layout, new_layout)
+            .inspect_err(|_| warn_realloc(AllocOp::Shrink, old_layout, new_layout))
+    }
+}
+
+#[cfg(feature = "defmt")]
+fn warn_realloc(op: AllocOp, old_layout: Layout, new_layout: Layout) {
+    defmt::warn!(
+        "allocator rejected {} from {=usize} to {=usize} bytes with alignment {=usize}",
+        op,
+        old_layout.size(),
+        new_layout.size(),
+        new_layout.align()
+    )
+}
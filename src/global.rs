@@ -0,0 +1,512 @@
+//! [`GlobalAlloc`] implementations, for binaries that should not allocate.
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    fmt, ptr,
+};
+#[cfg(feature = "std")]
+use core::{
+    cell::Cell,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// A [`GlobalAlloc`] that always fails to allocate.
+///
+/// `alloc`, `alloc_zeroed`, and `realloc` return null, and `dealloc` is a
+/// no-op. Use it as the global allocator of a binary that must never allocate,
+/// but links a dependency that uses `alloc`:
+///
+/// ```rust,no_run
+/// use noop_allocator::global::NullGlobalAlloc;
+/// #[global_allocator]
+/// static GLOBAL: NullGlobalAlloc = NullGlobalAlloc;
+/// # fn main() {}
+/// ```
+///
+/// This is mostly useful for `no_std` binaries: the `std` runtime itself
+/// allocates before `main` is called, so a `std` binary using this allocator
+/// aborts on startup.
+///
+/// Any allocation attempt fails, which for most `alloc` APIs means
+/// calling [`handle_alloc_error`][alloc::alloc::handle_alloc_error], which by
+/// default aborts (with `std`) or panics (without `std`). See
+/// [`alloc_error_panic`] for a helper to use in a custom handler. APIs that
+/// never allocate, such as those of [`owning_ref`][crate::owning_ref] and
+/// [`owning_slice`][crate::owning_slice] within capacity, keep working.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullGlobalAlloc;
+
+unsafe impl GlobalAlloc for NullGlobalAlloc {
+    unsafe fn alloc(&self, _layout: Layout) -> *mut u8 {
+        ptr::null_mut()
+    }
+
+    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {
+        // intentionally empty
+    }
+
+    unsafe fn realloc(&self, _ptr: *mut u8, _layout: Layout, _new_size: usize) -> *mut u8 {
+        ptr::null_mut()
+    }
+}
+
+/// Panics with the size and alignment of a failed allocation.
+///
+/// This is meant to be called from an allocation error handler, e.g. on
+/// targets or toolchains that need an explicit `#[alloc_error_handler]`, so
+/// that the panic handler reports which allocation was attempted.
+#[track_caller]
+pub fn alloc_error_panic(layout: Layout) -> ! {
+    panic!(
+        "memory allocation of {} bytes with alignment {} failed",
+        layout.size(),
+        layout.align()
+    )
+}
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    /// The nesting depth of [`AbortOnAllocGlobal::allow_allocations`] calls on
+    /// this thread.
+    static ALLOWED: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A [`GlobalAlloc`] that aborts the process with a message on any allocation
+/// while it is enabled, and otherwise forwards to another allocator.
+///
+/// This turns an accidental allocation, e.g. in a realtime audio callback, into
+/// an immediate failure that reports the layout. Before aborting, the message
+/// is passed to the `output` hook given to [`new`][Self::new], such as
+/// [`stderr_output`]. Deallocation is always forwarded, since only memory
+/// allocated while allowed can be freed.
+///
+/// The `std` runtime allocates before `main` is called, so an
+/// `AbortOnAllocGlobal` starts out disabled; call [`enable`][Self::enable]
+/// once initialization is done. Use
+/// [`allow_allocations`][Self::allow_allocations] to allow allocations on the
+/// current thread for the duration of a closure.
+///
+/// Requires the `std` feature.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use noop_allocator::global::{AbortOnAllocGlobal, stderr_output};
+/// use std::alloc::System;
+/// #[global_allocator]
+/// static GLOBAL: AbortOnAllocGlobal<System> = AbortOnAllocGlobal::new(System, stderr_output);
+///
+/// fn main() {
+///     let config = vec![1, 2, 3];
+///     GLOBAL.enable();
+///     let sum: i32 = config.iter().sum();
+///     let message = GLOBAL.allow_allocations(|| format!("sum is {sum}"));
+///     drop(message);
+///     let oops = Box::new(sum); // aborts
+/// }
+/// ```
+#[cfg(feature = "std")]
+pub struct AbortOnAllocGlobal<A: GlobalAlloc> {
+    inner: A,
+    output: fn(fmt::Arguments<'_>),
+    enabled: AtomicBool,
+}
+
+#[cfg(feature = "std")]
+impl<A: GlobalAlloc> AbortOnAllocGlobal<A> {
+    /// Creates a new, disabled `AbortOnAllocGlobal<A>` forwarding to `inner`
+    /// and reporting forbidden allocations to `output`.
+    ///
+    /// `output` is called from inside the global allocator, so it must not
+    /// allocate.
+    pub const fn new(inner: A, output: fn(fmt::Arguments<'_>)) -> Self {
+        Self {
+            inner,
+            output,
+            enabled: AtomicBool::new(false),
+        }
+    }
+
+    /// Starts aborting on allocations outside of
+    /// [`allow_allocations`][Self::allow_allocations].
+    pub fn enable(&self) {
+        self.enabled.store(true, Ordering::SeqCst);
+    }
+
+    /// Stops aborting on allocations.
+    pub fn disable(&self) {
+        self.enabled.store(false, Ordering::SeqCst);
+    }
+
+    /// Returns `true` if allocations on the current thread would abort.
+    pub fn is_enforcing(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst) && ALLOWED.with(Cell::get) == 0
+    }
+
+    /// Runs `f`, allowing allocations on the current thread while it runs.
+    pub fn allow_allocations<R>(&self, f: impl FnOnce() -> R) -> R {
+        struct Disallow;
+        impl Drop for Disallow {
+            fn drop(&mut self) {
+                ALLOWED.with(|allowed| allowed.set(allowed.get() - 1));
+            }
+        }
+
+        ALLOWED.with(|allowed| allowed.set(allowed.get() + 1));
+        let _guard = Disallow;
+        f()
+    }
+
+    fn check(&self, layout: Layout) {
+        if self.is_enforcing() {
+            (self.output)(format_args!(
+                "memory allocation of {} bytes with alignment {} while allocations are forbidden\n",
+                layout.size(),
+                layout.align()
+            ));
+            std::process::abort();
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+unsafe impl<A: GlobalAlloc> GlobalAlloc for AbortOnAllocGlobal<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.check(layout);
+        self.inner.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        self.check(layout);
+        self.inner.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        self.check(Layout::from_size_align_unchecked(new_size, layout.align()));
+        self.inner.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Writes `args` to the standard error stream without allocating, ignoring
+/// errors. For use as the output hook of an [`AbortOnAllocGlobal`].
+#[cfg(feature = "std")]
+pub fn stderr_output(args: fmt::Arguments<'_>) {
+    use std::io::Write;
+    let _ = std::io::stderr().write_fmt(args);
+}
+
+/// A [`GlobalAlloc`] that bump-allocates from a static buffer of `N` bytes.
+///
+/// This is meant for targets without a heap that still need a small amount of
+/// `alloc`, e.g. a few boxes created at startup:
+///
+/// ```rust,no_run
+/// use noop_allocator::global::StaticBumpGlobalAlloc;
+/// #[global_allocator]
+/// static HEAP: StaticBumpGlobalAlloc<4096> = StaticBumpGlobalAlloc::new();
+/// # fn main() {}
+/// ```
+///
+/// Memory is never freed: `dealloc` is a no-op, so every allocation
+/// permanently uses up its size plus any padding needed to align it. The one
+/// exception is `realloc` of the most recent allocation, which grows or shrinks
+/// it in place. Once the buffer is exhausted, allocations return null.
+///
+/// The offset into the buffer is an atomic updated with a compare-exchange
+/// loop, so allocating from several threads or from interrupt handlers is
+/// sound. This type is only available on targets with native atomic
+/// compare-exchange, or with the `portable-atomic` feature enabled (with
+/// `critical-section` on targets without compare-exchange).
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+pub struct StaticBumpGlobalAlloc<const N: usize> {
+    buf: core::cell::UnsafeCell<[core::mem::MaybeUninit<u8>; N]>,
+    offset: crate::sync::AtomicUsize,
+}
+
+// SAFETY: The buffer is only accessed through disjoint regions handed out by
+// the atomic offset.
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+unsafe impl<const N: usize> Sync for StaticBumpGlobalAlloc<N> {}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<const N: usize> StaticBumpGlobalAlloc<N> {
+    /// Creates a new `StaticBumpGlobalAlloc<N>` with nothing allocated.
+    pub const fn new() -> Self {
+        Self {
+            buf: core::cell::UnsafeCell::new([core::mem::MaybeUninit::uninit(); N]),
+            offset: crate::sync::AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of bytes used so far, including alignment padding.
+    ///
+    /// Since memory is never freed, this is also the high-water mark.
+    pub fn used(&self) -> usize {
+        self.offset.load(crate::sync::Ordering::Relaxed)
+    }
+
+    /// Returns the number of bytes not yet used.
+    pub fn remaining(&self) -> usize {
+        N - self.used()
+    }
+
+    fn base(&self) -> *mut u8 {
+        self.buf.get().cast()
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<const N: usize> Default for StaticBumpGlobalAlloc<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<const N: usize> fmt::Debug for StaticBumpGlobalAlloc<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticBumpGlobalAlloc")
+            .field("capacity", &N)
+            .field("used", &self.used())
+            .finish()
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+unsafe impl<const N: usize> GlobalAlloc for StaticBumpGlobalAlloc<N> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        use crate::sync::Ordering::Relaxed;
+        let base = self.base();
+        let mut current = self.offset.load(Relaxed);
+        loop {
+            let Some((start, end)) = crate::buffer::fit(base as usize, N, current, layout) else {
+                return ptr::null_mut();
+            };
+            match self
+                .offset
+                .compare_exchange_weak(current, end, Relaxed, Relaxed)
+            {
+                Ok(_) => return base.add(start),
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {
+        // intentionally empty
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        use crate::sync::Ordering::Relaxed;
+        let start = ptr as usize - self.base() as usize;
+        let old_end = start + layout.size();
+        if new_size <= N - start
+            && self
+                .offset
+                .compare_exchange(old_end, start + new_size, Relaxed, Relaxed)
+                .is_ok()
+        {
+            return ptr;
+        }
+        if new_size <= layout.size() {
+            return ptr;
+        }
+        let new_ptr = self.alloc(Layout::from_size_align_unchecked(new_size, layout.align()));
+        if !new_ptr.is_null() {
+            ptr::copy_nonoverlapping(ptr, new_ptr, layout.size());
+        }
+        new_ptr
+    }
+}
+
+/// Snapshot of the counters of a [`CountingGlobalAlloc`], or the difference
+/// between two snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+pub struct AllocCounts {
+    /// The number of calls to `alloc` and `alloc_zeroed`.
+    pub allocs: usize,
+    /// The number of calls to `realloc`.
+    pub reallocs: usize,
+    /// The number of calls to `dealloc`.
+    pub deallocs: usize,
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl AllocCounts {
+    /// Returns the number of calls counted since `earlier` was taken.
+    pub fn since(self, earlier: AllocCounts) -> AllocCounts {
+        AllocCounts {
+            allocs: self.allocs.wrapping_sub(earlier.allocs),
+            reallocs: self.reallocs.wrapping_sub(earlier.reallocs),
+            deallocs: self.deallocs.wrapping_sub(earlier.deallocs),
+        }
+    }
+
+    /// Returns `true` if all counters are zero.
+    pub fn is_zero(&self) -> bool {
+        *self == AllocCounts::default()
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl fmt::Display for AllocCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} allocs, {} reallocs, {} deallocs",
+            self.allocs, self.reallocs, self.deallocs
+        )
+    }
+}
+
+/// A [`GlobalAlloc`] that forwards to another allocator and counts the calls
+/// made to it.
+///
+/// This is meant to be the `#[global_allocator]` of a test binary, to assert
+/// that specific code paths do not allocate, with
+/// [`assert_no_alloc`][Self::assert_no_alloc] or an [`AllocCounterGuard`]:
+///
+/// ```rust
+/// use noop_allocator::global::CountingGlobalAlloc;
+/// use std::alloc::System;
+/// #[global_allocator]
+/// static GLOBAL: CountingGlobalAlloc<System> = CountingGlobalAlloc::new(System);
+///
+/// fn main() {
+///     let mut buf = vec![0u32; 16];
+///     GLOBAL.assert_no_alloc(|| buf.iter_mut().for_each(|x| *x += 1));
+///
+///     let guard = GLOBAL.guard();
+///     let boxed = Box::new(buf.len());
+///     drop(boxed);
+///     assert_eq!(guard.delta().allocs, 1);
+///     assert_eq!(guard.delta().deallocs, 1);
+/// }
+/// ```
+///
+/// The counters are shared by all threads, so allocations made concurrently by
+/// other threads (such as those of the default test harness running other
+/// tests) are counted too. Run allocation assertions single-threaded, e.g. in a
+/// test with `harness = false`.
+///
+/// This type is only available on targets with native atomics, or with the
+/// `portable-atomic` feature enabled.
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+pub struct CountingGlobalAlloc<A: GlobalAlloc> {
+    inner: A,
+    allocs: crate::sync::AtomicUsize,
+    reallocs: crate::sync::AtomicUsize,
+    deallocs: crate::sync::AtomicUsize,
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<A: GlobalAlloc> CountingGlobalAlloc<A> {
+    /// Creates a new `CountingGlobalAlloc<A>` forwarding to `inner`, with all
+    /// counters at zero.
+    pub const fn new(inner: A) -> Self {
+        Self {
+            inner,
+            allocs: crate::sync::AtomicUsize::new(0),
+            reallocs: crate::sync::AtomicUsize::new(0),
+            deallocs: crate::sync::AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns a reference to the underlying allocator.
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// Returns the current values of the counters.
+    pub fn counts(&self) -> AllocCounts {
+        use crate::sync::Ordering::SeqCst;
+        AllocCounts {
+            allocs: self.allocs.load(SeqCst),
+            reallocs: self.reallocs.load(SeqCst),
+            deallocs: self.deallocs.load(SeqCst),
+        }
+    }
+
+    /// Snapshots the counters, to later check how many calls were made since.
+    pub fn guard(&self) -> AllocCounterGuard<'_, A> {
+        AllocCounterGuard {
+            alloc: self,
+            start: self.counts(),
+        }
+    }
+
+    /// Runs `f`, and panics with the number of calls made if it allocated,
+    /// reallocated, or deallocated.
+    #[track_caller]
+    pub fn assert_no_alloc<R>(&self, f: impl FnOnce() -> R) -> R {
+        let guard = self.guard();
+        let result = f();
+        guard.assert_no_alloc();
+        result
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<A: GlobalAlloc + fmt::Debug> fmt::Debug for CountingGlobalAlloc<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CountingGlobalAlloc")
+            .field("inner", &self.inner)
+            .field("counts", &self.counts())
+            .finish()
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingGlobalAlloc<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.allocs.fetch_add(1, crate::sync::Ordering::SeqCst);
+        self.inner.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        self.allocs.fetch_add(1, crate::sync::Ordering::SeqCst);
+        self.inner.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.deallocs.fetch_add(1, crate::sync::Ordering::SeqCst);
+        self.inner.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        self.reallocs.fetch_add(1, crate::sync::Ordering::SeqCst);
+        self.inner.realloc(ptr, layout, new_size)
+    }
+}
+
+/// A snapshot of the counters of a [`CountingGlobalAlloc`], created by
+/// [`CountingGlobalAlloc::guard`].
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+#[derive(Debug)]
+pub struct AllocCounterGuard<'a, A: GlobalAlloc> {
+    alloc: &'a CountingGlobalAlloc<A>,
+    start: AllocCounts,
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<A: GlobalAlloc> AllocCounterGuard<'_, A> {
+    /// Returns the number of calls made since the guard was created.
+    pub fn delta(&self) -> AllocCounts {
+        self.alloc.counts().since(self.start)
+    }
+
+    /// Panics with the number of calls made since the guard was created, if
+    /// any.
+    #[track_caller]
+    pub fn assert_no_alloc(self) {
+        let delta = self.delta();
+        if !delta.is_zero() {
+            panic!("unexpected allocator calls: {delta}");
+        }
+    }
+}
@@ -1,10 +1,17 @@
 #![no_std]
-#![feature(allocator_api)]
-#![feature(alloc_layout_extra)]
+#![cfg_attr(feature = "nightly", feature(allocator_api))]
+#![cfg_attr(all(feature = "alloc", feature = "nightly"), feature(unsize))]
 #![warn(rust_2018_idioms)]
 
-#[cfg(feature = "alloc")]
+#[cfg(all(feature = "alloc", feature = "nightly"))]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(all(feature = "log", feature = "tracing"))]
+compile_error!("the `log` and `tracing` features are mutually exclusive");
+
+#[cfg(feature = "nightly")]
 use core::{
     alloc::{AllocError, Allocator, Layout},
     marker::PhantomData,
@@ -24,20 +31,128 @@ use core::{
 ///   "currently allocated", or fit `old_layout`, and will successfully return
 ///   the original pointer unchanged (with the length of the new layout) if the
 ///   `ptr` is aligned for the new layout and the new layout is smaller or the
-///   same size as the old layout.
+///   same size as the old layout. `grow` and `grow_zeroed` also succeed within
+///   the padded size of the old layout, see [below](#growing-within-padding).
 ///
 /// This type is usable as an [`Allocator`] when you want to borrow an existing
 /// memory range for use in a single-allocation collection type, for example in
 /// [`Box`][alloc::boxed::Box] or [`Vec`][alloc::vec::Vec].
 ///
-/// # Safety:
+/// # Examples
+///
+/// Zero-sized allocations succeed, returning a dangling pointer aligned for the
+/// layout:
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::alloc::{Allocator, Layout};
+/// use noop_allocator::NoopAllocator;
+/// let alloc = NoopAllocator::new();
+/// for layout in [
+///     Layout::new::<()>(),
+///     Layout::new::<[u64; 0]>(),
+///     Layout::from_size_align(0, 64).unwrap(),
+/// ] {
+///     let ptr = alloc.allocate(layout).unwrap();
+///     assert_eq!(ptr.len(), 0);
+///     assert_eq!(ptr.cast::<u8>().as_ptr() as usize % layout.align(), 0);
+/// }
+/// assert!(alloc.allocate(Layout::new::<u8>()).is_err());
+/// ```
+///
+/// # Growing within padding
+///
+/// [`grow`][NoopAllocator::grow] and
+/// [`grow_zeroed`][NoopAllocator::grow_zeroed] succeed as long as the new size
+/// is at most [`old_layout.pad_to_align().size()`][Layout::pad_to_align], not
+/// only `old_layout.size()`, and `grow_zeroed` zeroes the newly exposed bytes.
+///
+/// Every `OwningRef` and `OwningSlice` borrows a slot of some type `T` (or
+/// `[T]`), and the size of a type is always a multiple of its alignment, so the
+/// slot really does cover the padded size of any layout it fits. Code calling
+/// `grow` directly with a layout whose size is not a multiple of its alignment
+/// must likewise ensure that `ptr` is valid for the padded size.
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::{alloc::{Allocator, Layout}, mem::MaybeUninit, ptr::NonNull};
+/// use noop_allocator::NoopAllocator;
+/// #[repr(align(8))]
+/// struct Slot([MaybeUninit<u8>; 16]);
+/// let mut slot = Slot([MaybeUninit::new(0xFF); 16]);
+/// // Aligned to 4, but not to 8.
+/// let ptr = NonNull::new(slot.0[4..].as_mut_ptr().cast::<u8>()).unwrap();
+///
+/// let alloc = NoopAllocator::new();
+/// let old = Layout::from_size_align(5, 4).unwrap();
+/// let layout = |size, align| Layout::from_size_align(size, align).unwrap();
+/// unsafe {
+///     // Exactly the padded size.
+///     let grown = alloc.grow_zeroed(ptr, old, layout(8, 4)).unwrap();
+///     assert_eq!(grown.len(), 8);
+///     assert_eq!(grown.cast::<u8>(), ptr);
+///     assert_eq!(grown.as_ref(), [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0]);
+///     // One past the padded size.
+///     assert!(alloc.grow(ptr, old, layout(9, 4)).is_err());
+///     assert!(alloc.grow_zeroed(ptr, old, layout(9, 4)).is_err());
+///     // Within the padded size, but misaligned for the new layout.
+///     assert!(alloc.grow(ptr, old, layout(8, 8)).is_err());
+///     assert!(alloc.grow(ptr, old, layout(6, 8)).is_err());
+///     // Within the padded size, with a smaller alignment.
+///     assert_eq!(alloc.grow(ptr, old, layout(7, 1)).unwrap().len(), 7);
+/// }
+/// ```
+///
+/// # Poisoning
+///
+/// With the `poison` feature, [`deallocate`][NoopAllocator::deallocate]
+/// overwrites the `layout.size()` bytes at `ptr` with [`POISON`], and
+/// [`shrink`][NoopAllocator::shrink] overwrites the bytes between the new and
+/// old sizes, so that stale reads of released memory are easy to spot.
+/// Zero-sized layouts are never written to. With this feature, `deallocate` and
+/// `shrink` do require that `ptr` be valid for writes of `layout.size()` (resp.
+/// `old_layout.size()`) bytes, which is the case for pointers from an
+/// `OwningRef` or `OwningSlice`.
+///
+/// Storage borrowed as `MaybeUninit` may hold any bytes, so overwriting it is
+/// allowed once its value has been dropped or moved out; like any dropped
+/// value, the poisoned bytes must not be read as a `T`. Storage borrowed as an
+/// initialized type, with [`owning_ref::from_manuallydrop`],
+/// [`owning_ref::from_initialized`], or
+/// [`owning_slice::from_initialized_slice`], is left holding bytes that may not
+/// be a valid value of that type (e.g. a `bool`, a reference, or a `String`
+/// whose capacity is greater than `isize::MAX`), so those constructors require
+/// that it is not used as a value of its type again until it is overwritten.
+///
+/// The poisoned bytes can be inspected as bytes, e.g. through a raw pointer or
+/// in a debugger:
+#[cfg_attr(feature = "poison", doc = "```rust")]
+#[cfg_attr(not(feature = "poison"), doc = "```rust,ignore")]
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::{POISON, owning_ref};
+/// let mut slot = MaybeUninit::<[u8; 4]>::uninit();
+/// let ptr = slot.as_ptr().cast::<u8>();
+/// let owned = owning_ref::from_maybeuninit_write(&mut slot, [1, 2, 3, 4]);
+/// drop(owned);
+/// let bytes = unsafe { std::ptr::read(ptr.cast::<[u8; 4]>()) };
+/// assert_eq!(bytes, [POISON; 4]);
+#[doc = "```"]
+///
+/// # Safety
 ///
 /// Many functions in this crate assume that `impl Allocator for
 /// NoopAllocator<'_>` as described above is sound, but `feature(allocator_api)`
 /// is unstable and the preconditions may change.
+#[cfg(feature = "nightly")]
 #[repr(transparent)]
 pub struct NoopAllocator<'a>(PhantomData<&'a ()>);
 
+/// The byte written over released memory when the `poison` feature is enabled.
+/// See [`NoopAllocator`].
+#[cfg(all(feature = "poison", feature = "nightly"))]
+pub const POISON: u8 = 0xDE;
+
+#[cfg(feature = "nightly")]
 impl<'a> NoopAllocator<'a> {
     /// Creates a new `NoopAllocator<'a>`.
     pub const fn new() -> Self {
@@ -45,17 +160,27 @@ impl<'a> NoopAllocator<'a> {
     }
 }
 
+#[cfg(feature = "nightly")]
+impl Default for NoopAllocator<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "nightly")]
 unsafe impl Allocator for NoopAllocator<'_> {
     fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
         if layout.size() == 0 {
-            Ok(NonNull::slice_from_raw_parts(layout.dangling(), 0))
+            Ok(NonNull::slice_from_raw_parts(layout.dangling_ptr(), 0))
         } else {
             Err(AllocError)
         }
     }
 
-    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
-        // intentionally empty
+    #[cfg_attr(not(feature = "poison"), allow(unused_variables))]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        #[cfg(feature = "poison")]
+        ptr.write_bytes(POISON, layout.size());
     }
 
     unsafe fn grow(
@@ -63,21 +188,12 @@ unsafe impl Allocator for NoopAllocator<'_> {
         ptr: NonNull<u8>,
         old_layout: Layout,
         new_layout: Layout,
-    ) -> Result<NonNull<[u8]>, AllocError> {
-        self.grow_zeroed(ptr, old_layout, new_layout)
-    }
-
-    unsafe fn grow_zeroed(
-        &self,
-        ptr: NonNull<u8>,
-        old_layout: Layout,
-        new_layout: Layout,
     ) -> Result<NonNull<[u8]>, AllocError> {
         debug_assert!(
             new_layout.size() >= old_layout.size(),
             "`new_layout.size()` must be greater than or equal to `old_layout.size()`"
         );
-        if new_layout.size() > old_layout.size()
+        if new_layout.size() > old_layout.pad_to_align().size()
             || (ptr.as_ptr() as usize & (new_layout.align() - 1) != 0)
         {
             return Err(AllocError);
@@ -88,6 +204,18 @@ unsafe impl Allocator for NoopAllocator<'_> {
         Ok(new_ptr)
     }
 
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let new_ptr = self.grow(ptr, old_layout, new_layout)?;
+        ptr.add(old_layout.size())
+            .write_bytes(0, new_layout.size() - old_layout.size());
+        Ok(new_ptr)
+    }
+
     unsafe fn shrink(
         &self,
         ptr: NonNull<u8>,
@@ -105,13 +233,58 @@ unsafe impl Allocator for NoopAllocator<'_> {
             return Err(AllocError);
         }
 
+        #[cfg(feature = "poison")]
+        ptr.add(new_layout.size())
+            .write_bytes(POISON, old_layout.size() - new_layout.size());
+
         let new_ptr = NonNull::slice_from_raw_parts(ptr, new_layout.size());
 
         Ok(new_ptr)
     }
 }
 
-#[cfg(feature = "alloc")]
+pub mod buffer;
+#[cfg(feature = "nightly")]
+pub mod bump;
+#[cfg(feature = "nightly")]
+pub mod chain;
+#[cfg(feature = "nightly")]
+pub mod diagnostic;
+pub mod global;
+#[cfg(all(feature = "alloc", feature = "nightly"))]
+pub mod owning_deque;
+#[cfg(all(feature = "alloc", feature = "nightly"))]
+pub mod owning_queue;
+#[cfg(all(feature = "alloc", feature = "nightly"))]
 pub mod owning_ref;
-#[cfg(feature = "alloc")]
+pub mod owning_ring;
+#[cfg(all(feature = "alloc", feature = "nightly"))]
 pub mod owning_slice;
+#[cfg(feature = "nightly")]
+pub mod pinning;
+#[cfg(all(feature = "alloc", feature = "nightly"))]
+pub mod pool;
+#[cfg(all(feature = "alloc", feature = "nightly"))]
+pub mod prelude;
+#[cfg(all(
+    feature = "nightly",
+    any(feature = "portable-atomic", target_has_atomic = "ptr")
+))]
+pub mod quota;
+#[cfg(feature = "serde")]
+pub mod ser;
+#[cfg(feature = "nightly")]
+pub mod slab;
+#[cfg(all(feature = "alloc", feature = "nightly"))]
+pub mod slot;
+#[cfg(all(feature = "alloc", feature = "nightly"))]
+pub mod spill;
+pub mod stable;
+#[cfg(feature = "nightly")]
+pub mod stack;
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+mod sync;
+#[cfg(all(feature = "testing", feature = "nightly"))]
+pub mod testing;
+#[cfg(feature = "nightly")]
+pub mod zeroizing;
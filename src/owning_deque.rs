@@ -0,0 +1,82 @@
+//! Functions to produce an [`OwningDeque<'_, T>`][OwningDeque], a.k.a. a
+//! `VecDeque<T, NoopAllocator<'_>>`, from mutably borrowed `MaybeUninit<T>`s.
+//!
+//! Like for [`OwningSlice`][crate::owning_slice::OwningSlice], operations that
+//! stay within the capacity of the borrowed buffer never allocate, including:
+//! * [`push_back`][VecDeque::push_back] and
+//!   [`push_front`][VecDeque::push_front] while `len() < capacity()`
+//! * [`pop_back`][VecDeque::pop_back], [`pop_front`][VecDeque::pop_front],
+//!   [`truncate`][VecDeque::truncate], and [`clear`][VecDeque::clear]
+//! * [`rotate_left`][VecDeque::rotate_left],
+//!   [`rotate_right`][VecDeque::rotate_right], and
+//!   [`make_contiguous`][VecDeque::make_contiguous] (see [`make_contiguous`])
+//!
+//! Operations that need more capacity, such as pushing onto a full deque,
+//! [`reserve`][VecDeque::reserve], or [`extend`][Extend::extend]ing past the
+//! capacity, will fail to allocate and abort the process (see
+//! [`handle_alloc_error`][alloc::alloc::handle_alloc_error]). Use the
+//! `try_reserve` methods to check for capacity fallibly.
+
+use core::mem::MaybeUninit;
+
+use crate::{NoopAllocator, owning_slice};
+use alloc::collections::VecDeque;
+
+/// An owning deque borrowing a memory location but owning the values in it,
+/// implemented as `VecDeque<T, NoopAllocator<'a>>`.
+pub type OwningDeque<'a, T> = VecDeque<T, NoopAllocator<'a>>;
+
+/// Create a `OwningDeque<'a, T>` with a length of 0 from a `&'a mut
+/// [MaybeUninit<T>]>`. The capacity is the length of the given slice.
+///
+/// The slice elements are not assumed to be initialized, so this is not an
+/// `unsafe` function.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_deque;
+/// let mut buf: [MaybeUninit<String>; 4] = [const { MaybeUninit::uninit() }; 4];
+/// let mut deque = owning_deque::empty_from_maybeuninit_slice(&mut buf);
+/// assert!(deque.is_empty());
+/// assert_eq!(deque.capacity(), 4);
+/// deque.push_back("world".to_string());
+/// deque.push_front("Hello".to_string());
+/// assert_eq!(deque, ["Hello", "world"]);
+/// ```
+pub fn empty_from_maybeuninit_slice<T>(slot: &mut [MaybeUninit<T>]) -> OwningDeque<'_, T> {
+    // Converting a `Vec` to a `VecDeque` reuses its buffer without allocating.
+    VecDeque::from(owning_slice::empty_from_maybeuninit_slice(slot))
+}
+
+/// Rearrange the contents of an `OwningDeque<'a, T>` in-place so that they are
+/// contiguous, and return them as a slice.
+///
+/// This is [`VecDeque::make_contiguous`], which rotates the elements within the
+/// existing buffer and never allocates, so it is always safe to use with
+/// borrowed storage.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_deque;
+/// let mut buf: [MaybeUninit<u32>; 4] = [MaybeUninit::uninit(); 4];
+/// let start = buf.as_ptr().cast::<u32>();
+/// let mut deque = owning_deque::empty_from_maybeuninit_slice(&mut buf);
+/// deque.extend([2, 3]);
+/// deque.push_front(1);
+/// deque.push_front(0);
+/// deque.rotate_left(1);
+///
+/// // Any attempt to allocate would abort, so reaching the end of this example
+/// // shows that none of the above allocated.
+/// let slice = owning_deque::make_contiguous(&mut deque);
+/// assert_eq!(slice, [1, 2, 3, 0]);
+/// assert_eq!(slice.as_ptr(), start);
+/// assert_eq!(deque.capacity(), 4);
+/// ```
+pub fn make_contiguous<'b, T>(d: &'b mut OwningDeque<'_, T>) -> &'b mut [T] {
+    d.make_contiguous()
+}
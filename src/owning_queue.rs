@@ -0,0 +1,156 @@
+//! A FIFO queue over a borrowed buffer, with *O*(1) removal from the front.
+
+use core::{
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
+};
+
+use crate::{NoopAllocator, owning_slice::OwningSlice};
+use alloc::vec::Vec;
+
+/// An owning queue borrowing a memory location but owning the values in it.
+///
+/// Unlike [`owning_slice::pop_front_owning`][crate::owning_slice::pop_front_owning],
+/// [`pop_front`][Self::pop_front] does not shift the remaining elements: the
+/// queue just advances the start of its elements within the buffer. The whole
+/// buffer can be recovered as an [`OwningSlice`] with
+/// [`into_owning_slice`][Self::into_owning_slice], which moves the remaining
+/// elements back to the start.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::{owning_queue::OwningQueue, owning_slice};
+/// let mut buf: [MaybeUninit<String>; 4] = [const { MaybeUninit::uninit() }; 4];
+/// let start = buf.as_ptr().cast::<String>();
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// vec.extend(["a", "b", "c", "d"].map(String::from));
+///
+/// let mut queue = OwningQueue::new(vec);
+/// assert_eq!(queue.pop_front().as_deref(), Some("a"));
+/// assert_eq!(queue.pop_front().as_deref(), Some("b"));
+/// assert_eq!(queue.len(), 2);
+/// assert_eq!(queue.as_ptr(), start.wrapping_add(2));
+/// queue[0].push('!');
+///
+/// let mut vec = queue.into_owning_slice();
+/// assert_eq!(vec, ["c!", "d"]);
+/// assert_eq!(vec.as_ptr(), start);
+/// assert_eq!(vec.capacity(), 4);
+/// vec.extend(["e", "f"].map(String::from));
+/// assert_eq!(vec, ["c!", "d", "e", "f"]);
+/// ```
+pub struct OwningQueue<'a, T> {
+    buf: NonNull<T>,
+    head: usize,
+    len: usize,
+    capacity: usize,
+    _marker: PhantomData<OwningSlice<'a, T>>,
+}
+
+// SAFETY: An `OwningQueue<'_, T>` owns its elements like the `OwningSlice<'_,
+// T>` it was created from.
+unsafe impl<T: Send> Send for OwningQueue<'_, T> {}
+unsafe impl<T: Sync> Sync for OwningQueue<'_, T> {}
+
+impl<'a, T> OwningQueue<'a, T> {
+    /// Creates a new `OwningQueue<'a, T>` owning the elements and buffer of
+    /// `v`.
+    pub fn new(v: OwningSlice<'a, T>) -> Self {
+        let (buf, len, capacity, _) = v.into_raw_parts_with_alloc();
+        Self {
+            // SAFETY: `Vec`'s pointer is never null.
+            buf: unsafe { NonNull::new_unchecked(buf) },
+            head: 0,
+            len,
+            capacity,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of elements in the queue.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the queue contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the length of the borrowed buffer, including the slots before
+    /// the front of the queue.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Removes and returns the first element, or `None` if the queue is empty.
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let value = unsafe { self.buf.add(self.head).read() };
+        self.head += 1;
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Returns the elements of the queue as a slice.
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { NonNull::slice_from_raw_parts(self.buf.add(self.head), self.len).as_ref() }
+    }
+
+    /// Returns the elements of the queue as a mutable slice.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { NonNull::slice_from_raw_parts(self.buf.add(self.head), self.len).as_mut() }
+    }
+
+    /// Moves the remaining elements to the start of the buffer, and returns
+    /// them as an `OwningSlice<'a, T>` with the full capacity of the
+    /// buffer.
+    pub fn into_owning_slice(self) -> OwningSlice<'a, T> {
+        let this = core::mem::ManuallyDrop::new(self);
+        unsafe {
+            ptr::copy(
+                this.buf.add(this.head).as_ptr(),
+                this.buf.as_ptr(),
+                this.len,
+            );
+            Vec::from_raw_parts_in(
+                this.buf.as_ptr(),
+                this.len,
+                this.capacity,
+                NoopAllocator(PhantomData),
+            )
+        }
+    }
+}
+
+impl<T> Deref for OwningQueue<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> DerefMut for OwningQueue<'_, T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for OwningQueue<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+impl<T> Drop for OwningQueue<'_, T> {
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(self.as_mut_slice()) }
+    }
+}
@@ -2,20 +2,86 @@
 //! NoopAllocator<'_>>`, from a mutably borrowed `MaybeUninit<T>` or
 //! `ManuallyDrop<T>`.
 use core::{
-    marker::PhantomData,
+    alloc::Layout,
+    fmt,
+    marker::{PhantomData, Unsize},
     mem::{ManuallyDrop, MaybeUninit},
+    pin::Pin,
+    ptr::NonNull,
 };
 
-use crate::NoopAllocator;
+use crate::{NoopAllocator, owning_slice::PartialInitGuard};
 use alloc::boxed::Box;
 
 /// An owning reference boorrowing a memory location but owning the value in it,
 /// implemented as `Box<T, NoopAllocator<'a>>`.
+///
+/// Like any `Box`, an `OwningRef` is a pointer, so [`core::mem::swap`]ping two
+/// `OwningRef`s swaps which slot each one points to; the values stay where they
+/// are. Use `core::mem::swap(&mut *a, &mut *b)` to swap the values instead.
+/// Either way, each value is dropped exactly once, in the slot it lives in.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_ref;
+/// static DROPS: [AtomicUsize; 2] = [AtomicUsize::new(0), AtomicUsize::new(0)];
+/// static DROPPED_AT: [AtomicUsize; 2] = [AtomicUsize::new(0), AtomicUsize::new(0)];
+/// struct Counted(usize, &'static str);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS[self.0].fetch_add(1, Ordering::Relaxed);
+///         DROPPED_AT[self.0].store(self as *mut Self as usize, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut slot_a = MaybeUninit::uninit();
+/// let mut slot_b = MaybeUninit::uninit();
+/// let addr_a = slot_a.as_ptr();
+/// let addr_b = slot_b.as_ptr();
+/// let mut a = owning_ref::from_maybeuninit_write(&mut slot_a, Counted(0, "a"));
+/// let mut b = owning_ref::from_maybeuninit_write(&mut slot_b, Counted(1, "b"));
+///
+/// std::mem::swap(&mut a, &mut b);
+/// assert_eq!(&raw const *a, addr_b);
+/// assert_eq!(&raw const *b, addr_a);
+/// assert_eq!((a.1, b.1), ("b", "a"));
+///
+/// // Mutating through each box writes to the slot it now points to.
+/// a.1 = "written through a";
+/// b.1 = "written through b";
+///
+/// drop(a);
+/// assert_eq!(DROPS[0].load(Ordering::Relaxed), 0);
+/// assert_eq!(DROPS[1].load(Ordering::Relaxed), 1);
+/// drop(b);
+/// assert_eq!(DROPS[0].load(Ordering::Relaxed), 1);
+/// assert_eq!(DROPS[1].load(Ordering::Relaxed), 1);
+///
+/// // Each value was dropped in the slot it was originally written to.
+/// assert_eq!(DROPPED_AT[0].load(Ordering::Relaxed), addr_a as usize);
+/// assert_eq!(DROPPED_AT[1].load(Ordering::Relaxed), addr_b as usize);
+/// ```
 pub type OwningRef<'a, T> = Box<T, NoopAllocator<'a>>;
 
+// `NoopAllocator` is zero-sized, so an `OwningRef` is exactly as big as the
+// plain `Box` it stands in for, thin or fat.
+const _: () = {
+    use core::mem::size_of;
+    assert!(size_of::<OwningRef<'static, u8>>() == size_of::<Box<u8>>());
+    assert!(size_of::<OwningRef<'static, [u8]>>() == size_of::<Box<[u8]>>());
+    assert!(size_of::<OwningRef<'static, str>>() == size_of::<Box<str>>());
+    assert!(
+        size_of::<OwningRef<'static, dyn core::any::Any>>() == size_of::<Box<dyn core::any::Any>>()
+    );
+    assert!(size_of::<Option<OwningRef<'static, u8>>>() == size_of::<OwningRef<'static, u8>>());
+};
+
 /// Create a `OwningRef<'a, T>` from a `&'a mut ManuallyDrop<T>>`.
 ///
-/// # Safety:
+/// # Safety
 ///
 /// Dropping or moving out of the `OwningRef` leaves the borrowed `ManuallyDrop`
 /// semantically without a value; see [`ManuallyDrop::drop`] and
@@ -23,6 +89,11 @@ pub type OwningRef<'a, T> = Box<T, NoopAllocator<'a>>;
 ///
 /// You must not use the `Box` in such a way that would violate the vailidity
 /// invariant of the `ManuallyDrop<T>`.
+///
+/// With the `poison` feature, dropping the `OwningRef` overwrites the slot's
+/// bytes with `POISON`, which need not be a valid `T`. After that, the
+/// `ManuallyDrop<T>` must not be read, moved, or copied until a new value has
+/// been written to it with [`core::ptr::write`].
 pub unsafe fn from_manuallydrop<T: ?Sized>(slot: &mut ManuallyDrop<T>) -> OwningRef<'_, T> {
     Box::from_raw_in(
         slot as *mut ManuallyDrop<T> as *mut T,
@@ -30,9 +101,66 @@ pub unsafe fn from_manuallydrop<T: ?Sized>(slot: &mut ManuallyDrop<T>) -> Owning
     )
 }
 
+/// Create a `OwningRef<'a, T>` from an already-initialized `&'a mut T`.
+///
+/// # Safety
+///
+/// **The `OwningRef` takes ownership of the value without the borrowed
+/// location knowing about it.** Dropping or moving out of the `OwningRef`
+/// leaves the borrowed location semantically without a value, as with
+/// [`from_manuallydrop`], but nothing else prevents the value from being used
+/// or dropped again: the location will still be dropped normally by its owner.
+///
+/// So after the `OwningRef` is dropped or moved out of, the location must not
+/// be used until a new value has been written to it with [`core::ptr::write`]
+/// (which does not drop the old value), and it must have a value again before
+/// its owner drops it, or before it is otherwise used. Panicking in between
+/// will usually cause a double drop.
+///
+/// With the `poison` feature, dropping the `OwningRef` also overwrites the
+/// location's bytes with `POISON`, which need not be a valid `T`, so this
+/// applies even to types without drop glue.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_ref;
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// struct Counted(u32);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut value = Counted(1);
+/// let owned = unsafe { owning_ref::from_initialized(&mut value) };
+/// assert_eq!(owned.0, 1);
+/// drop(owned);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+/// // `value` is logically empty now; refill it before it is used or dropped.
+/// unsafe { std::ptr::write(&mut value, Counted(2)) };
+///
+/// // Moving out of the `OwningRef` does not drop anything.
+/// let moved: Counted = {
+///     let owned = unsafe { owning_ref::from_initialized(&mut value) };
+///     *owned
+/// };
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+/// unsafe { std::ptr::write(&mut value, Counted(3)) };
+/// drop(moved);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+/// drop(value);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 3);
+/// ```
+pub unsafe fn from_initialized<T: ?Sized>(slot: &mut T) -> OwningRef<'_, T> {
+    Box::from_raw_in(slot as *mut T, NoopAllocator(PhantomData))
+}
+
 /// Create a `OwningRef<'a, T>` from a `&'a mut MaybeUninit<T>>`.
 ///
-/// # Safety:
+/// # Safety
 ///
 /// The `T` must be initialized, see [`MaybeUninit::assume_init_mut`] and
 /// [`MaybeUninit::assume_init_drop`].
@@ -43,6 +171,194 @@ pub unsafe fn from_maybeuninit<T>(slot: &mut MaybeUninit<T>) -> OwningRef<'_, T>
     )
 }
 
+/// The error type returned by [`from_maybeuninit_checked`] when the slot is
+/// not sufficiently aligned for `T`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Misaligned;
+
+impl fmt::Display for Misaligned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("slot is not sufficiently aligned for its type")
+    }
+}
+
+/// Create a `OwningRef<'a, T>` from a `*mut MaybeUninit<T>`, checking that the
+/// slot is aligned for `T`.
+///
+/// A `&mut MaybeUninit<T>` is always aligned for `T` (creating a misaligned one
+/// is undefined behavior), so this takes a raw pointer, e.g. from a hand-rolled
+/// reinterpretation of a `MaybeUninit<u8>` region, and checks it before any
+/// reference to the slot exists. Returns `Err(Misaligned)` if the pointer is
+/// misaligned, instead of producing a misaligned `Box`.
+///
+/// # Safety
+///
+/// If `slot` is aligned, it must be valid for reads and writes of a `T` and not
+/// be accessed other than through the returned `OwningRef` for `'a`, as if it
+/// were a `&'a mut MaybeUninit<T>`, and the `T` must be initialized, see
+/// [`MaybeUninit::assume_init_mut`] and [`MaybeUninit::assume_init_drop`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_ref::{self, Misaligned};
+/// #[repr(C, align(4))]
+/// struct Buf([MaybeUninit<u8>; 8]);
+/// let mut buf = Buf([MaybeUninit::new(0); 8]);
+///
+/// let aligned = buf.0.as_mut_ptr().cast::<MaybeUninit<u32>>();
+/// assert!(unsafe { owning_ref::from_maybeuninit_checked(aligned) }.is_ok());
+///
+/// // Deliberately misaligned, as a buggy reinterpretation might produce.
+/// let misaligned = buf.0[1..].as_mut_ptr().cast::<MaybeUninit<u32>>();
+/// assert_eq!(
+///     unsafe { owning_ref::from_maybeuninit_checked(misaligned) }.err(),
+///     Some(Misaligned)
+/// );
+/// ```
+pub unsafe fn from_maybeuninit_checked<'a, T: 'a>(
+    slot: *mut MaybeUninit<T>,
+) -> Result<OwningRef<'a, T>, Misaligned> {
+    if !slot.is_aligned() {
+        return Err(Misaligned);
+    }
+    Ok(from_maybeuninit(&mut *slot))
+}
+
+/// A `#[repr(C, align(N))]` newtype around [`Inner`][AlignWrapper::Inner],
+/// used to over-align borrowed storage. See [`from_aligned_wrapper`].
+///
+/// # Safety
+///
+/// `Self` must be a `#[repr(C)]` (or `#[repr(transparent)]`) struct whose only
+/// field is of type `Self::Inner`, so that the field is at offset 0, and must
+/// not implement [`Drop`].
+pub unsafe trait AlignWrapper {
+    /// The wrapped type.
+    type Inner;
+}
+
+/// Create a `OwningRef<'a, T>` to the field of a `&'a mut MaybeUninit<W>>`,
+/// where `W` is an over-aligned wrapper around `T`.
+///
+/// The returned pointer is the address of the wrapper, so it keeps the
+/// alignment of `W`, not just that of `T`. This is useful for borrowing
+/// over-aligned scratch space, e.g. for SIMD.
+///
+/// # Safety
+///
+/// The `W` must be initialized, see [`MaybeUninit::assume_init_mut`] and
+/// [`MaybeUninit::assume_init_drop`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_ref::{self, AlignWrapper};
+/// #[repr(C, align(64))]
+/// struct CacheAligned([f32; 4]);
+/// unsafe impl AlignWrapper for CacheAligned {
+///     type Inner = [f32; 4];
+/// }
+///
+/// let mut slot = MaybeUninit::new(CacheAligned([1.0, 2.0, 3.0, 4.0]));
+/// let mut lanes = unsafe { owning_ref::from_aligned_wrapper(&mut slot) };
+/// assert_eq!(lanes.as_ptr() as usize % 64, 0);
+/// lanes[3] = 5.0;
+/// assert_eq!(*lanes, [1.0, 2.0, 3.0, 5.0]);
+/// ```
+pub unsafe fn from_aligned_wrapper<T, W: AlignWrapper<Inner = T>>(
+    slot: &mut MaybeUninit<W>,
+) -> OwningRef<'_, T> {
+    Box::from_raw_in(
+        slot as *mut MaybeUninit<W> as *mut T,
+        NoopAllocator(PhantomData),
+    )
+}
+
+/// Create a `OwningRef<'a, Inner>` to the field at `offset` bytes into a `&'a
+/// mut MaybeUninit<Outer>>`.
+///
+/// The `OwningRef` owns only that field: dropping it drops the field, and
+/// leaves the rest of the `Outer` alone. Use [`field_owning_ref!`] to compute
+/// the offset and check the field's type.
+///
+/// [`field_owning_ref!`]: crate::field_owning_ref
+///
+/// # Safety
+///
+/// There must be an initialized `Inner` at `offset` bytes into the `Outer`,
+/// aligned for `Inner`, e.g. a field at [`offset_of!`][core::mem::offset_of]
+/// of an initialized `Outer`. Dropping or moving out of the `OwningRef` leaves
+/// the `Outer` without a valid value for that field, so the caller is
+/// responsible for the rest of the `Outer`: its other fields must be dropped
+/// individually or leaked, and the `Outer` must not be used as a whole again
+/// until it is overwritten.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::{mem::{offset_of, MaybeUninit}, ptr};
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_ref;
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// struct Counted(&'static str);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+/// struct Outer {
+///     id: u32,
+///     name: Counted,
+///     extra: Counted,
+/// }
+///
+/// let mut slot = MaybeUninit::new(Outer {
+///     id: 7,
+///     name: Counted("name"),
+///     extra: Counted("extra"),
+/// });
+/// let name: owning_ref::OwningRef<'_, Counted> =
+///     unsafe { owning_ref::from_field(&mut slot, offset_of!(Outer, name)) };
+/// assert_eq!(name.0, "name");
+/// drop(name);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+///
+/// // The other fields are still there, and still need to be dropped.
+/// let outer = slot.as_mut_ptr();
+/// assert_eq!(unsafe { (*outer).id }, 7);
+/// unsafe { ptr::drop_in_place(&raw mut (*outer).extra) };
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+/// ```
+pub unsafe fn from_field<Outer, Inner>(
+    slot: &mut MaybeUninit<Outer>,
+    offset: usize,
+) -> OwningRef<'_, Inner> {
+    debug_assert!(
+        offset
+            .checked_add(core::mem::size_of::<Inner>())
+            .is_some_and(|end| end <= core::mem::size_of::<Outer>()),
+        "field out of bounds"
+    );
+    let ptr = slot.as_mut_ptr().byte_add(offset).cast::<Inner>();
+    debug_assert!(ptr.is_aligned(), "field is misaligned");
+    Box::from_raw_in(ptr, NoopAllocator(PhantomData))
+}
+
+/// Implementation detail of [`field_owning_ref!`][crate::field_owning_ref],
+/// which infers `Inner` from the `field` projection.
+#[doc(hidden)]
+pub unsafe fn __from_field_typed<Outer, Inner>(
+    slot: &mut MaybeUninit<Outer>,
+    offset: usize,
+    _field: fn(&Outer) -> &Inner,
+) -> OwningRef<'_, Inner> {
+    from_field(slot, offset)
+}
+
 /// Create a `OwningRef<'a, T>` from a `&'a mut MaybeUninit<T>>` by writing a
 /// value into it.
 ///
@@ -57,9 +373,243 @@ pub fn from_maybeuninit_write<T>(slot: &mut MaybeUninit<T>, value: T) -> OwningR
     }
 }
 
+/// Create a `OwningRef<'a, T>` from a `&'a mut MaybeUninit<T>>` by writing a
+/// value into it, after letting `configure` mutate the value.
+///
+/// This is [`from_maybeuninit_write`] followed by a call to `configure` with
+/// `&mut T`, returning its result alongside the `OwningRef`, to keep a
+/// builder's configuration step separate from handing off the value. If
+/// `configure` panics, the value is dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_ref;
+/// struct Config {
+///     retries: u32,
+///     name: String,
+/// }
+///
+/// let mut slot = MaybeUninit::uninit();
+/// let (config, old_retries) = owning_ref::build_then_box(
+///     &mut slot,
+///     Config {
+///         retries: 3,
+///         name: String::from("default"),
+///     },
+///     |config| {
+///         config.name.push_str("-tuned");
+///         std::mem::replace(&mut config.retries, 5)
+///     },
+/// );
+/// assert_eq!(old_retries, 3);
+/// assert_eq!((config.retries, config.name.as_str()), (5, "default-tuned"));
+/// ```
+pub fn build_then_box<T, R>(
+    slot: &mut MaybeUninit<T>,
+    value: T,
+    configure: impl FnOnce(&mut T) -> R,
+) -> (OwningRef<'_, T>, R) {
+    let mut b = from_maybeuninit_write(slot, value);
+    let result = configure(&mut b);
+    (b, result)
+}
+
+/// Create a `OwningRef<'a, U>` from a `&'a mut MaybeUninit<T>>` by writing the
+/// value returned by `f` into it, then unsizing it to `U`, e.g. a trait object.
+///
+/// This lets the concrete type be chosen where the slot is declared, while the
+/// rest of the code only names the unsized type.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_ref::{self, OwningRef};
+/// trait Draw {
+///     fn draw(&self) -> String;
+/// }
+/// struct Circle(u32);
+/// struct Square(u32);
+/// impl Draw for Circle {
+///     fn draw(&self) -> String {
+///         format!("circle r={}", self.0)
+///     }
+/// }
+/// impl Draw for Square {
+///     fn draw(&self) -> String {
+///         format!("square s={}", self.0)
+///     }
+/// }
+///
+/// let mut circle = MaybeUninit::uninit();
+/// let mut square = MaybeUninit::uninit();
+/// let shapes: [OwningRef<'_, dyn Draw>; 2] = [
+///     owning_ref::from_fn_dyn(&mut circle, || Circle(1)),
+///     owning_ref::from_fn_dyn(&mut square, || Square(2)),
+/// ];
+/// let drawn: Vec<String> = shapes.iter().map(|s| s.draw()).collect();
+/// assert_eq!(drawn, ["circle r=1", "square s=2"]);
+/// ```
+pub fn from_fn_dyn<T, U: ?Sized>(
+    slot: &mut MaybeUninit<T>,
+    f: impl FnOnce() -> T,
+) -> OwningRef<'_, U>
+where
+    T: Unsize<U>,
+{
+    from_maybeuninit_write(slot, f())
+}
+
+/// Create a `OwningRef<'a, MaybeUninit<T>>` from a `&'a mut MaybeUninit<T>>`,
+/// to be initialized later and converted with [`assume_init`].
+///
+/// This is like [`Box::new_uninit`], for borrowed storage. Dropping the
+/// `OwningRef` does not drop the (maybe uninitialized) `T`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_ref::{self, OwningRef};
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// struct Counted(u32);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// fn fill(mut slot: OwningRef<'_, MaybeUninit<Counted>>) -> OwningRef<'_, Counted> {
+///     slot.write(Counted(7));
+///     unsafe { owning_ref::assume_init(slot) }
+/// }
+///
+/// let mut slot = MaybeUninit::uninit();
+/// let value = fill(owning_ref::uninit(&mut slot));
+/// assert_eq!(value.0, 7);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+/// drop(value);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+///
+/// // Dropping the uninitialized handle drops nothing.
+/// let mut slot = MaybeUninit::uninit();
+/// let mut uninit = owning_ref::uninit(&mut slot);
+/// uninit.write(Counted(8));
+/// drop(uninit);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+/// ```
+pub fn uninit<T>(slot: &mut MaybeUninit<T>) -> OwningRef<'_, MaybeUninit<T>> {
+    unsafe { Box::from_raw_in(slot, NoopAllocator(PhantomData)) }
+}
+
+/// Convert a `OwningRef<'a, MaybeUninit<T>>` into a `OwningRef<'a, T>`.
+///
+/// This is [`Box::assume_init`]; see [`uninit`].
+///
+/// # Safety
+///
+/// The `T` must be initialized, see [`MaybeUninit::assume_init`].
+pub unsafe fn assume_init<'a, T>(b: OwningRef<'a, MaybeUninit<T>>) -> OwningRef<'a, T> {
+    b.assume_init()
+}
+
+/// Converts a `&'a mut MaybeUninit<T>` into an uninitialized
+/// `OwningRef<'a, MaybeUninit<T>>`; this is [`uninit`].
+///
+/// Only the conversions that do not assume the slot is initialized are
+/// provided as `From` impls: a `From<&mut MaybeUninit<T>> for OwningRef<'_, T>`
+/// would have to be `unsafe`, which trait methods called through `.into()`
+/// cannot be. Use [`from_maybeuninit`] or [`from_maybeuninit_write`] for those.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_ref::OwningRef;
+/// fn make<'a, S: Into<OwningRef<'a, MaybeUninit<u32>>>>(slot: S) -> u32 {
+///     let mut slot = slot.into();
+///     *slot.write(42)
+/// }
+/// let mut slot = MaybeUninit::uninit();
+/// assert_eq!(make(&mut slot), 42);
+/// ```
+impl<'a, T> From<&'a mut MaybeUninit<T>> for OwningRef<'a, MaybeUninit<T>> {
+    fn from(slot: &'a mut MaybeUninit<T>) -> Self {
+        uninit(slot)
+    }
+}
+
+/// Converts a `&'a mut [MaybeUninit<T>]` into an uninitialized
+/// `OwningRef<'a, [MaybeUninit<T>]>` of the same length.
+///
+/// As with the `From<&mut MaybeUninit<T>>` impl, there is no safe conversion
+/// to an initialized `OwningRef<'a, [T]>`; use [`from_maybeuninit_slice`].
+/// For an empty [`OwningSlice`][crate::owning_slice::OwningSlice], use
+/// [`empty_from_maybeuninit_slice`][crate::owning_slice::empty_from_maybeuninit_slice].
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_ref::OwningRef;
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 4];
+/// let mut slots: OwningRef<'_, [MaybeUninit<u8>]> = (&mut buf[..]).into();
+/// assert_eq!(slots.len(), 4);
+/// slots[0].write(1);
+/// ```
+impl<'a, T> From<&'a mut [MaybeUninit<T>]> for OwningRef<'a, [MaybeUninit<T>]> {
+    fn from(slot: &'a mut [MaybeUninit<T>]) -> Self {
+        unsafe { Box::from_raw_in(slot, NoopAllocator(PhantomData)) }
+    }
+}
+
+/// Create a `OwningRef<'a, T>` from a `&'a mut MaybeUninit<T>>` by replacing
+/// the value in it, returning the old value.
+///
+/// Unlike [`from_maybeuninit_write`], which overwrites (and so leaks) any value
+/// already in the slot, this moves the old value out and returns it.
+///
+/// # Safety
+///
+/// The `T` must be initialized, see [`MaybeUninit::assume_init_read`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_ref;
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// struct Counted(&'static str);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut slot = MaybeUninit::new(Counted("old"));
+/// let (new, old) = unsafe { owning_ref::from_maybeuninit_replace(&mut slot, Counted("new")) };
+/// assert_eq!((new.0, old.0), ("new", "old"));
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+/// drop(new);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+/// drop(old);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+/// ```
+pub unsafe fn from_maybeuninit_replace<T>(
+    slot: &mut MaybeUninit<T>,
+    value: T,
+) -> (OwningRef<'_, T>, T) {
+    let old = slot.assume_init_read();
+    (from_maybeuninit_write(slot, value), old)
+}
+
 /// Create a `OwningRef<'a, [T]>` from a `&'a mut [MaybeUninit<T>]>`.
 ///
-/// # Safety:
+/// # Safety
 ///
 /// All slice elements must be initialized, see [`MaybeUninit::assume_init_mut`]
 /// and [`MaybeUninit::assume_init_drop`].
@@ -69,3 +619,992 @@ pub unsafe fn from_maybeuninit_slice<T>(slot: &mut [MaybeUninit<T>]) -> OwningRe
         NoopAllocator(PhantomData),
     )
 }
+
+/// Create a `OwningRef<'a, [T]>` owning the first `initialized` elements of a
+/// `&'a mut [MaybeUninit<T>]>`.
+///
+/// The rest of the slice stays borrowed for `'a`, but is not owned, so it is
+/// never read or dropped.
+///
+/// # Panics
+///
+/// Panics if `initialized > slot.len()`.
+///
+/// # Safety
+///
+/// The first `initialized` elements must be initialized, see
+/// [`from_maybeuninit_slice`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_ref;
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// struct Counted(u32);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut buf = [const { MaybeUninit::uninit() }; 5];
+/// buf[0].write(Counted(1));
+/// buf[1].write(Counted(2));
+/// let prefix = unsafe { owning_ref::from_maybeuninit_slice_prefix(&mut buf, 2) };
+/// assert_eq!(prefix.len(), 2);
+/// assert_eq!(prefix[1].0, 2);
+/// drop(prefix);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+/// ```
+pub unsafe fn from_maybeuninit_slice_prefix<T>(
+    slot: &mut [MaybeUninit<T>],
+    initialized: usize,
+) -> OwningRef<'_, [T]> {
+    assert!(
+        initialized <= slot.len(),
+        "initialized length {} is greater than slice length {}",
+        initialized,
+        slot.len()
+    );
+    from_maybeuninit_slice(&mut slot[..initialized])
+}
+
+/// Create a `OwningRef<'a, [T]>` from a `&'a mut [MaybeUninit<T>]` by writing
+/// `f(i)` into the element at each index `i`.
+///
+/// The whole slice is filled, in order. If `f` panics, the elements written so
+/// far are dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_ref;
+/// let mut buf = [const { MaybeUninit::uninit() }; 4];
+/// let squares = owning_ref::boxed_slice_from_fn(&mut buf, |i| (i * i).to_string());
+/// assert_eq!(squares.len(), 4);
+/// assert_eq!(*squares, ["0", "1", "4", "9"]);
+/// ```
+///
+/// If `f` panics, exactly the elements written so far are dropped:
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_ref;
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// struct Counted(usize);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut buf = [const { MaybeUninit::uninit() }; 5];
+/// let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+///     owning_ref::boxed_slice_from_fn(&mut buf, |i| {
+///         assert!(i < 3, "no fourth value");
+///         Counted(i)
+///     });
+/// }));
+/// assert!(result.is_err());
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 3);
+/// ```
+pub fn boxed_slice_from_fn<T>(
+    slot: &mut [MaybeUninit<T>],
+    mut f: impl FnMut(usize) -> T,
+) -> OwningRef<'_, [T]> {
+    let len = slot.len();
+    let mut guard = PartialInitGuard::new(slot);
+    for i in 0..len {
+        guard.push(f(i));
+    }
+    guard.finish().into_boxed_slice()
+}
+
+/// Create a `OwningRef<'a, (A, B)>` from a `&'a mut MaybeUninit<(A, B)>>` by
+/// writing `(a, b)` into it.
+///
+/// The `MaybeUninit<(A, B)>` will be overwritten. See [`split_pair`] to split
+/// the result into an `OwningRef` for each element.
+pub fn from_tuple<A, B>(slot: &mut MaybeUninit<(A, B)>, a: A, b: B) -> OwningRef<'_, (A, B)> {
+    from_maybeuninit_write(slot, (a, b))
+}
+
+/// Split a `OwningRef<'a, (A, B)>` into an `OwningRef<'a, A>` and an
+/// `OwningRef<'a, B>`, each owning one element of the pair in-place.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_ref;
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// struct Counted(&'static str);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut slot = MaybeUninit::uninit();
+/// let pair = owning_ref::from_tuple(&mut slot, Counted("a"), Counted("b"));
+/// let (a, b) = owning_ref::split_pair(pair);
+/// assert_eq!((a.0, b.0), ("a", "b"));
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+/// drop(b);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+/// drop(a);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+/// ```
+pub fn split_pair<'a, A, B>(pair: OwningRef<'a, (A, B)>) -> (OwningRef<'a, A>, OwningRef<'a, B>) {
+    let (ptr, _) = Box::into_raw_with_allocator(pair);
+    unsafe {
+        (
+            Box::from_raw_in(&raw mut (*ptr).0, NoopAllocator(PhantomData)),
+            Box::from_raw_in(&raw mut (*ptr).1, NoopAllocator(PhantomData)),
+        )
+    }
+}
+
+/// Create an `[OwningRef<'a, T>; N]` from a `&'a mut [MaybeUninit<T>; N]` by
+/// writing `init(i)` into the `i`th slot, so that each element is owned by its
+/// own `OwningRef`.
+///
+/// This saves declaring a separate `MaybeUninit` slot for each `OwningRef`.
+/// Slots are initialized in order; if `init` panics, the values written so far
+/// are dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_ref;
+/// let mut slots = [const { MaybeUninit::uninit() }; 3];
+/// let [a, b, c] = owning_ref::array_of_boxes(&mut slots, |i| format!("item {i}"));
+/// assert_eq!([&*a, &*b, &*c], ["item 0", "item 1", "item 2"]);
+/// drop(b);
+/// assert_eq!(*c, "item 2");
+/// ```
+///
+/// If `init` panics, the values written so far are dropped:
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_ref;
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// struct Counted(usize);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut slots = [const { MaybeUninit::uninit() }; 3];
+/// let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+///     owning_ref::array_of_boxes(&mut slots, |i| {
+///         assert!(i < 2, "no third value");
+///         Counted(i)
+///     });
+/// }));
+/// assert!(result.is_err());
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+/// ```
+pub fn array_of_boxes<'a, T, const N: usize>(
+    slots: &'a mut [MaybeUninit<T>; N],
+    mut init: impl FnMut(usize) -> T,
+) -> [OwningRef<'a, T>; N] {
+    // `array::map` drops the outputs produced so far if the closure panics.
+    let mut i = 0;
+    slots.each_mut().map(|slot| {
+        let value = init(i);
+        i += 1;
+        from_maybeuninit_write(slot, value)
+    })
+}
+
+/// Create a `Pin<OwningRef<'a, [T]>>` from a `&'a mut [MaybeUninit<T>]>`.
+///
+/// The elements stay at the address of the borrowed slice for as long as the
+/// `OwningRef` exists, so they can be pinned in-place, e.g. for an array of
+/// futures on the stack.
+///
+/// # Safety
+///
+/// All slice elements must be initialized, see [`MaybeUninit::assume_init_mut`]
+/// and [`MaybeUninit::assume_init_drop`].
+///
+/// Additionally, unless `T: Unpin`, the returned `Pin` must be dropped before
+/// `'a` ends, i.e. it must not be leaked (e.g. with [`core::mem::forget`]).
+/// [`Pin`] guarantees that a pinned value is dropped before its memory is
+/// reused, but the borrowed memory can be reused as soon as the borrow ends,
+/// whether or not the elements were dropped. (This is also why
+/// [`Box::into_pin`] requires a `'static` allocator.)
+pub unsafe fn pin_from_maybeuninit_slice<T>(
+    slot: &mut [MaybeUninit<T>],
+) -> Pin<OwningRef<'_, [T]>> {
+    Pin::new_unchecked(from_maybeuninit_slice(slot))
+}
+
+/// Create a `Pin<OwningRef<'a, [T]>>` from a `&'a mut [MaybeUninit<T>]>` by
+/// filling it with copies of `value`.
+///
+/// Unlike [`pin_from_maybeuninit_slice`], this is safe even if the result is
+/// leaked: `T: Copy` means `T` has no drop glue, so nothing can rely on it
+/// being dropped before its memory is reused.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::{mem::MaybeUninit, pin::Pin};
+/// use noop_allocator::owning_ref;
+/// let mut buf = [MaybeUninit::<u32>::uninit(); 4];
+/// let start = buf.as_ptr().cast::<u32>();
+/// let mut pinned = owning_ref::pin_filled(&mut buf, 7);
+/// assert_eq!(pinned.as_ptr(), start);
+///
+/// // `u32: Unpin`, so the elements can still be mutated.
+/// Pin::get_mut(pinned.as_mut())[1] = 8;
+/// assert_eq!(*pinned, [7, 8, 7, 7]);
+/// ```
+///
+/// Elements that are not `Unpin` cannot be accessed mutably (or moved out):
+///
+/// ```rust,compile_fail,E0277
+/// # use std::{marker::PhantomPinned, mem::MaybeUninit, pin::Pin};
+/// use noop_allocator::owning_ref;
+/// let mut buf = [MaybeUninit::<PhantomPinned>::uninit(); 4];
+/// let mut pinned = owning_ref::pin_filled(&mut buf, PhantomPinned);
+/// let slice: &mut [PhantomPinned] = Pin::get_mut(pinned.as_mut());
+/// ```
+pub fn pin_filled<T: Copy>(slot: &mut [MaybeUninit<T>], value: T) -> Pin<OwningRef<'_, [T]>> {
+    slot.fill(MaybeUninit::new(value));
+    unsafe { pin_from_maybeuninit_slice(slot) }
+}
+
+/// Shorten the lifetime of an `OwningRef<'long, T>` to `OwningRef<'short, T>`.
+///
+/// `NoopAllocator<'a>` is covariant in `'a`, so this is a plain subtyping
+/// coercion and a no-op at runtime. It exists to make that guarantee explicit
+/// (and tested): if `NoopAllocator`'s variance ever changes, this function
+/// will stop compiling.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_ref::{self, OwningRef};
+/// struct Holder<'short> {
+///     value: OwningRef<'short, [u32]>,
+///     name: &'short str,
+/// }
+///
+/// fn store<'short, 'long: 'short>(
+///     value: OwningRef<'long, [u32]>,
+///     name: &'short str,
+/// ) -> Holder<'short> {
+///     Holder {
+///         value: owning_ref::shorten(value),
+///         name,
+///     }
+/// }
+///
+/// let mut buf = [MaybeUninit::new(1), MaybeUninit::new(2)];
+/// let long = unsafe { owning_ref::from_maybeuninit_slice(&mut buf) };
+/// let name = String::from("short-lived");
+/// let holder = store(long, &name);
+/// assert_eq!((&*holder.value, holder.name), (&[1, 2][..], "short-lived"));
+/// ```
+pub fn shorten<'short, 'long: 'short, T: ?Sized>(b: OwningRef<'long, T>) -> OwningRef<'short, T> {
+    b
+}
+
+/// Returns `true` if an `OwningRef<'_, T>` points into `slot`.
+///
+/// This is a debugging aid, to check that an `OwningRef` was built in-place in
+/// the expected slot and not copied elsewhere. The slot is taken as a pointer,
+/// since the `OwningRef` holds the only reference to it while it exists; take
+/// the pointer before creating the `OwningRef`, e.g. with `&raw const slot`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_ref;
+/// let mut slots = [MaybeUninit::<u64>::uninit(); 2];
+/// let [a, b] = &mut slots;
+/// let (a_ptr, b_ptr) = (&raw const *a, &raw const *b);
+/// let boxed = owning_ref::from_maybeuninit_write(a, 7);
+/// assert!(owning_ref::assert_points_into(&boxed, a_ptr));
+/// assert!(!owning_ref::assert_points_into(&boxed, b_ptr));
+/// ```
+pub fn assert_points_into<T>(b: &OwningRef<'_, T>, slot: *const MaybeUninit<T>) -> bool {
+    core::ptr::eq(&raw const **b, slot.cast::<T>())
+}
+
+/// Return the [`Layout`] of the storage borrowed by an `OwningRef<'a, T>`,
+/// e.g. to report how much of a buffer it occupies.
+///
+/// This is [`Layout::for_value`] of the value, so it has size 0 for
+/// zero-sized values.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::{alloc::Layout, mem::MaybeUninit};
+/// use noop_allocator::owning_ref;
+/// let mut slot = MaybeUninit::uninit();
+/// let b = owning_ref::from_maybeuninit_write(&mut slot, [0u16; 3]);
+/// assert_eq!(owning_ref::backing_layout(&b), Layout::new::<[u16; 3]>());
+///
+/// let mut slots = [MaybeUninit::new(1u64), MaybeUninit::new(2)];
+/// let b = unsafe { owning_ref::from_maybeuninit_slice(&mut slots) };
+/// assert_eq!(
+///     owning_ref::backing_layout(&b),
+///     Layout::array::<u64>(2).unwrap()
+/// );
+///
+/// let mut slot = MaybeUninit::uninit();
+/// let b = owning_ref::from_maybeuninit_write(&mut slot, ());
+/// assert_eq!(owning_ref::backing_layout(&b).size(), 0);
+/// ```
+pub fn backing_layout<T: ?Sized>(b: &OwningRef<'_, T>) -> Layout {
+    Layout::for_value::<T>(b)
+}
+
+/// Returns a value that displays the address of the value owned by `b`, i.e.
+/// of its slot, like `{:p}`.
+///
+/// This is the same as formatting the `OwningRef` itself with `{:p}`, but can
+/// be passed wherever a [`Display`][fmt::Display] is expected, e.g. as a
+/// structured logging field. Formatting flags are forwarded to
+/// [`fmt::Pointer`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_ref;
+/// let mut slot = MaybeUninit::uninit();
+/// let expected = format!("{:p}", slot.as_ptr());
+/// let b = owning_ref::from_maybeuninit_write(&mut slot, 42u32);
+/// assert_eq!(owning_ref::ptr_fmt(&b).to_string(), expected);
+/// assert_eq!(format!("{:p}", b), expected);
+/// assert_eq!(
+///     format!("{:>20}", owning_ref::ptr_fmt(&b)),
+///     format!("{:>20p}", &raw const *b),
+/// );
+/// ```
+pub fn ptr_fmt<T: ?Sized>(b: &OwningRef<'_, T>) -> impl fmt::Display {
+    PtrFmt((&raw const **b).cast())
+}
+
+/// The [`fmt::Display`] returned by [`ptr_fmt`] and
+/// [`owning_slice::ptr_fmt`][crate::owning_slice::ptr_fmt].
+pub(crate) struct PtrFmt(pub(crate) *const u8);
+
+impl fmt::Display for PtrFmt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&self.0, f)
+    }
+}
+
+/// An [`OwningRef<'a, T>`][OwningRef] with a hook that is run on the value just
+/// before it is dropped.
+///
+/// This is useful when the borrowed value holds handles that need to be
+/// flushed or released in a particular order before its destructor runs. The
+/// hook is called exactly once when the `DropOrdered` is dropped, including
+/// during unwinding, and the value is dropped afterwards even if the hook
+/// panics.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::Mutex;
+/// use noop_allocator::owning_ref::{self, DropOrdered};
+/// static LOG: Mutex<Vec<&str>> = Mutex::new(vec![]);
+/// struct Handle(Vec<u8>);
+/// impl Drop for Handle {
+///     fn drop(&mut self) {
+///         LOG.lock().unwrap().push("drop");
+///     }
+/// }
+/// let flush = |handle: &mut Handle| {
+///     handle.0.clear();
+///     LOG.lock().unwrap().push("flush");
+/// };
+///
+/// let mut slot = MaybeUninit::uninit();
+/// let mut handle = DropOrdered::new(
+///     owning_ref::from_maybeuninit_write(&mut slot, Handle(vec![])),
+///     flush,
+/// );
+/// handle.0.push(1);
+/// drop(handle);
+/// assert_eq!(*LOG.lock().unwrap(), ["flush", "drop"]);
+///
+/// // The hook also runs exactly once when unwinding.
+/// LOG.lock().unwrap().clear();
+/// let result = std::panic::catch_unwind(|| {
+///     let mut slot = MaybeUninit::uninit();
+///     let _handle = DropOrdered::new(
+///         owning_ref::from_maybeuninit_write(&mut slot, Handle(vec![])),
+///         flush,
+///     );
+///     panic!("oops");
+/// });
+/// assert!(result.is_err());
+/// assert_eq!(*LOG.lock().unwrap(), ["flush", "drop"]);
+/// ```
+pub struct DropOrdered<'a, T: ?Sized, F: FnOnce(&mut T)> {
+    value: ManuallyDrop<OwningRef<'a, T>>,
+    on_drop: ManuallyDrop<F>,
+}
+
+impl<'a, T: ?Sized, F: FnOnce(&mut T)> DropOrdered<'a, T, F> {
+    /// Wraps `value`, to call `on_drop` on it before it is dropped.
+    pub fn new(value: OwningRef<'a, T>, on_drop: F) -> Self {
+        Self {
+            value: ManuallyDrop::new(value),
+            on_drop: ManuallyDrop::new(on_drop),
+        }
+    }
+
+    /// Returns the `OwningRef` and the hook, without calling the hook.
+    pub fn into_parts(self) -> (OwningRef<'a, T>, F) {
+        let mut this = ManuallyDrop::new(self);
+        unsafe {
+            (
+                ManuallyDrop::take(&mut this.value),
+                ManuallyDrop::take(&mut this.on_drop),
+            )
+        }
+    }
+}
+
+impl<T: ?Sized, F: FnOnce(&mut T)> core::ops::Deref for DropOrdered<'_, T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: ?Sized, F: FnOnce(&mut T)> core::ops::DerefMut for DropOrdered<'_, T, F> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T: ?Sized + fmt::Debug, F: FnOnce(&mut T)> fmt::Debug for DropOrdered<'_, T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DropOrdered").field(&&**self).finish()
+    }
+}
+
+impl<T: ?Sized, F: FnOnce(&mut T)> Drop for DropOrdered<'_, T, F> {
+    fn drop(&mut self) {
+        /// Drops the value even if the hook panics.
+        struct DropValue<'r, 'a, T: ?Sized>(&'r mut ManuallyDrop<OwningRef<'a, T>>);
+        impl<T: ?Sized> Drop for DropValue<'_, '_, T> {
+            fn drop(&mut self) {
+                unsafe { ManuallyDrop::drop(self.0) }
+            }
+        }
+
+        let on_drop = unsafe { ManuallyDrop::take(&mut self.on_drop) };
+        let value = DropValue(&mut self.value);
+        on_drop(value.0);
+    }
+}
+
+/// A `#[repr(C)]` struct whose fields are a [`Header`][TrailingDst::Header]
+/// followed by a trailing `[`[`Item`][TrailingDst::Item]`]`, the shape built
+/// by a [`DstBuilder`].
+///
+/// Implement this with [`dst_owning_ref!`][crate::dst_owning_ref], which
+/// defines the struct as well, so its layout is known to be right.
+///
+/// # Safety
+///
+/// `Self` must be a `#[repr(C)]` struct with exactly two fields, of types
+/// `Self::Header` and `[Self::Item]`, in that order, and
+/// [`from_slice_ptr`][TrailingDst::from_slice_ptr] must return its argument,
+/// cast with `as`.
+pub unsafe trait TrailingDst {
+    /// The type of the first field.
+    type Header;
+    /// The element type of the trailing slice field.
+    type Item;
+
+    /// Reinterprets a pointer to the trailing items, with the address of the
+    /// whole struct and the number of trailing items as its metadata.
+    fn from_slice_ptr(ptr: *mut [Self::Item]) -> *mut Self;
+}
+
+/// Builds a custom dynamically sized type, a header followed by a trailing
+/// slice, in a borrowed byte buffer, producing an `OwningRef` to it.
+///
+/// The header is written by [`new`][DstBuilder::new], which aligns the start
+/// of the value within the buffer; trailing items are then
+/// [`push`][DstBuilder::push]ed until [`finish`][DstBuilder::finish] turns the
+/// builder into an `OwningRef<'a, D>` for a [`TrailingDst`] `D` with
+/// `D::Header = H` and `D::Item = T`. If the builder is dropped before it is
+/// finished, it drops the header and the items pushed so far.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::{dst_owning_ref, owning_ref::DstBuilder};
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// #[derive(Debug)]
+/// struct Chunk(String);
+/// impl Drop for Chunk {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Header {
+///     id: u16,
+///     checksum: u64,
+/// }
+///
+/// dst_owning_ref! {
+///     #[derive(Debug)]
+///     struct Packet {
+///         header: Header,
+///         chunks: [Chunk],
+///     }
+/// }
+///
+/// let mut buf = [MaybeUninit::uninit(); 256];
+/// let mut builder = DstBuilder::new(&mut buf, Header { id: 7, checksum: 0 }).unwrap();
+/// for s in ["hello", ", ", "world"] {
+///     builder.push(Chunk(s.into())).unwrap();
+/// }
+/// builder.header_mut().checksum = 0xC0FFEE;
+/// let mut packet = builder.finish::<Packet>();
+///
+/// assert_eq!(
+///     packet.header,
+///     Header {
+///         id: 7,
+///         checksum: 0xC0FFEE
+///     }
+/// );
+/// assert_eq!(packet.chunks.len(), 3);
+/// packet.chunks[2].0.push('!');
+/// let text: String = packet.chunks.iter().map(|c| c.0.as_str()).collect();
+/// assert_eq!(text, "hello, world!");
+/// assert_eq!((&raw const *packet).addr() % align_of::<Header>(), 0);
+///
+/// drop(packet);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 3);
+/// ```
+pub struct DstBuilder<'a, H, T> {
+    base: NonNull<u8>,
+    data_offset: usize,
+    capacity: usize,
+    len: usize,
+    _marker: PhantomData<(&'a mut [MaybeUninit<u8>], H, T)>,
+}
+
+impl<'a, H, T> DstBuilder<'a, H, T> {
+    /// Writes `header` at the first suitably aligned address in `buf`.
+    ///
+    /// Returns `header` back if `buf` is too small to hold it (with no
+    /// trailing items) after aligning it.
+    pub fn new(buf: &'a mut [MaybeUninit<u8>], header: H) -> Result<Self, H> {
+        let (first, data_offset) = Layout::new::<H>()
+            .extend(Layout::new::<T>())
+            .expect("header and item layouts overflow");
+        let align = first.align();
+        let pad = buf.as_ptr().align_offset(align);
+        let Some(available) = buf.len().checked_sub(pad) else {
+            return Err(header);
+        };
+        // The value's size is a multiple of its alignment, so it must fit in
+        // the largest such multiple that fits in the buffer.
+        let usable = available - available % align;
+        let Some(room) = usable.checked_sub(data_offset) else {
+            return Err(header);
+        };
+        let capacity = room
+            .checked_div(core::mem::size_of::<T>())
+            .unwrap_or(usize::MAX);
+        let base = unsafe { NonNull::new_unchecked(buf.as_mut_ptr().add(pad).cast::<u8>()) };
+        unsafe { base.cast::<H>().write(header) };
+        Ok(Self {
+            base,
+            data_offset,
+            capacity,
+            len: 0,
+            _marker: PhantomData,
+        })
+    }
+
+    /// The number of trailing items pushed so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no trailing items have been pushed yet.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of trailing items that fit in the buffer.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The header written by [`new`][DstBuilder::new].
+    pub fn header(&self) -> &H {
+        unsafe { self.base.cast::<H>().as_ref() }
+    }
+
+    /// The header written by [`new`][DstBuilder::new], mutably.
+    pub fn header_mut(&mut self) -> &mut H {
+        unsafe { self.base.cast::<H>().as_mut() }
+    }
+
+    /// The trailing items pushed so far.
+    pub fn items(&self) -> &[T] {
+        unsafe { core::slice::from_raw_parts(self.items_ptr(), self.len) }
+    }
+
+    fn items_ptr(&self) -> *mut T {
+        unsafe { self.base.as_ptr().add(self.data_offset).cast::<T>() }
+    }
+
+    /// Appends a trailing item, or returns it back if the buffer is full.
+    pub fn push(&mut self, item: T) -> Result<(), T> {
+        if self.len == self.capacity {
+            return Err(item);
+        }
+        unsafe { self.items_ptr().add(self.len).write(item) };
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Finishes the value, as an `OwningRef` to a `D` made of the header and
+    /// the items pushed so far.
+    pub fn finish<D>(self) -> OwningRef<'a, D>
+    where
+        D: TrailingDst<Header = H, Item = T> + ?Sized,
+    {
+        let this = ManuallyDrop::new(self);
+        let items = core::ptr::slice_from_raw_parts_mut(this.base.as_ptr().cast::<T>(), this.len);
+        unsafe { Box::from_raw_in(D::from_slice_ptr(items), NoopAllocator(PhantomData)) }
+    }
+}
+
+impl<H, T> Drop for DstBuilder<'_, H, T> {
+    fn drop(&mut self) {
+        /// Drops the items even if dropping the header panics.
+        struct DropItems<T>(*mut [T]);
+        impl<T> Drop for DropItems<T> {
+            fn drop(&mut self) {
+                unsafe { core::ptr::drop_in_place(self.0) }
+            }
+        }
+
+        let _items = DropItems(core::ptr::slice_from_raw_parts_mut(
+            self.items_ptr(),
+            self.len,
+        ));
+        unsafe { self.base.cast::<H>().drop_in_place() };
+    }
+}
+
+impl<H: fmt::Debug, T: fmt::Debug> fmt::Debug for DstBuilder<'_, H, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DstBuilder")
+            .field("header", self.header())
+            .field("items", &self.items())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+/// Counts the [`LeakTracked`] handles created with it, and how many of them
+/// were dropped, to find handles that were forgotten instead.
+///
+/// An `OwningRef` that is [`core::mem::forget`]ten never drops its value, and
+/// since its allocator does nothing on deallocation, nothing else notices
+/// either. Forgetting is not an event that can be observed, so a
+/// `LeakCounter` instead counts the handles that are still outstanding: every
+/// tracked handle that has not been dropped (or unwrapped with
+/// [`LeakTracked::into_inner`]) by the time it should have been is a leak.
+///
+/// Tracking only happens in debug builds; in release builds all the counts
+/// stay 0.
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+#[derive(Debug, Default)]
+pub struct LeakCounter {
+    tracked: crate::sync::AtomicUsize,
+    dropped: crate::sync::AtomicUsize,
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl LeakCounter {
+    /// Creates a counter with no tracked handles.
+    pub const fn new() -> Self {
+        Self {
+            tracked: crate::sync::AtomicUsize::new(0),
+            dropped: crate::sync::AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of handles tracked, not counting ones that were
+    /// unwrapped with [`LeakTracked::into_inner`].
+    pub fn tracked(&self) -> usize {
+        self.tracked.load(crate::sync::Ordering::Relaxed)
+    }
+
+    /// Returns the number of tracked handles that were dropped.
+    pub fn dropped(&self) -> usize {
+        self.dropped.load(crate::sync::Ordering::Relaxed)
+    }
+
+    /// Returns the number of tracked handles that have not been dropped:
+    /// handles that are still alive, or that were leaked.
+    pub fn leaked(&self) -> usize {
+        // Handles are dropped concurrently with this call, so load `dropped`
+        // first and saturate: the two loads are not a consistent snapshot.
+        let dropped = self.dropped();
+        self.tracked().saturating_sub(dropped)
+    }
+}
+
+/// An [`OwningRef<'a, T>`][OwningRef] whose drop is recorded in a
+/// [`LeakCounter`], created by [`with_leak_tracking`].
+///
+/// In release builds, this is a `#[repr(transparent)]` wrapper around the
+/// `OwningRef`, and records nothing.
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+#[cfg_attr(not(debug_assertions), repr(transparent))]
+pub struct LeakTracked<'a, 'c, T: ?Sized> {
+    value: ManuallyDrop<OwningRef<'a, T>>,
+    #[cfg(debug_assertions)]
+    counter: &'c LeakCounter,
+    #[cfg(not(debug_assertions))]
+    _counter: PhantomData<&'c LeakCounter>,
+}
+
+/// Wraps `b` so that, in debug builds, `counter` records whether it is
+/// dropped.
+///
+/// # Examples
+#[cfg_attr(debug_assertions, doc = "```rust")]
+#[cfg_attr(not(debug_assertions), doc = "```rust,ignore")]
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_ref::{self, LeakCounter};
+/// static COUNTER: LeakCounter = LeakCounter::new();
+///
+/// let mut a = MaybeUninit::uninit();
+/// let mut b = MaybeUninit::uninit();
+/// let a = owning_ref::with_leak_tracking(
+///     owning_ref::from_maybeuninit_write(&mut a, String::from("dropped")),
+///     &COUNTER,
+/// );
+/// let b = owning_ref::with_leak_tracking(
+///     owning_ref::from_maybeuninit_write(&mut b, String::from("forgotten")),
+///     &COUNTER,
+/// );
+/// assert_eq!(COUNTER.tracked(), 2);
+///
+/// drop(a);
+/// std::mem::forget(b);
+/// assert_eq!(COUNTER.dropped(), 1);
+/// assert_eq!(COUNTER.leaked(), 1);
+/// ```
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+#[cfg_attr(not(debug_assertions), allow(unused_variables))]
+pub fn with_leak_tracking<'a, 'c, T: ?Sized>(
+    b: OwningRef<'a, T>,
+    counter: &'c LeakCounter,
+) -> LeakTracked<'a, 'c, T> {
+    #[cfg(debug_assertions)]
+    counter.tracked.fetch_add(1, crate::sync::Ordering::Relaxed);
+    LeakTracked {
+        value: ManuallyDrop::new(b),
+        #[cfg(debug_assertions)]
+        counter,
+        #[cfg(not(debug_assertions))]
+        _counter: PhantomData,
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<'a, T: ?Sized> LeakTracked<'a, '_, T> {
+    /// Stops tracking the handle, and returns the `OwningRef`.
+    ///
+    /// The handle no longer counts as tracked, so it is neither dropped nor
+    /// leaked.
+    pub fn into_inner(this: Self) -> OwningRef<'a, T> {
+        let mut this = ManuallyDrop::new(this);
+        #[cfg(debug_assertions)]
+        this.counter
+            .tracked
+            .fetch_sub(1, crate::sync::Ordering::Relaxed);
+        unsafe { ManuallyDrop::take(&mut this.value) }
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<T: ?Sized> core::ops::Deref for LeakTracked<'_, '_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<T: ?Sized> core::ops::DerefMut for LeakTracked<'_, '_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<T: ?Sized + fmt::Debug> fmt::Debug for LeakTracked<'_, '_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("LeakTracked").field(&&**self).finish()
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<T: ?Sized> Drop for LeakTracked<'_, '_, T> {
+    fn drop(&mut self) {
+        #[cfg(debug_assertions)]
+        self.counter
+            .dropped
+            .fetch_add(1, crate::sync::Ordering::Relaxed);
+        unsafe { ManuallyDrop::drop(&mut self.value) }
+    }
+}
+
+/// Create a `OwningRef<'a, Inner>` to a field of a `&'a mut
+/// MaybeUninit<Outer>>`.
+///
+/// `field_owning_ref!(slot, Outer, field)` is
+/// [`owning_ref::from_field`][crate::owning_ref::from_field] with the offset
+/// of `Outer::field`, and checks that `Inner` is the type of that field. It has
+/// the same safety requirements, so it must be used in an `unsafe` block.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::field_owning_ref;
+/// struct Pair {
+///     key: u8,
+///     value: String,
+/// }
+/// let mut slot = MaybeUninit::new(Pair {
+///     key: 1,
+///     value: String::from("owned"),
+/// });
+/// let mut value = unsafe { field_owning_ref!(&mut slot, Pair, value) };
+/// value.push('!');
+/// assert_eq!(*value, "owned!");
+/// ```
+///
+/// The type of the `OwningRef` must match the field:
+///
+/// ```rust,compile_fail,E0308
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::{field_owning_ref, owning_ref::OwningRef};
+/// struct Pair {
+///     key: u8,
+///     value: String,
+/// }
+/// let mut slot = MaybeUninit::new(Pair {
+///     key: 1,
+///     value: String::new(),
+/// });
+/// let value: OwningRef<'_, Vec<u8>> = unsafe { field_owning_ref!(&mut slot, Pair, value) };
+/// ```
+#[macro_export]
+macro_rules! field_owning_ref {
+    ($slot:expr, $outer:ty, $field:tt $(,)?) => {
+        $crate::owning_ref::__from_field_typed::<$outer, _>(
+            $slot,
+            ::core::mem::offset_of!($outer, $field),
+            |outer| &outer.$field,
+        )
+    };
+}
+
+/// Defines a `#[repr(C)]` struct with a header field and a trailing slice
+/// field, and implements [`TrailingDst`][crate::owning_ref::TrailingDst] for
+/// it, so that it can be built with a
+/// [`DstBuilder`][crate::owning_ref::DstBuilder].
+///
+/// The struct may have attributes, visibilities, and lifetime parameters, but
+/// not type or const parameters.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::{dst_owning_ref, owning_ref::DstBuilder};
+/// dst_owning_ref! {
+///     struct Name {
+///         len: u8,
+///         bytes: [u8],
+///     }
+/// }
+///
+/// let mut buf = [MaybeUninit::uninit(); 16];
+/// let mut builder = DstBuilder::new(&mut buf, 0u8).unwrap();
+/// for &b in b"noop" {
+///     builder.push(b).unwrap();
+///     *builder.header_mut() += 1;
+/// }
+/// let name = builder.finish::<Name>();
+/// assert_eq!(name.len, 4);
+/// assert_eq!(&name.bytes, b"noop");
+/// ```
+#[macro_export]
+macro_rules! dst_owning_ref {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident $(<$($lt:lifetime),+ $(,)?>)? {
+            $(#[$header_attr:meta])*
+            $header_vis:vis $header:ident: $header_ty:ty,
+            $(#[$items_attr:meta])*
+            $items_vis:vis $items:ident: [$item_ty:ty] $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[repr(C)]
+        $vis struct $name $(<$($lt),+>)? {
+            $(#[$header_attr])*
+            $header_vis $header: $header_ty,
+            $(#[$items_attr])*
+            $items_vis $items: [$item_ty],
+        }
+
+        unsafe impl $(<$($lt),+>)? $crate::owning_ref::TrailingDst for $name $(<$($lt),+>)? {
+            type Header = $header_ty;
+            type Item = $item_ty;
+
+            fn from_slice_ptr(ptr: *mut [$item_ty]) -> *mut Self {
+                ptr as *mut Self
+            }
+        }
+    };
+}
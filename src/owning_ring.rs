@@ -0,0 +1,267 @@
+//! A fixed-capacity ring buffer over a borrowed buffer.
+
+use core::{fmt, marker::PhantomData, mem::MaybeUninit, ptr::NonNull};
+
+/// A fixed-capacity double-ended queue borrowing a memory location but owning
+/// the values in it.
+///
+/// Unlike an [`OwningDeque`][crate::owning_deque::OwningDeque], pushing onto a
+/// full `OwningRing` returns the value back as `Err` instead of trying to
+/// allocate, so no operation can abort the process. All pushes and pops are
+/// *O*(1): the elements wrap around the end of the buffer. The capacity is the
+/// length of the borrowed slice, even for zero-sized `T`.
+///
+/// The remaining elements are dropped when the `OwningRing` is dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_ring::OwningRing;
+/// let mut buf: [MaybeUninit<u32>; 3] = [MaybeUninit::uninit(); 3];
+/// let mut ring = OwningRing::new(&mut buf);
+/// ring.push_back(2).unwrap();
+/// ring.push_back(3).unwrap();
+/// ring.push_front(1).unwrap();
+/// assert!(ring.is_full());
+/// assert_eq!(ring.push_back(4), Err(4));
+/// assert_eq!(ring.push_front(0), Err(0));
+///
+/// // `1` wrapped around to the end of the buffer.
+/// assert_eq!(ring.as_slices(), (&[1][..], &[2, 3][..]));
+/// assert_eq!(ring.pop_front(), Some(1));
+/// ring.push_back(4).unwrap();
+/// assert_eq!(ring.iter().copied().collect::<Vec<_>>(), [2, 3, 4]);
+///
+/// assert_eq!(ring.pop_back(), Some(4));
+/// assert_eq!(ring.pop_front(), Some(2));
+/// assert_eq!(ring.pop_back(), Some(3));
+/// assert!(ring.is_empty());
+/// assert_eq!(ring.pop_front(), None);
+/// assert_eq!(ring.pop_back(), None);
+/// ```
+///
+/// Remaining elements are dropped with the ring:
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_ring::OwningRing;
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// struct Counted(u32);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut buf = [const { MaybeUninit::uninit() }; 4];
+/// let mut ring = OwningRing::new(&mut buf);
+/// for i in 0..4 {
+///     ring.push_front(Counted(i)).ok().unwrap();
+/// }
+/// drop(ring.pop_back());
+/// assert!(ring.push_front(Counted(4)).is_ok());
+/// assert!(ring.push_front(Counted(5)).is_err());
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+/// drop(ring);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 6);
+/// ```
+pub struct OwningRing<'a, T> {
+    buf: NonNull<T>,
+    capacity: usize,
+    head: usize,
+    len: usize,
+    _marker: PhantomData<(&'a mut [MaybeUninit<T>], T)>,
+}
+
+// SAFETY: An `OwningRing<'_, T>` owns its elements and exclusively borrows its
+// buffer.
+unsafe impl<T: Send> Send for OwningRing<'_, T> {}
+unsafe impl<T: Sync> Sync for OwningRing<'_, T> {}
+
+impl<'a, T> OwningRing<'a, T> {
+    /// Creates a new, empty `OwningRing<'a, T>` storing elements in `slot`.
+    pub fn new(slot: &'a mut [MaybeUninit<T>]) -> Self {
+        Self {
+            buf: NonNull::from(&mut *slot).cast(),
+            capacity: slot.len(),
+            head: 0,
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of elements in the ring.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the ring contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if the ring cannot hold any more elements.
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity
+    }
+
+    /// Returns the number of elements the ring can hold.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the index in the buffer of the `index`th element, for
+    /// `index <= capacity`.
+    fn physical(&self, index: usize) -> usize {
+        let i = self.head + index;
+        if i >= self.capacity {
+            i - self.capacity
+        } else {
+            i
+        }
+    }
+
+    /// Appends `value` to the back of the ring, or returns it back if the ring
+    /// is full.
+    pub fn push_back(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+        unsafe { self.buf.add(self.physical(self.len)).write(value) };
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Prepends `value` to the front of the ring, or returns it back if the
+    /// ring is full.
+    pub fn push_front(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+        self.head = self.physical(self.capacity - 1);
+        unsafe { self.buf.add(self.head).write(value) };
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes and returns the last element, or `None` if the ring is empty.
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        self.len -= 1;
+        Some(unsafe { self.buf.add(self.physical(self.len)).read() })
+    }
+
+    /// Removes and returns the first element, or `None` if the ring is empty.
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        let value = unsafe { self.buf.add(self.head).read() };
+        self.head = self.physical(1);
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Returns a reference to the first element, or `None` if the ring is
+    /// empty.
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Returns a reference to the last element, or `None` if the ring is
+    /// empty.
+    pub fn back(&self) -> Option<&T> {
+        self.len.checked_sub(1).and_then(|index| self.get(index))
+    }
+
+    /// Returns a reference to the `index`th element from the front, or `None`
+    /// if `index >= len()`.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        (index < self.len).then(|| unsafe { self.buf.add(self.physical(index)).as_ref() })
+    }
+
+    /// Returns a mutable reference to the `index`th element from the front, or
+    /// `None` if `index >= len()`.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        (index < self.len).then(|| unsafe { self.buf.add(self.physical(index)).as_mut() })
+    }
+
+    /// Returns the elements as two slices, which are in order when
+    /// concatenated: the elements up to the end of the buffer, and the
+    /// elements that wrapped around to its start.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (front, back) = self.slice_ranges();
+        unsafe {
+            (
+                NonNull::slice_from_raw_parts(self.buf.add(self.head), front).as_ref(),
+                NonNull::slice_from_raw_parts(self.buf, back).as_ref(),
+            )
+        }
+    }
+
+    /// Returns the elements as two mutable slices, like
+    /// [`as_slices`][Self::as_slices].
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let (front, back) = self.slice_ranges();
+        unsafe {
+            (
+                NonNull::slice_from_raw_parts(self.buf.add(self.head), front).as_mut(),
+                NonNull::slice_from_raw_parts(self.buf, back).as_mut(),
+            )
+        }
+    }
+
+    /// Returns the lengths of the two slices returned by `as_slices`.
+    fn slice_ranges(&self) -> (usize, usize) {
+        let front = self.len.min(self.capacity - self.head);
+        (front, self.len - front)
+    }
+
+    /// Returns an iterator over the elements, front to back.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + '_ {
+        let (front, back) = self.as_slices();
+        front.iter().chain(back)
+    }
+
+    /// Returns an iterator over mutable references to the elements, front to
+    /// back.
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut T> + '_ {
+        let (front, back) = self.as_mut_slices();
+        front.iter_mut().chain(back)
+    }
+
+    /// Drops all elements, leaving the ring empty.
+    pub fn clear(&mut self) {
+        /// Drops the wrapped-around elements, even if dropping one of the
+        /// others panics.
+        struct DropBack<T>(*mut [T]);
+        impl<T> Drop for DropBack<T> {
+            fn drop(&mut self) {
+                unsafe { self.0.drop_in_place() }
+            }
+        }
+
+        let (front, back) = self.as_mut_slices();
+        let (front, back): (*mut [T], *mut [T]) = (front, back);
+        self.head = 0;
+        self.len = 0;
+        let _back = DropBack(back);
+        unsafe { front.drop_in_place() }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for OwningRing<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Drop for OwningRing<'_, T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
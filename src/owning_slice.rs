@@ -5,26 +5,121 @@
 //! as `unsafe fn`s, since `Vec` may use it's spare capacity in ways that
 //! violate `ManuallyDrop<T>`'s validity invariants, not just it's safety
 //! invariants.
+//!
+//! # Zero-sized types
+//!
+//! Zero-sized elements take up no storage, so an `OwningSlice` of a ZST has a
+//! capacity of `usize::MAX` regardless of the borrowed slice's length, and
+//! never needs to allocate or grow:
+//!
+//! ```rust
+//! # use std::mem::MaybeUninit;
+//! # use std::sync::atomic::{AtomicUsize, Ordering};
+//! use noop_allocator::owning_slice;
+//! static DROPS: AtomicUsize = AtomicUsize::new(0);
+//! struct Counted;
+//! impl Drop for Counted {
+//!     fn drop(&mut self) {
+//!         DROPS.fetch_add(1, Ordering::Relaxed);
+//!     }
+//! }
+//!
+//! let mut buf: [MaybeUninit<Counted>; 0] = [];
+//! let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+//! assert_eq!(vec.capacity(), usize::MAX);
+//! vec.extend((0..1000).map(|_| Counted));
+//! assert_eq!(vec.len(), 1000);
+//! vec.truncate(400);
+//! assert_eq!(DROPS.load(Ordering::Relaxed), 600);
+//! drop(vec);
+//! assert_eq!(DROPS.load(Ordering::Relaxed), 1000);
+//!
+//! // Lengths near `usize::MAX` are fine too.
+//! let mut buf = [MaybeUninit::new(()); 1];
+//! let mut vec = unsafe { owning_slice::from_maybeuninit_slice(&mut buf, 1) };
+//! unsafe { vec.set_len(usize::MAX - 1) };
+//! vec.push(());
+//! assert_eq!(vec.len(), usize::MAX);
+//! assert_eq!(vec.pop(), Some(()));
+//! ```
+//!
+//! Pushing past a length of `usize::MAX` panics with a capacity overflow,
+//! rather than trying to allocate:
+//!
+//! ```rust,should_panic
+//! # use std::mem::MaybeUninit;
+//! use noop_allocator::owning_slice;
+//! let mut buf: [MaybeUninit<()>; 0] = [];
+//! let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+//! unsafe { vec.set_len(usize::MAX) };
+//! vec.push(());
+//! ```
+//!
+//! Likewise for [`OwningRef`][crate::owning_ref::OwningRef]s of ZSTs:
+//!
+//! ```rust
+//! # use std::mem::MaybeUninit;
+//! # use std::sync::atomic::{AtomicUsize, Ordering};
+//! use noop_allocator::owning_ref;
+//! static DROPS: AtomicUsize = AtomicUsize::new(0);
+//! struct Counted;
+//! impl Drop for Counted {
+//!     fn drop(&mut self) {
+//!         DROPS.fetch_add(1, Ordering::Relaxed);
+//!     }
+//! }
+//!
+//! let mut unit = MaybeUninit::uninit();
+//! let unit = owning_ref::from_maybeuninit_write(&mut unit, ());
+//! assert_eq!(*unit, ());
+//!
+//! let mut slot = MaybeUninit::uninit();
+//! let counted = owning_ref::from_maybeuninit_write(&mut slot, Counted);
+//! let mut buf = [const { MaybeUninit::new(Counted) }; 3];
+//! let slice = unsafe { owning_ref::from_maybeuninit_slice(&mut buf) };
+//! assert_eq!(slice.len(), 3);
+//! drop(counted);
+//! drop(slice);
+//! assert_eq!(DROPS.load(Ordering::Relaxed), 4);
+//! ```
+
+use core::{
+    alloc::Layout,
+    cell::RefMut,
+    cmp::Ordering,
+    fmt,
+    marker::PhantomData,
+    mem::{ManuallyDrop, MaybeUninit},
+};
 
-use core::{marker::PhantomData, mem::MaybeUninit};
+use crate::{NoopAllocator, owning_ref::OwningRef};
+use alloc::{boxed::Box, vec::Vec};
 
-use crate::NoopAllocator;
-use alloc::vec::Vec;
+pub mod reorder;
 
 /// An owning slice reference boorrowing a memory location but owning the value
 /// in it, implemented as `Vec<T, NoopAllocator<'a>>`.
 pub type OwningSlice<'a, T> = Vec<T, NoopAllocator<'a>>;
 
+// `NoopAllocator` is zero-sized, so an `OwningSlice` is exactly as big as the
+// plain `Vec` it stands in for.
+const _: () = {
+    use core::mem::size_of;
+    assert!(size_of::<OwningSlice<'static, u8>>() == size_of::<Vec<u8>>());
+    assert!(size_of::<OwningSlice<'static, u64>>() == size_of::<Vec<u64>>());
+    assert!(size_of::<Option<OwningSlice<'static, u8>>>() == size_of::<OwningSlice<'static, u8>>());
+};
+
 /// Create a `OwningSlice<'a, T>` with a length and capacity of 1 from a `&'a
 /// mut MaybeUninit<T>>`.
 ///
-/// # Safety:
+/// # Safety
 ///
 /// The `T` must be initialized, and dropping or removing the element from the
 /// `OwningSlice` leaves the `MaybeUninit` semantically without a value, see
 /// [`MaybeUninit::assume_init_mut`] and [`MaybeUninit::assume_init_drop`].
 ///
-/// # Examples:
+/// # Examples
 ///
 /// ```rust
 /// # use std::mem::MaybeUninit;
@@ -49,12 +144,12 @@ pub unsafe fn from_maybeuninit<T>(slot: &mut MaybeUninit<T>) -> OwningSlice<'_,
 /// Create a `OwningSlice<'a, T>` with a given length from a `&'a mut
 /// [MaybeUninit<T>]>`. The capacity is the length of the given slice.
 ///
-/// # Safety:
+/// # Safety
 ///
 /// All slice elements in `[0..length]` must be initialized, see
 /// [`MaybeUninit::assume_init_mut`] and [`MaybeUninit::assume_init_drop`].
 ///
-/// # Examples:
+/// # Examples
 ///
 /// ```rust
 /// # use std::mem::MaybeUninit;
@@ -81,13 +176,127 @@ pub unsafe fn from_maybeuninit_slice<T>(
     )
 }
 
+/// The error type returned by [`try_from_maybeuninit_slice`] when the length
+/// is greater than the capacity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LengthError {
+    /// The requested length.
+    pub length: usize,
+    /// The capacity, i.e. the length of the given slice.
+    pub capacity: usize,
+}
+
+impl fmt::Display for LengthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "length {} is greater than capacity {}",
+            self.length, self.capacity
+        )
+    }
+}
+
+/// Create a `OwningSlice<'a, T>` with a given length from a `&'a mut
+/// [MaybeUninit<T>]>`, or return `Err` if `length` is greater than the length
+/// of the slice. The capacity is the length of the given slice.
+///
+/// This is like [`from_maybeuninit_slice`], but checks `length` instead of
+/// (debug-)asserting it.
+///
+/// # Safety
+///
+/// All slice elements in `[0..length]` must be initialized, see
+/// [`MaybeUninit::assume_init_mut`] and [`MaybeUninit::assume_init_drop`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice::{self, LengthError};
+/// let mut buf: [MaybeUninit<u32>; 4] = [MaybeUninit::new(1); 4];
+/// let err = unsafe { owning_slice::try_from_maybeuninit_slice(&mut buf, 5) }.unwrap_err();
+/// assert_eq!(
+///     err,
+///     LengthError {
+///         length: 5,
+///         capacity: 4
+///     }
+/// );
+///
+/// let vec = unsafe { owning_slice::try_from_maybeuninit_slice(&mut buf, 4) }.unwrap();
+/// assert_eq!(vec, [1, 1, 1, 1]);
+/// ```
+pub unsafe fn try_from_maybeuninit_slice<T>(
+    slot: &mut [MaybeUninit<T>],
+    length: usize,
+) -> Result<OwningSlice<'_, T>, LengthError> {
+    if length > slot.len() {
+        return Err(LengthError {
+            length,
+            capacity: slot.len(),
+        });
+    }
+    Ok(from_maybeuninit_slice(slot, length))
+}
+
+/// Create a `OwningSlice<'a, T>` from a `&'a mut [T]` of already-initialized
+/// values. The length and capacity are the length of the given slice.
+///
+/// # Safety
+///
+/// The `OwningSlice` takes ownership of the elements, so the slice is left
+/// logically empty: once the `OwningSlice` is dropped or an element is removed
+/// from it, the affected elements of the original slice must not be used or
+/// dropped again (unless they are first overwritten, e.g. with
+/// [`core::ptr::write`]). In particular, the owner of the slice must not drop
+/// them, e.g. by borrowing from a [`ManuallyDrop`][core::mem::ManuallyDrop].
+///
+/// With the `poison` feature, dropping the `OwningSlice`, or shrinking it, also
+/// overwrites the released elements' bytes with `POISON`, which need not be a
+/// valid `T`, so this applies even to types without drop glue.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::ManuallyDrop;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_slice;
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// struct Counted(u32);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut values = ManuallyDrop::new([Counted(1), Counted(2), Counted(3)]);
+/// let mut vec = unsafe { owning_slice::from_initialized_slice(&mut *values) };
+/// assert_eq!(vec.capacity(), 3);
+/// let last = vec.pop().unwrap();
+/// assert_eq!(last.0, 3);
+/// drop(last);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+/// drop(vec);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 3);
+/// // `values` is now logically empty, and is never dropped.
+/// ```
+pub unsafe fn from_initialized_slice<T>(slot: &mut [T]) -> OwningSlice<'_, T> {
+    Vec::from_raw_parts_in(
+        slot.as_mut_ptr(),
+        slot.len(),
+        slot.len(),
+        NoopAllocator(PhantomData),
+    )
+}
+
 /// Create a `OwningSlice<'a, T>` with a length of 0 and a capacity of 1 from a
 /// `&'a mut MaybeUninit<T>>`.
 ///
 /// The `T` is not assumed to be initialized, so this is not an `unsafe`
 /// function.
 ///
-/// # Examples:
+/// # Examples
 ///
 /// ```rust
 /// # use std::mem::MaybeUninit;
@@ -116,7 +325,12 @@ pub fn empty_from_maybeuninit<T>(slot: &mut MaybeUninit<T>) -> OwningSlice<'_, T
 /// The slice elements are not assumed to be initialized, so this is not an
 /// `unsafe` function.
 ///
-/// # Exapmples
+/// This is not available as a `From` impl: `OwningSlice` is a `Vec`, and the
+/// orphan rules do not allow this crate to implement a foreign trait for it.
+/// Generic code can take an `Into<OwningRef<'a, [MaybeUninit<T>]>>`
+/// instead; see [`OwningRef`][crate::owning_ref::OwningRef]'s `From` impls.
+///
+/// # Examples
 ///
 /// ```rust
 /// # use std::mem::MaybeUninit;
@@ -138,3 +352,1654 @@ pub fn empty_from_maybeuninit_slice<T>(slot: &mut [MaybeUninit<T>]) -> OwningSli
         )
     }
 }
+
+/// Create a `OwningSlice<'a, T>` with a length of 0 from any storage that can
+/// be borrowed as a `&mut [MaybeUninit<T>]`, such as an array or a buffer type
+/// from another crate. The capacity is the length of the borrowed slice.
+///
+/// This is [`empty_from_maybeuninit_slice`] applied to `storage.as_mut()`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice;
+/// let mut array: [MaybeUninit<u32>; 4] = [MaybeUninit::uninit(); 4];
+/// let mut vec = owning_slice::empty_from_storage(&mut array);
+/// vec.extend([1, 2, 3]);
+/// assert_eq!((vec.len(), vec.capacity()), (3, 4));
+///
+/// struct Scratch {
+///     slots: [MaybeUninit<u32>; 8],
+/// }
+/// impl AsMut<[MaybeUninit<u32>]> for Scratch {
+///     fn as_mut(&mut self) -> &mut [MaybeUninit<u32>] {
+///         &mut self.slots[2..]
+///     }
+/// }
+/// let mut scratch = Scratch {
+///     slots: [MaybeUninit::uninit(); 8],
+/// };
+/// let vec = owning_slice::empty_from_storage(&mut scratch);
+/// assert_eq!(vec.capacity(), 6);
+/// ```
+pub fn empty_from_storage<'a, T: 'a, S: AsMut<[MaybeUninit<T>]> + ?Sized>(
+    storage: &'a mut S,
+) -> OwningSlice<'a, T> {
+    empty_from_maybeuninit_slice(storage.as_mut())
+}
+
+/// Create a `OwningSlice<'a, T>` with a length of 0 from storage borrowed out
+/// of a [`RefCell`][core::cell::RefCell]. The capacity is the length of the
+/// borrowed slice.
+///
+/// The `OwningSlice` borrows the [`RefMut`] guard, rather than taking it, so
+/// the guard cannot be dropped, and the `RefCell` stays mutably borrowed, for
+/// as long as the `OwningSlice` lives. (Returning the `OwningSlice` alongside
+/// a separate value keeping the guard alive would not be sound: dropping that
+/// value first would release the `RefCell` while the `OwningSlice` still
+/// points into it.)
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice;
+/// use std::cell::{RefCell, RefMut};
+/// let pool = RefCell::new([const { MaybeUninit::<String>::uninit() }; 4]);
+///
+/// let mut guard = RefMut::map(pool.borrow_mut(), |slots| &mut slots[..]);
+/// let mut vec = owning_slice::from_refmut(&mut guard);
+/// vec.push("borrowed".to_string());
+/// assert_eq!(vec.capacity(), 4);
+/// assert!(pool.try_borrow().is_err());
+///
+/// drop(vec);
+/// drop(guard);
+/// assert!(pool.try_borrow().is_ok());
+/// ```
+///
+/// The guard must outlive the `OwningSlice`:
+///
+/// ```rust,compile_fail,E0505
+/// # use std::mem::MaybeUninit;
+/// use std::cell::{RefCell, RefMut};
+/// use noop_allocator::owning_slice;
+/// let pool = RefCell::new([const { MaybeUninit::<String>::uninit() }; 4]);
+///
+/// let mut guard = RefMut::map(pool.borrow_mut(), |slots| &mut slots[..]);
+/// let mut vec = owning_slice::from_refmut(&mut guard);
+/// drop(guard);
+/// vec.push("dangling".to_string());
+/// ```
+pub fn from_refmut<'a, T>(guard: &'a mut RefMut<'_, [MaybeUninit<T>]>) -> OwningSlice<'a, T> {
+    empty_from_maybeuninit_slice(guard)
+}
+
+/// The remaining items of an iterator that did not fit in the buffer given to
+/// [`try_collect_into`]: the first item that did not fit, followed by the rest
+/// of the iterator.
+pub type Overflow<T, I> = core::iter::Chain<core::iter::Once<T>, I>;
+
+/// Collect the items of `iter` into an `OwningSlice<'a, T>` over `slot`,
+/// without allocating.
+///
+/// Returns `Ok` if every item fit, or `Err` with the full `OwningSlice` and the
+/// items that did not fit otherwise. Telling a full buffer from an overflowing
+/// one requires taking one more item from the iterator, so the remaining items
+/// are returned as an [`Overflow`] iterator that yields that item first.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice;
+/// let mut buf = [MaybeUninit::<u32>::uninit(); 4];
+/// let vec = owning_slice::try_collect_into(&mut buf, 1..=4).unwrap();
+/// assert_eq!(vec, [1, 2, 3, 4]);
+///
+/// let mut buf = [MaybeUninit::<u32>::uninit(); 4];
+/// let vec =
+///     owning_slice::try_collect_into(&mut buf, (1..).filter(|x| x % 2 == 0).take(3)).unwrap();
+/// assert_eq!((vec.len(), vec.capacity()), (3, 4));
+///
+/// let mut buf = [MaybeUninit::<String>::uninit(), MaybeUninit::uninit()];
+/// let words = ["a", "b", "c", "d"].map(String::from);
+/// let (vec, rest) = owning_slice::try_collect_into(&mut buf, words).unwrap_err();
+/// assert_eq!(vec, ["a", "b"]);
+/// assert_eq!(rest.collect::<Vec<_>>(), ["c", "d"]);
+/// ```
+///
+/// If `iter` panics, the items collected so far are dropped, and nothing else:
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_slice;
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// struct Counted(u32);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut buf = [const { MaybeUninit::uninit() }; 8];
+/// let items = (0..8).map(|n| {
+///     assert!(n < 3, "no more items");
+///     Counted(n)
+/// });
+/// let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+///     drop(owning_slice::try_collect_into(&mut buf, items));
+/// }));
+/// assert!(result.is_err());
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 3);
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn try_collect_into<'a, T, I: IntoIterator<Item = T>>(
+    slot: &'a mut [MaybeUninit<T>],
+    iter: I,
+) -> Result<OwningSlice<'a, T>, (OwningSlice<'a, T>, Overflow<T, I::IntoIter>)> {
+    let mut iter = iter.into_iter();
+    let mut guard = PartialInitGuard::new(slot);
+    while !guard.is_full() {
+        match iter.next() {
+            Some(item) => guard.push(item),
+            None => return Ok(guard.finish()),
+        }
+    }
+    let vec = guard.finish();
+    match iter.next() {
+        Some(item) => Err((vec, core::iter::once(item).chain(iter))),
+        None => Ok(vec),
+    }
+}
+
+/// Create a full `OwningSlice<'a, T>` from a `&'a mut [MaybeUninit<T>]>`, by
+/// calling `f` once for each element, in order.
+///
+/// This is like [`try_collect_into`] with [`iter::repeat_with`], except that
+/// the length is always that of `slot`. If `f` panics, exactly the elements
+/// written so far are dropped.
+///
+/// [`iter::repeat_with`]: core::iter::repeat_with
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice;
+/// let mut buf = [MaybeUninit::uninit(); 5];
+/// let mut next = 1;
+/// let vec = owning_slice::fill_with(&mut buf, || {
+///     next *= 2;
+///     next
+/// });
+/// assert_eq!(vec, [2, 4, 8, 16, 32]);
+/// assert_eq!(vec.capacity(), 5);
+/// ```
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_slice;
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// struct Counted;
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut buf = [const { MaybeUninit::uninit() }; 5];
+/// let mut calls = 0;
+/// let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+///     owning_slice::fill_with(&mut buf, || {
+///         calls += 1;
+///         assert!(calls <= 2, "out of values");
+///         Counted
+///     });
+/// }));
+/// assert!(result.is_err());
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+/// ```
+pub fn fill_with<'a, T>(
+    slot: &'a mut [MaybeUninit<T>],
+    mut f: impl FnMut() -> T,
+) -> OwningSlice<'a, T> {
+    let len = slot.len();
+    let mut guard = PartialInitGuard::new(slot);
+    for _ in 0..len {
+        guard.push(f());
+    }
+    guard.finish()
+}
+
+/// The error type returned by [`interleave_into`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InterleaveError {
+    /// The two sources have different lengths.
+    LengthMismatch {
+        /// The length of `a`.
+        a: usize,
+        /// The length of `b`.
+        b: usize,
+    },
+    /// The destination is too short to hold both sources.
+    Overflow {
+        /// The number of interleaved elements, twice the length of a source.
+        needed: usize,
+        /// The length of the destination.
+        capacity: usize,
+    },
+}
+
+impl fmt::Display for InterleaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::LengthMismatch { a, b } => {
+                write!(f, "cannot interleave sources of lengths {a} and {b}")
+            }
+            Self::Overflow { needed, capacity } => write!(
+                f,
+                "{needed} interleaved elements do not fit in a buffer of length {capacity}"
+            ),
+        }
+    }
+}
+
+/// Create a `OwningSlice<'a, T>` from a `&'a mut [MaybeUninit<T>]` holding the
+/// elements of `a` and `b` interleaved, i.e. `[a[0], b[0], a[1], b[1], ...]`.
+/// The capacity is the length of `dst`.
+///
+/// Returns `Err`, without writing anything, if `a` and `b` have different
+/// lengths, or if `dst` is shorter than both of them together.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice::{self, InterleaveError};
+/// let left = [0.1f32, 0.2, 0.3];
+/// let right = [-0.1f32, -0.2, -0.3];
+///
+/// let mut buf = [MaybeUninit::uninit(); 8];
+/// let stereo = owning_slice::interleave_into(&mut buf, &left, &right).unwrap();
+/// assert_eq!(stereo, [0.1, -0.1, 0.2, -0.2, 0.3, -0.3]);
+/// assert_eq!(stereo.capacity(), 8);
+///
+/// let mut buf = [MaybeUninit::uninit(); 8];
+/// assert_eq!(
+///     owning_slice::interleave_into(&mut buf, &left, &right[..2]),
+///     Err(InterleaveError::LengthMismatch { a: 3, b: 2 }),
+/// );
+/// assert_eq!(
+///     owning_slice::interleave_into(&mut buf[..5], &left, &right),
+///     Err(InterleaveError::Overflow {
+///         needed: 6,
+///         capacity: 5
+///     }),
+/// );
+/// ```
+pub fn interleave_into<'a, T: Copy>(
+    dst: &'a mut [MaybeUninit<T>],
+    a: &[T],
+    b: &[T],
+) -> Result<OwningSlice<'a, T>, InterleaveError> {
+    if a.len() != b.len() {
+        return Err(InterleaveError::LengthMismatch {
+            a: a.len(),
+            b: b.len(),
+        });
+    }
+    let needed = a.len().checked_mul(2).ok_or(InterleaveError::Overflow {
+        needed: usize::MAX,
+        capacity: dst.len(),
+    })?;
+    if dst.len() < needed {
+        return Err(InterleaveError::Overflow {
+            needed,
+            capacity: dst.len(),
+        });
+    }
+    for (pair, (&x, &y)) in dst.chunks_exact_mut(2).zip(a.iter().zip(b)) {
+        pair[0].write(x);
+        pair[1].write(y);
+    }
+    Ok(unsafe { from_maybeuninit_slice(dst, needed) })
+}
+
+/// Drops the initialized prefix of a slot being filled, if filling it panics.
+///
+/// Elements are written in order with [`push`][Self::push]; once filling is
+/// done, [`finish`][Self::finish] hands them over to an `OwningSlice`. Like
+/// `OwningSlice`, the capacity is unbounded for zero-sized `T`.
+pub(crate) struct PartialInitGuard<'a, T> {
+    slot: &'a mut [MaybeUninit<T>],
+    initialized: usize,
+}
+
+impl<'a, T> PartialInitGuard<'a, T> {
+    pub(crate) fn new(slot: &'a mut [MaybeUninit<T>]) -> Self {
+        Self {
+            slot,
+            initialized: 0,
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        if core::mem::size_of::<T>() == 0 {
+            usize::MAX
+        } else {
+            self.slot.len()
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.initialized == self.capacity()
+    }
+
+    pub(crate) fn push(&mut self, value: T) {
+        assert!(!self.is_full(), "slot is already full");
+        unsafe {
+            self.slot
+                .as_mut_ptr()
+                .add(self.initialized)
+                .cast::<T>()
+                .write(value)
+        };
+        self.initialized += 1;
+    }
+
+    pub(crate) fn finish(self) -> OwningSlice<'a, T> {
+        let mut this = core::mem::ManuallyDrop::new(self);
+        let (initialized, capacity) = (this.initialized, this.capacity());
+        let slot = core::mem::take(&mut this.slot);
+        unsafe {
+            Vec::from_raw_parts_in(
+                slot.as_mut_ptr().cast::<T>(),
+                initialized,
+                capacity,
+                NoopAllocator(PhantomData),
+            )
+        }
+    }
+}
+
+impl<T> Drop for PartialInitGuard<'_, T> {
+    fn drop(&mut self) {
+        unsafe {
+            core::ptr::slice_from_raw_parts_mut(
+                self.slot.as_mut_ptr().cast::<T>(),
+                self.initialized,
+            )
+            .drop_in_place()
+        }
+    }
+}
+
+/// Resize an `OwningSlice<'a, T>` in-place so that its length is `new_len`,
+/// without reallocating.
+///
+/// If `new_len` is greater than the current length, the `OwningSlice` is
+/// extended with clones of `value`, but only if `new_len` is not greater than
+/// its capacity; otherwise `Err(())` is returned and the `OwningSlice` is
+/// unchanged. If `new_len` is less than the current length, the `OwningSlice`
+/// is truncated, which always succeeds.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_slice;
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// #[derive(Clone)]
+/// struct Counted(u32);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut buf: [MaybeUninit<Counted>; 4] = [const { MaybeUninit::uninit() }; 4];
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+///
+/// // Growing within capacity clones `value` (moving it into the last slot).
+/// owning_slice::resize_within_capacity(&mut vec, 3, Counted(7)).unwrap();
+/// assert_eq!(vec.len(), 3);
+/// assert!(vec.iter().all(|c| c.0 == 7));
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+///
+/// // Growing past capacity fails, dropping only `value`.
+/// assert_eq!(
+///     owning_slice::resize_within_capacity(&mut vec, 5, Counted(0)),
+///     Err(())
+/// );
+/// assert_eq!(vec.len(), 3);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+///
+/// // Shrinking drops the truncated elements (and `value`).
+/// owning_slice::resize_within_capacity(&mut vec, 1, Counted(0)).unwrap();
+/// assert_eq!(vec.len(), 1);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 4);
+///
+/// drop(vec);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 5);
+/// ```
+#[allow(clippy::result_unit_err)]
+pub fn resize_within_capacity<T: Clone>(
+    v: &mut OwningSlice<'_, T>,
+    new_len: usize,
+    value: T,
+) -> Result<(), ()> {
+    if new_len > v.capacity() {
+        return Err(());
+    }
+    // `Vec::resize` does not reallocate when `new_len <= capacity`.
+    v.resize(new_len, value);
+    Ok(())
+}
+
+/// Split an `OwningSlice<'a, T>` into its initialized elements and its spare
+/// capacity.
+///
+/// This is like [`Vec::spare_capacity_mut`], but also returns the initialized
+/// prefix, so a buffer can be filled incrementally while reading what is
+/// already there. After writing to the first `n` spare slots, call
+/// [`Vec::set_len`] to make them part of the `OwningSlice`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice;
+/// let mut buf = [MaybeUninit::<u32>::uninit(); 5];
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// vec.extend([1, 1]);
+///
+/// let (init, spare) = owning_slice::split_at_spare_mut(&mut vec);
+/// assert_eq!(spare.len(), 3);
+/// // Write the next two Fibonacci numbers.
+/// spare[0].write(init[0] + init[1]);
+/// spare[1].write(init[1] + unsafe { spare[0].assume_init() });
+/// unsafe { vec.set_len(4) };
+/// assert_eq!(vec, [1, 1, 2, 3]);
+/// ```
+pub fn split_at_spare_mut<'b, T>(
+    v: &'b mut OwningSlice<'_, T>,
+) -> (&'b mut [T], &'b mut [MaybeUninit<T>]) {
+    let len = v.len();
+    let spare_len = v.capacity() - len;
+    let ptr = v.as_mut_ptr();
+    unsafe {
+        (
+            core::slice::from_raw_parts_mut(ptr, len),
+            core::slice::from_raw_parts_mut(ptr.add(len).cast::<MaybeUninit<T>>(), spare_len),
+        )
+    }
+}
+
+/// Convert an `OwningSlice<'a, T>` into a `&'a mut [T]` of its elements,
+/// without dropping them.
+///
+/// This is [`Vec::leak`], which is also fine to use directly: its `A: 'a` bound
+/// means the returned slice cannot outlive the borrow of the buffer. The
+/// elements are never dropped (unless the caller does so through the slice),
+/// and the spare capacity is not included.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_slice;
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// struct Counted(u32);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut buf = [const { MaybeUninit::uninit() }; 4];
+/// let start = buf.as_ptr().cast::<Counted>();
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// vec.extend([Counted(1), Counted(2)]);
+/// let leaked = owning_slice::leak(vec);
+/// assert_eq!(leaked.as_ptr(), start);
+/// assert_eq!(leaked.len(), 2);
+/// leaked[0].0 = 10;
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+/// ```
+///
+/// The leaked slice cannot outlive the buffer:
+///
+/// ```rust,compile_fail,E0597
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice;
+/// let leaked: &mut [u32] = {
+///     let mut buf = [MaybeUninit::new(1u32); 4];
+///     owning_slice::leak(unsafe { owning_slice::from_maybeuninit_slice(&mut buf, 4) })
+/// };
+/// ```
+pub fn leak<'a, T>(v: OwningSlice<'a, T>) -> &'a mut [T] {
+    v.leak()
+}
+
+/// Move the elements of an `OwningSlice<'a, T>` of length exactly `N` into an
+/// owned `[T; N]`, or return it unchanged if its length is not `N`.
+///
+/// The elements are moved out of the borrowed buffer, leaving it
+/// uninitialized, so the array can outlive the borrow.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_slice;
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// #[derive(Debug)]
+/// struct Counted(u32);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let array: [Counted; 3] = {
+///     let mut buf = [const { MaybeUninit::uninit() }; 4];
+///     let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+///     vec.extend([Counted(1), Counted(2), Counted(3)]);
+///
+///     // Wrong length: the `OwningSlice` is returned unchanged.
+///     let vec = owning_slice::into_array::<_, 2>(vec).unwrap_err();
+///     assert_eq!(vec.len(), 3);
+///
+///     owning_slice::into_array(vec).unwrap()
+/// };
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+/// assert_eq!(array.each_ref().map(|c| c.0), [1, 2, 3]);
+/// drop(array);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 3);
+/// ```
+pub fn into_array<'a, T, const N: usize>(
+    mut v: OwningSlice<'a, T>,
+) -> Result<[T; N], OwningSlice<'a, T>> {
+    if v.len() != N {
+        return Err(v);
+    }
+    unsafe {
+        v.set_len(0);
+        Ok(v.as_ptr().cast::<[T; N]>().read())
+    }
+}
+
+/// Convert an `OwningSlice<'a, T>` of length exactly `N` into an
+/// `OwningRef<'a, [T; N]>` borrowing the same buffer, or return it unchanged if
+/// its length is not `N`.
+///
+/// Unlike [`into_array`], the elements are not moved: the `OwningRef` points
+/// at the start of the buffer. Any spare capacity past the first `N` elements
+/// stays borrowed, but unused, until the `OwningRef` is dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice;
+/// let mut buf = [const { MaybeUninit::uninit() }; 4];
+/// let start = buf.as_ptr().cast::<String>();
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// vec.extend(["a", "b", "c"].map(String::from));
+///
+/// // Wrong length: the `OwningSlice` is returned unchanged.
+/// let vec = owning_slice::into_owning_array::<_, 4>(vec).unwrap_err();
+/// assert_eq!(vec, ["a", "b", "c"]);
+///
+/// let mut array = owning_slice::into_owning_array::<_, 3>(vec).unwrap();
+/// assert_eq!(array.as_ptr(), start);
+/// array[1].push('!');
+/// assert_eq!(*array, ["a", "b!", "c"]);
+/// ```
+pub fn into_owning_array<'a, T, const N: usize>(
+    v: OwningSlice<'a, T>,
+) -> Result<OwningRef<'a, [T; N]>, OwningSlice<'a, T>> {
+    if v.len() != N {
+        return Err(v);
+    }
+    let mut v = ManuallyDrop::new(v);
+    unsafe {
+        Ok(Box::from_raw_in(
+            v.as_mut_ptr().cast::<[T; N]>(),
+            NoopAllocator(PhantomData),
+        ))
+    }
+}
+
+/// Remove and return the first element of an `OwningSlice<'a, T>`, or `None`
+/// if it is empty.
+///
+/// This shifts the remaining elements down, so it is *O*(*n*). See
+/// [`OwningQueue`][crate::owning_queue::OwningQueue] for *O*(1) front removal.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice;
+/// let mut buf: [MaybeUninit<String>; 3] = [const { MaybeUninit::uninit() }; 3];
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// vec.extend(["a", "b"].map(String::from));
+/// assert_eq!(
+///     owning_slice::pop_front_owning(&mut vec).as_deref(),
+///     Some("a")
+/// );
+/// assert_eq!(
+///     owning_slice::pop_front_owning(&mut vec).as_deref(),
+///     Some("b")
+/// );
+/// assert_eq!(owning_slice::pop_front_owning(&mut vec), None);
+/// assert_eq!(vec.capacity(), 3);
+/// ```
+pub fn pop_front_owning<T>(v: &mut OwningSlice<'_, T>) -> Option<T> {
+    if v.is_empty() {
+        None
+    } else {
+        Some(v.remove(0))
+    }
+}
+
+/// Remove consecutive repeated elements of an `OwningSlice<'a, T>`, like
+/// [`Vec::dedup`], but pass each removed element to `removed` instead of
+/// dropping it.
+///
+/// Elements are compared to the last kept element, and removed elements are
+/// passed to `removed` in order. If `T::eq` or `removed` panics, the elements
+/// that have not been processed yet are kept, and no element is dropped or
+/// passed to `removed` twice.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_slice;
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// /// Compares only by number, so that duplicates can be told apart.
+/// struct Counted(u32, char);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+/// impl PartialEq for Counted {
+///     fn eq(&self, other: &Counted) -> bool {
+///         self.0 == other.0
+///     }
+/// }
+///
+/// let mut buf = [const { MaybeUninit::uninit() }; 8];
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// vec.extend(
+///     [(1, 'a'), (1, 'b'), (2, 'c'), (3, 'd'), (3, 'e'), (3, 'f')].map(|(n, c)| Counted(n, c)),
+/// );
+///
+/// let mut recycled = vec![];
+/// owning_slice::dedup_moving(&mut vec, |dup| recycled.push(dup.1));
+/// assert_eq!(vec.iter().map(|c| c.0).collect::<Vec<_>>(), [1, 2, 3]);
+/// assert_eq!(vec.iter().map(|c| c.1).collect::<String>(), "acd");
+/// assert_eq!(recycled, ['b', 'e', 'f']);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 3);
+/// ```
+///
+/// If `removed` panics, the elements after the one it was given are kept:
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice;
+/// let mut buf = [MaybeUninit::uninit(); 6];
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// vec.extend([1, 1, 2, 2, 3, 3]);
+/// let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+///     owning_slice::dedup_moving(&mut vec, |dup| assert_ne!(dup, 2));
+/// }));
+/// assert!(result.is_err());
+/// assert_eq!(vec, [1, 2, 3, 3]);
+/// ```
+pub fn dedup_moving<'a, T: PartialEq>(v: &mut OwningSlice<'a, T>, mut removed: impl FnMut(T)) {
+    /// Moves the unprocessed elements down over the gap and restores the
+    /// length, even if `T::eq` or `removed` panics.
+    struct Guard<'r, 'a, T> {
+        v: &'r mut OwningSlice<'a, T>,
+        read: usize,
+        write: usize,
+        len: usize,
+    }
+
+    impl<T> Drop for Guard<'_, '_, T> {
+        fn drop(&mut self) {
+            let tail = self.len - self.read;
+            unsafe {
+                let ptr = self.v.as_mut_ptr();
+                core::ptr::copy(ptr.add(self.read), ptr.add(self.write), tail);
+                self.v.set_len(self.write + tail);
+            }
+        }
+    }
+
+    let len = v.len();
+    if len <= 1 {
+        return;
+    }
+    unsafe { v.set_len(0) };
+    let mut guard = Guard {
+        v,
+        read: 1,
+        write: 1,
+        len,
+    };
+    while guard.read < len {
+        unsafe {
+            let ptr = guard.v.as_mut_ptr();
+            let current = ptr.add(guard.read);
+            if *current == *ptr.add(guard.write - 1) {
+                let item = current.read();
+                guard.read += 1;
+                removed(item);
+            } else {
+                if guard.read != guard.write {
+                    core::ptr::copy_nonoverlapping(current, ptr.add(guard.write), 1);
+                }
+                guard.read += 1;
+                guard.write += 1;
+            }
+        }
+    }
+}
+
+/// Replaces each element of `v` with the result of calling `f` on it, in
+/// order, without allocating.
+///
+/// Each element is moved out of its slot, passed to `f`, and the result is
+/// written back into the same slot, so the `OwningSlice` keeps its buffer and
+/// its borrow.
+///
+/// # Panics
+///
+/// If `f` panics on the element at index `i`, that element has been moved
+/// into `f` and is dropped by it, and the elements after it are dropped. `v`
+/// is left truncated to `i`, holding the new values for the elements before
+/// it.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice;
+/// let mut buf = [const { MaybeUninit::uninit() }; 4];
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// vec.extend(["a", "b", "c"].map(String::from));
+/// owning_slice::map_in_place(&mut vec, |mut s| {
+///     s.push('!');
+///     s
+/// });
+/// assert_eq!(vec, ["a!", "b!", "c!"]);
+/// ```
+///
+/// If `f` panics, every element is still dropped exactly once:
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_slice;
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// struct Counted(u32);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut buf = [const { MaybeUninit::uninit() }; 5];
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// vec.extend((0..5).map(Counted));
+/// let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+///     owning_slice::map_in_place(&mut vec, |c| {
+///         assert_ne!(c.0, 2);
+///         Counted(c.0 * 10)
+///     });
+/// }));
+/// assert!(result.is_err());
+/// // Two originals replaced, the one given to `f` and the two after it.
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 5);
+/// assert_eq!(vec.iter().map(|c| c.0).collect::<Vec<_>>(), [0, 10]);
+/// drop(vec);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 7);
+/// ```
+pub fn map_in_place<'a, T>(v: &mut OwningSlice<'a, T>, mut f: impl FnMut(T) -> T) {
+    /// Drops the elements after the one being mapped and truncates `v` to
+    /// the ones already mapped, if `f` panics.
+    struct Guard<'r, 'a, T> {
+        v: &'r mut OwningSlice<'a, T>,
+        index: usize,
+        len: usize,
+    }
+
+    impl<T> Drop for Guard<'_, '_, T> {
+        fn drop(&mut self) {
+            unsafe {
+                let tail = core::ptr::slice_from_raw_parts_mut(
+                    self.v.as_mut_ptr().add(self.index + 1),
+                    self.len - self.index - 1,
+                );
+                self.v.set_len(self.index);
+                core::ptr::drop_in_place(tail);
+            }
+        }
+    }
+
+    let len = v.len();
+    unsafe { v.set_len(0) };
+    let mut guard = Guard { v, index: 0, len };
+    while guard.index < len {
+        unsafe {
+            let slot = guard.v.as_mut_ptr().add(guard.index);
+            slot.write(f(slot.read()));
+        }
+        guard.index += 1;
+    }
+    let mut guard = ManuallyDrop::new(guard);
+    unsafe { guard.v.set_len(len) };
+}
+
+/// Converts each element of `v` to a `U` with `f`, in order, reusing `v`'s
+/// storage for the results instead of allocating.
+///
+/// This is the borrowed analog of `v.into_iter().map(f).collect::<Vec<U>>()`,
+/// which only works in place because `T` and `U` have the same size, and
+/// storage aligned for `T` is also aligned for `U`.
+///
+/// # Panics
+///
+/// Panics if `T` and `U` differ in size, or if `U` has a greater alignment
+/// than `T`.
+///
+/// If `f` panics on the element at index `i`, that element has been moved
+/// into `f` and is dropped by it, and the `U`s before it and the `T`s after it
+/// are dropped.
+///
+/// # Safety
+///
+/// Once `'a` ends, the storage `v` borrows may hold `U`s where it held `T`s.
+/// This is always fine for `MaybeUninit<T>` storage, but if `v` was made from
+/// initialized storage, e.g. with [`from_initialized_slice`], the bytes of a
+/// `U` must also be valid for a `T`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice::{self, OwningSlice};
+/// let mut buf = [MaybeUninit::uninit(); 4];
+/// let mut words = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// words.extend([0x0403_0201u32, 0x0807_0605]);
+/// let addr = words.as_ptr().addr();
+///
+/// let bytes: OwningSlice<'_, [u8; 4]> =
+///     unsafe { owning_slice::map_in_place_to(words, u32::to_le_bytes) };
+/// assert_eq!(bytes, [[1, 2, 3, 4], [5, 6, 7, 8]]);
+/// assert_eq!(bytes.as_ptr().addr(), addr);
+/// assert_eq!(bytes.capacity(), 4);
+/// ```
+///
+/// Widening the elements does not fit in the same storage:
+///
+/// ```rust,should_panic
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice;
+/// let mut buf = [MaybeUninit::uninit(); 4];
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// vec.push(1u32);
+/// let _ = unsafe { owning_slice::map_in_place_to(vec, u64::from) };
+/// ```
+///
+/// If `f` panics, every element is still dropped exactly once:
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_slice;
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// struct Counted(u32);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+/// struct Doubled(u32);
+/// impl Drop for Doubled {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut buf = [const { MaybeUninit::uninit() }; 5];
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// vec.extend((0..5).map(Counted));
+/// let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| unsafe {
+///     owning_slice::map_in_place_to(vec, |c| {
+///         assert_ne!(c.0, 2);
+///         Doubled(c.0 * 2)
+///     })
+/// }));
+/// assert!(result.is_err());
+/// // Three `Counted`s given to `f`, two after the panic, and two `Doubled`s.
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 7);
+/// ```
+pub unsafe fn map_in_place_to<'a, T, U>(
+    v: OwningSlice<'a, T>,
+    mut f: impl FnMut(T) -> U,
+) -> OwningSlice<'a, U> {
+    /// Drops the mapped and the unmapped elements, but not the one being
+    /// mapped, if `f` panics.
+    struct Guard<T, U> {
+        ptr: *mut T,
+        index: usize,
+        len: usize,
+        _marker: PhantomData<U>,
+    }
+
+    impl<T, U> Drop for Guard<T, U> {
+        fn drop(&mut self) {
+            unsafe {
+                core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                    self.ptr.cast::<U>(),
+                    self.index,
+                ));
+                core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                    self.ptr.add(self.index + 1),
+                    self.len - self.index - 1,
+                ));
+            }
+        }
+    }
+
+    assert_eq!(
+        core::mem::size_of::<T>(),
+        core::mem::size_of::<U>(),
+        "element sizes differ"
+    );
+    assert!(
+        core::mem::align_of::<U>() <= core::mem::align_of::<T>(),
+        "`U` is more aligned than `T`"
+    );
+    let mut v = ManuallyDrop::new(v);
+    let (ptr, len, capacity) = (v.as_mut_ptr(), v.len(), v.capacity());
+    let mut guard = Guard {
+        ptr,
+        index: 0,
+        len,
+        _marker: PhantomData::<U>,
+    };
+    while guard.index < len {
+        let slot = ptr.add(guard.index);
+        slot.cast::<U>().write(f(slot.read()));
+        guard.index += 1;
+    }
+    core::mem::forget(guard);
+    Vec::from_raw_parts_in(ptr.cast::<U>(), len, capacity, NoopAllocator(PhantomData))
+}
+
+/// The error type returned by [`merge_sort_with`] when the scratch buffer is
+/// shorter than the `OwningSlice` being sorted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ScratchTooSmall {
+    /// The required scratch length, i.e. the length of the `OwningSlice`.
+    pub needed: usize,
+    /// The length of the given scratch buffer.
+    pub provided: usize,
+}
+
+impl fmt::Display for ScratchTooSmall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "scratch buffer of length {} is shorter than the {} elements to sort",
+            self.provided, self.needed
+        )
+    }
+}
+
+/// Sorts `v` with a stable merge sort, using `scratch` instead of allocating.
+///
+/// [`slice::sort_by`] allocates its scratch space with the global allocator,
+/// which borrowed-storage users may not have. This takes a borrowed scratch
+/// buffer of at least `v.len()` elements instead, or returns `Err` without
+/// touching `v` if `scratch` is too short.
+///
+/// Elements are only ever moved bitwise between `v` and `scratch`, so
+/// `scratch` is treated as uninitialized, and is left logically
+/// uninitialized: nothing in it is dropped, by this or by the caller.
+///
+/// If `cmp` panics, `v` is left holding all of its elements, in an
+/// unspecified order.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice;
+/// let mut buf = [MaybeUninit::uninit(); 8];
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// vec.extend([(3, 'a'), (1, 'b'), (3, 'c'), (2, 'd'), (1, 'e'), (3, 'f')]);
+///
+/// let mut scratch = [MaybeUninit::uninit(); 6];
+/// owning_slice::merge_sort_with(&mut vec, &mut scratch, |a, b| a.0.cmp(&b.0)).unwrap();
+/// // Equal keys keep their original order.
+/// assert_eq!(
+///     vec,
+///     [(1, 'b'), (1, 'e'), (2, 'd'), (3, 'a'), (3, 'c'), (3, 'f')]
+/// );
+/// ```
+///
+/// The scratch buffer must be at least as long as `v`:
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice::{self, ScratchTooSmall};
+/// let mut buf = [MaybeUninit::uninit(); 4];
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// vec.extend([3, 1, 2]);
+///
+/// let mut scratch = [MaybeUninit::uninit(); 2];
+/// assert_eq!(
+///     owning_slice::merge_sort_with(&mut vec, &mut scratch, Ord::cmp),
+///     Err(ScratchTooSmall {
+///         needed: 3,
+///         provided: 2
+///     }),
+/// );
+/// assert_eq!(vec, [3, 1, 2]);
+/// ```
+pub fn merge_sort_with<'a, T>(
+    v: &mut OwningSlice<'a, T>,
+    scratch: &mut [MaybeUninit<T>],
+    mut cmp: impl FnMut(&T, &T) -> Ordering,
+) -> Result<(), ScratchTooSmall> {
+    /// The part of the left run still in the scratch buffer, which is copied
+    /// into the gap it left in `v` when the merge finishes or `cmp` panics.
+    struct MergeHole<T> {
+        v: *mut T,
+        scratch: *const T,
+        src: usize,
+        end: usize,
+        dest: usize,
+    }
+
+    impl<T> Drop for MergeHole<T> {
+        fn drop(&mut self) {
+            unsafe {
+                core::ptr::copy_nonoverlapping(
+                    self.scratch.add(self.src),
+                    self.v.add(self.dest),
+                    self.end - self.src,
+                );
+            }
+        }
+    }
+
+    let len = v.len();
+    if scratch.len() < len {
+        return Err(ScratchTooSmall {
+            needed: len,
+            provided: scratch.len(),
+        });
+    }
+    let v = v.as_mut_ptr();
+    let scratch = scratch.as_mut_ptr().cast::<T>();
+    let mut width = 1;
+    while width < len {
+        let mut start = 0;
+        while start + width < len {
+            let mid = start + width;
+            let end = len.min(mid + width);
+            let run = unsafe { v.add(start) };
+            // Runs that are already in order need no merging.
+            if unsafe { cmp(&*v.add(mid), &*v.add(mid - 1)) } == Ordering::Less {
+                unsafe { core::ptr::copy_nonoverlapping(run, scratch, width) };
+                let mut hole = MergeHole {
+                    v: run,
+                    scratch,
+                    src: 0,
+                    end: width,
+                    dest: 0,
+                };
+                let mut right = width;
+                while hole.src < hole.end && right < end - start {
+                    unsafe {
+                        let take_right =
+                            cmp(&*run.add(right), &*scratch.add(hole.src)) == Ordering::Less;
+                        if take_right {
+                            core::ptr::copy_nonoverlapping(run.add(right), run.add(hole.dest), 1);
+                            right += 1;
+                        } else {
+                            core::ptr::copy_nonoverlapping(
+                                scratch.add(hole.src),
+                                run.add(hole.dest),
+                                1,
+                            );
+                            hole.src += 1;
+                        }
+                    }
+                    hole.dest += 1;
+                }
+            }
+            start = end;
+        }
+        width *= 2;
+    }
+    Ok(())
+}
+
+/// Calls `run` with each maximal run of consecutive elements of `v` whose keys
+/// are equal, in order.
+///
+/// This is [`slice::chunk_by`] comparing `key(a) == key(b)`, so it does not
+/// allocate. `run` is never called with an empty slice, and is not called at
+/// all if `v` is empty.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice;
+/// let mut buf = [MaybeUninit::uninit(); 8];
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// vec.extend([1, 3, 5, 2, 4, 7, 6, 8]);
+///
+/// let mut runs = vec![];
+/// owning_slice::for_each_run(&vec, |x| x % 2, |run| runs.push(run.to_vec()));
+/// assert_eq!(runs, [vec![1, 3, 5], vec![2, 4], vec![7], vec![6, 8]]);
+///
+/// vec.clear();
+/// owning_slice::for_each_run(&vec, |x| x % 2, |_| unreachable!());
+/// ```
+pub fn for_each_run<T, K: PartialEq>(
+    v: &OwningSlice<'_, T>,
+    key: impl Fn(&T) -> K,
+    mut run: impl FnMut(&[T]),
+) {
+    v.chunk_by(|a, b| key(a) == key(b)).for_each(&mut run);
+}
+
+/// The error type returned by [`partition_into`] when an output buffer is too
+/// small.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PartitionOverflow {
+    /// More elements matched the predicate than fit in the `yes` buffer.
+    Yes,
+    /// More elements did not match the predicate than fit in the `no` buffer.
+    No,
+}
+
+impl fmt::Display for PartitionOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let which = match self {
+            Self::Yes => "yes",
+            Self::No => "no",
+        };
+        write!(f, "the `{which}` buffer of a partition overflowed")
+    }
+}
+
+/// Move the elements of `src` into two new `OwningSlice`s, borrowing `yes` and
+/// `no`, depending on whether `pred` returns `true` for them. Both keep the
+/// elements in their original order.
+///
+/// If an element does not fit in its buffer, returns `Err`, dropping all the
+/// elements, and without calling `pred` for the rest. If `pred` panics, all
+/// the elements are dropped as well.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice::{self, PartitionOverflow};
+/// let mut buf = [MaybeUninit::uninit(); 6];
+/// let mut src = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// src.extend([1, 2, 3, 4, 5, 6]);
+/// let mut evens = [MaybeUninit::uninit(); 3];
+/// let mut odds = [MaybeUninit::uninit(); 3];
+/// let (evens, odds) =
+///     owning_slice::partition_into(src, |x| x % 2 == 0, &mut evens, &mut odds).unwrap();
+/// assert_eq!(evens, [2, 4, 6]);
+/// assert_eq!(odds, [1, 3, 5]);
+///
+/// let mut src = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// src.extend([2, 4, 5, 6]);
+/// let mut evens = [MaybeUninit::uninit(); 2];
+/// let mut odds = [MaybeUninit::uninit(); 2];
+/// let err = owning_slice::partition_into(src, |x| x % 2 == 0, &mut evens, &mut odds).unwrap_err();
+/// assert_eq!(err, PartitionOverflow::Yes);
+/// ```
+///
+/// Every element is dropped exactly once, even if `pred` panics:
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_slice;
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// struct Counted(u32);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut buf = [const { MaybeUninit::uninit() }; 5];
+/// let mut src = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// src.extend((0..5).map(Counted));
+/// let mut yes = [const { MaybeUninit::uninit() }; 5];
+/// let mut no = [const { MaybeUninit::uninit() }; 5];
+/// let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+///     let _ = owning_slice::partition_into(
+///         src,
+///         |x| {
+///             assert_ne!(x.0, 3);
+///             x.0 % 2 == 0
+///         },
+///         &mut yes,
+///         &mut no,
+///     );
+/// }));
+/// assert!(result.is_err());
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 5);
+/// ```
+pub fn partition_into<'a, 'b, T>(
+    mut src: OwningSlice<'_, T>,
+    mut pred: impl FnMut(&T) -> bool,
+    yes: &'a mut [MaybeUninit<T>],
+    no: &'b mut [MaybeUninit<T>],
+) -> Result<(OwningSlice<'a, T>, OwningSlice<'b, T>), PartitionOverflow> {
+    /// Drops the elements of `src` that have not been moved out yet.
+    struct Remaining<T> {
+        base: *mut T,
+        read: usize,
+        len: usize,
+    }
+
+    impl<T> Drop for Remaining<T> {
+        fn drop(&mut self) {
+            unsafe {
+                core::ptr::drop_in_place(core::ptr::slice_from_raw_parts_mut(
+                    self.base.add(self.read),
+                    self.len - self.read,
+                ));
+            }
+        }
+    }
+
+    let mut remaining = Remaining {
+        base: src.as_mut_ptr(),
+        read: 0,
+        len: src.len(),
+    };
+    // The elements are now owned by `remaining`, until they are read.
+    unsafe { src.set_len(0) };
+    let mut yes = PartialInitGuard::new(yes);
+    let mut no = PartialInitGuard::new(no);
+    while remaining.read < remaining.len {
+        let item = unsafe { remaining.base.add(remaining.read).read() };
+        remaining.read += 1;
+        if pred(&item) {
+            if yes.is_full() {
+                return Err(PartitionOverflow::Yes);
+            }
+            yes.push(item);
+        } else {
+            if no.is_full() {
+                return Err(PartitionOverflow::No);
+            }
+            no.push(item);
+        }
+    }
+    Ok((yes.finish(), no.finish()))
+}
+
+/// Consume an `OwningSlice<'a, T>` in arbitrary order, passing each element to
+/// `f`, and return the whole buffer, now uninitialized.
+///
+/// Elements are removed with [`Vec::swap_remove`] from the front, so each step
+/// is *O*(1) and the order in which `f` sees them is unspecified. The returned
+/// slice covers the full capacity, so it can be reused, e.g. with
+/// [`empty_from_maybeuninit_slice`].
+///
+/// If `f` panics, the element it was given is dropped by `f`, and the
+/// remaining elements are dropped, so every element is either passed to `f` or
+/// dropped exactly once.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice;
+/// let mut buf = [const { MaybeUninit::uninit() }; 6];
+/// let start = buf.as_ptr();
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// vec.extend((1..=5).map(|n| n.to_string()));
+///
+/// let mut seen = vec![];
+/// let buf = owning_slice::drain_swap_recover(vec, |s| seen.push(s));
+/// seen.sort();
+/// assert_eq!(seen, ["1", "2", "3", "4", "5"]);
+/// assert_eq!(buf.len(), 6);
+/// assert_eq!(buf.as_ptr(), start);
+///
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(buf);
+/// vec.push(String::from("reused"));
+/// assert_eq!(vec.capacity(), 6);
+/// ```
+///
+/// If `f` panics, every element is still dropped exactly once:
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_slice;
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// struct Counted(u32);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut buf = [const { MaybeUninit::uninit() }; 4];
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// vec.extend((0..4).map(Counted));
+/// let mut consumed = 0;
+/// let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+///     owning_slice::drain_swap_recover(vec, |c| {
+///         consumed += 1;
+///         assert!(consumed < 2, "giving up on {}", c.0);
+///     });
+/// }));
+/// assert!(result.is_err());
+/// assert_eq!(consumed, 2);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 4);
+/// ```
+pub fn drain_swap_recover<'a, T>(
+    mut v: OwningSlice<'a, T>,
+    mut f: impl FnMut(T),
+) -> &'a mut [MaybeUninit<T>] {
+    while !v.is_empty() {
+        f(v.swap_remove(0));
+    }
+    let capacity = v.capacity();
+    let ptr = v.as_mut_ptr().cast::<MaybeUninit<T>>();
+    core::mem::forget(v);
+    unsafe { core::slice::from_raw_parts_mut(ptr, capacity) }
+}
+
+/// The error type returned by [`from_bytes_for_slice_recover`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ReinterpretError {
+    /// The element type is zero-sized, so the number of elements is unbounded.
+    ZeroSized,
+    /// The byte buffer is too small to hold a single aligned element.
+    TooSmall,
+}
+
+impl fmt::Display for ReinterpretError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReinterpretError::ZeroSized => f.write_str("element type is zero-sized"),
+            ReinterpretError::TooSmall => {
+                f.write_str("byte buffer is too small to hold an aligned element")
+            }
+        }
+    }
+}
+
+/// Create a `OwningSlice<'a, T>` with a length of 0 from the longest aligned
+/// prefix of a `&'a mut [MaybeUninit<u8>]>` that fits a whole number of `T`s,
+/// and return it along with the trailing bytes that did not fit a whole `T`.
+///
+/// If `bytes` is not aligned for `T`, the first few bytes are skipped as
+/// padding. Those bytes are not part of either returned value, since they are
+/// not contiguous with the trailing bytes; they stay borrowed for `'a`.
+///
+/// Returns `Err` if `T` is zero-sized, or if not even one `T` fits in `bytes`
+/// after padding.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice::{self, ReinterpretError};
+/// #[repr(C, align(4))]
+/// struct Buf([MaybeUninit<u8>; 10]);
+/// let mut buf = Buf([MaybeUninit::uninit(); 10]);
+///
+/// let (mut vec, rest) = owning_slice::from_bytes_for_slice_recover::<u32>(&mut buf.0).unwrap();
+/// assert_eq!(vec.capacity(), 2);
+/// assert_eq!(rest.len(), 2);
+/// vec.extend([1, 2]);
+/// rest[0].write(3);
+/// drop(vec);
+///
+/// // Starting one byte in, 3 bytes of padding are skipped, so only one `u32`
+/// // fits, and the same 2 trailing bytes are left over.
+/// let (vec, rest) = owning_slice::from_bytes_for_slice_recover::<u32>(&mut buf.0[1..]).unwrap();
+/// assert_eq!(vec.capacity(), 1);
+/// assert_eq!(rest.len(), 2);
+/// assert_eq!(vec.as_ptr() as usize % 4, 0);
+/// drop(vec);
+///
+/// assert_eq!(
+///     owning_slice::from_bytes_for_slice_recover::<u32>(&mut buf.0[..3]).err(),
+///     Some(ReinterpretError::TooSmall)
+/// );
+/// assert_eq!(
+///     owning_slice::from_bytes_for_slice_recover::<()>(&mut buf.0).err(),
+///     Some(ReinterpretError::ZeroSized)
+/// );
+/// ```
+pub fn from_bytes_for_slice_recover<T>(
+    bytes: &mut [MaybeUninit<u8>],
+) -> Result<(OwningSlice<'_, T>, &mut [MaybeUninit<u8>]), ReinterpretError> {
+    let size = core::mem::size_of::<T>();
+    if size == 0 {
+        return Err(ReinterpretError::ZeroSized);
+    }
+    let padding = bytes.as_ptr().align_offset(core::mem::align_of::<T>());
+    if padding > bytes.len() {
+        return Err(ReinterpretError::TooSmall);
+    }
+    let count = (bytes.len() - padding) / size;
+    if count == 0 {
+        return Err(ReinterpretError::TooSmall);
+    }
+    let (typed, rest) = bytes[padding..].split_at_mut(count * size);
+    let vec = unsafe {
+        Vec::from_raw_parts_in(
+            typed.as_mut_ptr().cast::<T>(),
+            0,
+            count,
+            NoopAllocator(PhantomData),
+        )
+    };
+    Ok((vec, rest))
+}
+
+/// The error type returned by [`diagnose_capacity`] when a buffer is too small.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CapacityShortfall {
+    /// The number of elements that were needed.
+    pub needed: usize,
+    /// The number of elements that are available.
+    pub available: usize,
+}
+
+impl fmt::Display for CapacityShortfall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "buffer has capacity for {} elements, but {} are needed",
+            self.available, self.needed
+        )
+    }
+}
+
+/// Check that `slot` is big enough to hold `needed` elements, e.g. before
+/// creating an `OwningSlice<'a, T>` from it that will later have `needed`
+/// elements pushed.
+///
+/// Pushing past the capacity of an `OwningSlice` aborts the process (see
+/// [`handle_alloc_error`][alloc::alloc::handle_alloc_error]), so this can be
+/// used to report an undersized buffer up front instead.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice::{self, CapacityShortfall};
+/// let buf: [MaybeUninit<u32>; 4] = [MaybeUninit::uninit(); 4];
+/// assert_eq!(owning_slice::diagnose_capacity(4, &buf), Ok(()));
+///
+/// let err = owning_slice::diagnose_capacity(6, &buf).unwrap_err();
+/// assert_eq!(
+///     err,
+///     CapacityShortfall {
+///         needed: 6,
+///         available: 4
+///     }
+/// );
+/// assert_eq!(
+///     err.to_string(),
+///     "buffer has capacity for 4 elements, but 6 are needed"
+/// );
+/// ```
+pub fn diagnose_capacity<T>(
+    needed: usize,
+    slot: &[MaybeUninit<T>],
+) -> Result<(), CapacityShortfall> {
+    if needed > slot.len() {
+        Err(CapacityShortfall {
+            needed,
+            available: slot.len(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Return the [`Layout`] of a buffer big enough to hold `v.len() + additional`
+/// elements, or `None` if `v` already has the capacity for them.
+///
+/// Reserving past the capacity of an `OwningSlice` fails, since it cannot
+/// reallocate. This gives the size and alignment of a bigger buffer to get
+/// elsewhere, and move the elements into with
+/// [`empty_from_maybeuninit_slice`] and [`Vec::drain`].
+///
+/// # Panics
+///
+/// Panics if `v.len() + additional` elements would overflow `isize::MAX`
+/// bytes, like [`Vec::reserve_exact`].
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::{alloc::Layout, mem::MaybeUninit};
+/// use noop_allocator::owning_slice;
+/// let mut buf = [MaybeUninit::uninit(); 4];
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// vec.extend([1u32, 2, 3]);
+/// assert_eq!(owning_slice::growth_requirement(&vec, 1), None);
+///
+/// let layout = owning_slice::growth_requirement(&vec, 3).unwrap();
+/// assert_eq!(layout, Layout::array::<u32>(6).unwrap());
+/// assert_eq!((layout.size(), layout.align()), (24, 4));
+///
+/// let mut bigger = [MaybeUninit::uninit(); 6];
+/// let mut moved = owning_slice::empty_from_maybeuninit_slice(&mut bigger);
+/// moved.extend(vec.drain(..));
+/// moved.extend([4, 5, 6]);
+/// assert_eq!(moved, [1, 2, 3, 4, 5, 6]);
+/// ```
+pub fn growth_requirement<T>(v: &OwningSlice<'_, T>, additional: usize) -> Option<Layout> {
+    let needed = v.len().checked_add(additional).expect("capacity overflow");
+    if needed <= v.capacity() {
+        return None;
+    }
+    Some(Layout::array::<T>(needed).expect("capacity overflow"))
+}
+
+/// Return the [`Layout`] of the storage borrowed by an `OwningSlice<'a, T>`,
+/// including its spare capacity, e.g. to report how much of a buffer it
+/// occupies.
+///
+/// This is `Layout::array::<T>(v.capacity())`. An `OwningSlice` of a
+/// zero-sized type has a capacity of `usize::MAX`, but its layout still has
+/// size 0.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::{alloc::Layout, mem::MaybeUninit};
+/// use noop_allocator::owning_slice;
+/// let mut buf = [MaybeUninit::<u32>::uninit(); 5];
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// assert_eq!(
+///     owning_slice::backing_layout(&vec),
+///     Layout::array::<u32>(5).unwrap()
+/// );
+/// vec.extend([1, 2]);
+/// assert_eq!(owning_slice::backing_layout(&vec).size(), 20);
+///
+/// let mut buf: [MaybeUninit<u32>; 0] = [];
+/// let vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// assert_eq!(
+///     owning_slice::backing_layout(&vec),
+///     Layout::array::<u32>(0).unwrap()
+/// );
+///
+/// let mut buf = [MaybeUninit::<()>::uninit(); 3];
+/// let vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// assert_eq!(vec.capacity(), usize::MAX);
+/// assert_eq!(owning_slice::backing_layout(&vec).size(), 0);
+/// assert_eq!(owning_slice::backing_layout(&vec).align(), 1);
+/// ```
+pub fn backing_layout<T>(v: &OwningSlice<'_, T>) -> Layout {
+    // The capacity is the length of the borrowed slice, which fits in memory
+    // (or `usize::MAX` for ZSTs, which are always fine).
+    Layout::array::<T>(v.capacity()).expect("capacity of an `OwningSlice` fits in a `Layout`")
+}
+
+/// Returns a value that displays the address of the buffer borrowed by `v`,
+/// like `{:p}` of [`v.as_ptr()`][slice::as_ptr].
+///
+/// See [`owning_ref::ptr_fmt`][crate::owning_ref::ptr_fmt]. The address is
+/// that of the buffer, even if `v` is empty.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice;
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 4];
+/// let expected = format!("{:p}", buf.as_ptr());
+/// let vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// assert_eq!(owning_slice::ptr_fmt(&vec).to_string(), expected);
+/// ```
+pub fn ptr_fmt<T>(v: &OwningSlice<'_, T>) -> impl fmt::Display {
+    crate::owning_ref::PtrFmt(v.as_ptr().cast())
+}
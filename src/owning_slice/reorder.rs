@@ -0,0 +1,106 @@
+//! Order-changing operations on an [`OwningSlice`], and reclaiming its
+//! storage afterwards.
+//!
+//! These are the slice methods of the same names, which move elements in
+//! place without allocating, gathered here so that code working with
+//! borrowed storage has a vetted set of reorderings to reach for, rather than
+//! e.g. `v.iter().rev().cloned().collect()` or `v.drain(..mid)` followed by
+//! `extend`, which need a second buffer. None of them call back into user
+//! code, so they cannot be interrupted by a panic partway through: each one
+//! either panics before moving anything (on an out-of-bounds index) or
+//! completes.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use std::mem::MaybeUninit;
+//! use noop_allocator::owning_slice::{self, reorder};
+//! let mut buf = [const { MaybeUninit::uninit() }; 6];
+//! let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+//! vec.extend(["a", "b", "c", "d", "e"].map(String::from));
+//!
+//! reorder::rotate_left(&mut vec, 2);
+//! assert_eq!(vec, ["c", "d", "e", "a", "b"]);
+//! reorder::rotate_right(&mut vec, 1);
+//! assert_eq!(vec, ["b", "c", "d", "e", "a"]);
+//! reorder::reverse(&mut vec);
+//! assert_eq!(vec, ["a", "e", "d", "c", "b"]);
+//! reorder::swap(&mut vec, 1, 4);
+//! assert_eq!(vec, ["a", "b", "d", "c", "e"]);
+//!
+//! let storage = reorder::into_maybeuninit_slice(vec);
+//! assert_eq!(storage.len(), 6);
+//! ```
+
+use core::mem::MaybeUninit;
+
+use super::OwningSlice;
+
+/// Rotates `v` in place so that the element at `mid` becomes the first.
+///
+/// # Panics
+///
+/// Panics, without moving anything, if `mid > v.len()`.
+pub fn rotate_left<T>(v: &mut OwningSlice<'_, T>, mid: usize) {
+    v.rotate_left(mid);
+}
+
+/// Rotates `v` in place so that the element at `v.len() - k` becomes the
+/// first.
+///
+/// # Panics
+///
+/// Panics, without moving anything, if `k > v.len()`.
+pub fn rotate_right<T>(v: &mut OwningSlice<'_, T>, k: usize) {
+    v.rotate_right(k);
+}
+
+/// Reverses the order of the elements of `v` in place.
+pub fn reverse<T>(v: &mut OwningSlice<'_, T>) {
+    v.reverse();
+}
+
+/// Swaps the elements of `v` at indices `a` and `b`.
+///
+/// # Panics
+///
+/// Panics, without moving anything, if `a` or `b` is out of bounds.
+pub fn swap<T>(v: &mut OwningSlice<'_, T>, a: usize, b: usize) {
+    v.swap(a, b);
+}
+
+/// Drops the elements of `v`, and returns its storage, the whole capacity, as
+/// uninitialized slots for reuse.
+///
+/// If dropping an element panics, the remaining elements are still dropped,
+/// but the storage is not returned.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::owning_slice::{self, reorder};
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// struct Counted(u32);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut buf = [const { MaybeUninit::uninit() }; 4];
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// vec.extend((0..3).map(Counted));
+/// reorder::reverse(&mut vec);
+///
+/// let storage = reorder::into_maybeuninit_slice(vec);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 3);
+/// let mut vec = owning_slice::empty_from_maybeuninit_slice(storage);
+/// vec.push(Counted(7));
+/// assert_eq!(vec.capacity(), 4);
+/// ```
+pub fn into_maybeuninit_slice<'a, T>(mut v: OwningSlice<'a, T>) -> &'a mut [MaybeUninit<T>] {
+    v.clear();
+    super::drain_swap_recover(v, |_| unreachable!())
+}
@@ -0,0 +1,149 @@
+//! An allocator adapter that never moves an allocation.
+use core::{
+    alloc::{AllocError, Allocator, Layout},
+    ptr::NonNull,
+};
+
+/// An [`Allocator`] adapter that allocates from an inner allocator, but never
+/// moves an allocation once it has been made.
+///
+/// Like [`NoopAllocator`][crate::NoopAllocator], it guarantees that a
+/// collection's buffer stays at the same address for as long as it lives, e.g.
+/// for DMA targets or data that is addressed by pointer from elsewhere, while
+/// still owning its memory. Specifically:
+/// * [`allocate`][Allocator::allocate] and
+///   [`allocate_zeroed`][Allocator::allocate_zeroed] allocate from the inner
+///   allocator.
+/// * [`grow`][Allocator::grow] and [`grow_zeroed`][Allocator::grow_zeroed]
+///   always return `Err`, so e.g. `Vec::try_reserve` fails instead of moving
+///   the elements, and `Vec::reserve` or `Vec::push` past the capacity abort
+///   the process.
+/// * [`shrink`][Allocator::shrink] returns the original pointer (with the
+///   length of the new layout) if it is aligned for the new layout, and `Err`
+///   otherwise. The inner allocation keeps its original size.
+/// * [`deallocate`][Allocator::deallocate] returns the whole original
+///   allocation to the inner allocator.
+///
+/// To deallocate with the original layout after a `shrink`, each allocation
+/// stores its layout in a header just before the returned pointer, so it uses
+/// `size_of::<Layout>()` bytes more than requested, plus any padding needed to
+/// align the allocation after the header.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::alloc::Global;
+/// use noop_allocator::{pinning::PinningAllocator, quota::QuotaAllocator};
+/// let quota = QuotaAllocator::new(Global, 1024);
+/// let alloc = PinningAllocator::new(&quota);
+///
+/// let mut v = Vec::<u32, _>::with_capacity_in(8, &alloc);
+/// let addr = v.as_ptr();
+/// v.extend(0..8);
+///
+/// // Growing would move the elements, so it fails.
+/// assert!(v.try_reserve(1).is_err());
+/// assert_eq!(v.as_ptr(), addr);
+///
+/// // Shrinking happens in place.
+/// v.truncate(3);
+/// v.shrink_to_fit();
+/// assert_eq!(v.capacity(), 3);
+/// assert_eq!(v.as_ptr(), addr);
+/// assert_eq!(v, [0, 1, 2]);
+///
+/// // The original allocation is returned in full.
+/// drop(v);
+/// assert_eq!(quota.used(), 0);
+/// ```
+pub struct PinningAllocator<A> {
+    inner: A,
+}
+
+/// Returns the layout of the inner allocation for `layout`, and the offset of
+/// the returned pointer in it.
+fn with_header(layout: Layout) -> Result<(Layout, usize), AllocError> {
+    Layout::new::<Layout>()
+        .extend(layout)
+        .map_err(|_| AllocError)
+}
+
+impl<A: Allocator> PinningAllocator<A> {
+    /// Creates a new `PinningAllocator<A>` allocating from `inner`.
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+
+    /// Returns a reference to the inner allocator.
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    fn allocate_with(
+        &self,
+        layout: Layout,
+        allocate: impl FnOnce(&A, Layout) -> Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let (outer, offset) = with_header(layout)?;
+        let block = allocate(&self.inner, outer)?;
+        // SAFETY: `offset + layout.size() <= outer.size()`, and the header
+        // fits before `offset` and is aligned, by `Layout::extend`.
+        unsafe {
+            let ptr = block.cast::<u8>().add(offset);
+            ptr.cast::<Layout>().sub(1).write(layout);
+            Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+        }
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for PinningAllocator<A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate_with(layout, A::allocate)
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocate_with(layout, A::allocate_zeroed)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, _layout: Layout) {
+        let original = ptr.cast::<Layout>().sub(1).read();
+        // SAFETY: This succeeded when `ptr` was allocated.
+        let (outer, offset) = with_header(original).unwrap_unchecked();
+        self.inner.deallocate(ptr.sub(offset), outer)
+    }
+
+    unsafe fn grow(
+        &self,
+        _ptr: NonNull<u8>,
+        _old_layout: Layout,
+        _new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        Err(AllocError)
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        _ptr: NonNull<u8>,
+        _old_layout: Layout,
+        _new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        Err(AllocError)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        debug_assert!(
+            new_layout.size() <= old_layout.size(),
+            "`new_layout.size()` must be smaller than or equal to `old_layout.size()`"
+        );
+        if ptr.as_ptr() as usize & (new_layout.align() - 1) != 0 {
+            return Err(AllocError);
+        }
+        Ok(NonNull::slice_from_raw_parts(ptr, new_layout.size()))
+    }
+}
@@ -0,0 +1,605 @@
+//! Pools of typed slots that hand out individually owned values, and of
+//! buffers that hand out fixed-capacity chunks, which take the slot or chunk
+//! back when the handle is dropped.
+//!
+//! An [`OwningRef`][crate::owning_ref::OwningRef] cannot return its slot to a
+//! pool, since [`NoopAllocator::deallocate`][crate::NoopAllocator] does not
+//! know about one. Instead, each pool is itself an [`Allocator`] whose
+//! `deallocate` frees the slot, and hands out values as `Box`es using a
+//! reference to the pool as their allocator.
+use core::{
+    alloc::{AllocError, Allocator, Layout},
+    cell::{Cell, UnsafeCell},
+    marker::PhantomData,
+    mem::{self, ManuallyDrop, MaybeUninit},
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+};
+
+use crate::{NoopAllocator, owning_slice::OwningSlice};
+use alloc::{boxed::Box, vec::Vec};
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+use crate::sync::{AtomicBool, Ordering};
+
+/// A value in a [`StaticPool<T, N>`][StaticPool], a.k.a. a `Box<T, &'a
+/// StaticPool<T, N>>`.
+///
+/// Dropping it drops the value and frees its slot.
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+pub type PooledBox<'a, T, const N: usize> = Box<T, &'a StaticPool<T, N>>;
+
+/// A pool of `N` slots for values of type `T`, which can be declared as a
+/// `static`.
+///
+/// [`try_claim`][Self::try_claim] moves a value into a free slot, returning a
+/// [`PooledBox`] that owns it; dropping the `PooledBox` frees the slot again.
+/// If every slot is in use, the value is given back. Claiming from a `static`
+/// pool gives a `PooledBox<'static, T, N>`, which can be sent to other
+/// threads.
+///
+/// Each slot has an atomic flag, so the pool can be shared between threads.
+/// Finding a free slot takes *O*(`N`) time in the worst case.
+///
+/// A `StaticPool<T, N>` is an [`Allocator`] for any layout that fits in a
+/// `T`, so it can also be used directly, e.g. `Box::try_new_in(x, &POOL)`.
+/// Zero-sized allocations do not use a slot.
+///
+/// This type is only available on targets with native atomics, or with the
+/// `portable-atomic` feature enabled.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// use noop_allocator::pool::{PooledBox, StaticPool};
+/// static BUFFERS: StaticPool<[u8; 64], 2> = StaticPool::new();
+///
+/// let a: PooledBox<'static, _, 2> = BUFFERS.try_claim([1; 64]).unwrap();
+/// let b = BUFFERS.try_claim([2; 64]).unwrap();
+/// assert_eq!(BUFFERS.try_claim([3; 64]), Err([3; 64]));
+/// assert_eq!(BUFFERS.available(), 0);
+///
+/// let addr = &raw const *a;
+/// drop(a);
+/// let c = BUFFERS.try_claim([3; 64]).unwrap();
+/// assert_eq!(&raw const *c, addr);
+/// std::thread::spawn(move || assert_eq!(b[0] + c[0], 5))
+///     .join()
+///     .unwrap();
+/// assert_eq!(BUFFERS.available(), 2);
+/// ```
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+pub struct StaticPool<T, const N: usize> {
+    slots: [UnsafeCell<MaybeUninit<T>>; N],
+    used: [AtomicBool; N],
+}
+
+// SAFETY: Each slot is only accessed through the single allocation its flag
+// was claimed for, which may be sent to another thread.
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+unsafe impl<T: Send, const N: usize> Sync for StaticPool<T, N> {}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<T, const N: usize> StaticPool<T, N> {
+    /// Creates a new `StaticPool` with every slot free.
+    pub const fn new() -> Self {
+        Self {
+            slots: [const { UnsafeCell::new(MaybeUninit::uninit()) }; N],
+            used: [const { AtomicBool::new(false) }; N],
+        }
+    }
+
+    /// Returns the number of slots, `N`.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the number of free slots.
+    ///
+    /// While other threads are claiming or freeing slots, this is approximate.
+    pub fn available(&self) -> usize {
+        self.used
+            .iter()
+            .filter(|used| !used.load(Ordering::Relaxed))
+            .count()
+    }
+
+    /// Moves `value` into a free slot, returning a [`PooledBox`] that owns it,
+    /// or gives `value` back if every slot is in use.
+    pub fn try_claim(&self, value: T) -> Result<PooledBox<'_, T, N>, T> {
+        match self.allocate(Layout::new::<T>()) {
+            Ok(ptr) => {
+                let ptr = ptr.cast::<T>().as_ptr();
+                // SAFETY: `ptr` was allocated for a `T` by `self`.
+                unsafe {
+                    ptr.write(value);
+                    Ok(Box::from_raw_in(ptr, self))
+                }
+            }
+            Err(AllocError) => Err(value),
+        }
+    }
+
+    /// Returns the index of the slot `ptr` points to.
+    fn index_of(&self, ptr: NonNull<u8>) -> usize {
+        let offset = ptr.as_ptr() as usize - self.slots.as_ptr() as usize;
+        offset / mem::size_of::<T>()
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<T, const N: usize> Default for StaticPool<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<T, const N: usize> core::fmt::Debug for StaticPool<T, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("StaticPool")
+            .field("capacity", &N)
+            .field("available", &self.available())
+            .finish()
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+unsafe impl<T, const N: usize> Allocator for StaticPool<T, N> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if layout.size() == 0 {
+            return Ok(NonNull::slice_from_raw_parts(layout.dangling_ptr(), 0));
+        }
+        if layout.size() > mem::size_of::<T>() || layout.align() > mem::align_of::<T>() {
+            return Err(AllocError);
+        }
+        let index = self
+            .used
+            .iter()
+            .position(|used| {
+                used.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            })
+            .ok_or(AllocError)?;
+        let ptr = NonNull::new(self.slots[index].get().cast::<u8>()).unwrap();
+        Ok(NonNull::slice_from_raw_parts(ptr, mem::size_of::<T>()))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if layout.size() == 0 {
+            return;
+        }
+        let was_used = self.used[self.index_of(ptr)].swap(false, Ordering::Release);
+        debug_assert!(was_used, "deallocated a free slot of a `StaticPool`");
+    }
+}
+
+/// A value in a [`SlotPool<'a, T, N>`][SlotPool], a.k.a. a `Box<T, &'p
+/// SlotPool<'a, T, N>>`.
+///
+/// It behaves like an [`OwningRef`][crate::owning_ref::OwningRef], except that
+/// dropping it also returns its slot to the pool.
+pub type PooledRef<'p, 'a, T, const N: usize> = Box<T, &'p SlotPool<'a, T, N>>;
+
+/// A pool of `N` slots for values of type `T`, over a mutably borrowed
+/// `[MaybeUninit<T>; N]`.
+///
+/// [`try_insert`][Self::try_insert] moves a value into a free slot, returning a
+/// [`PooledRef`] that owns it; dropping the `PooledRef` returns the slot to
+/// the pool's free list. If every slot is in use, the value is given back. The
+/// pool is used through `&self`, so any number of `PooledRef`s can be live at
+/// once.
+///
+/// A `SlotPool<'a, T, N>` is an [`Allocator`] for any layout that fits in a
+/// `T`, so generic code can use `Box::new_in(value, &pool)`, and node-based
+/// collections whose nodes fit in a `T` can allocate from it. Layouts that are
+/// larger or more aligned than `T` are rejected. Zero-sized allocations do not
+/// use a slot.
+///
+/// Unlike [`StaticPool`], a `SlotPool` cannot be shared between threads, and
+/// finding a free slot takes *O*(1) time.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::pool::SlotPool;
+/// let mut buf = [const { MaybeUninit::uninit() }; 3];
+/// let pool = SlotPool::new(&mut buf);
+///
+/// let a = pool.try_insert(String::from("a")).unwrap();
+/// let b = Box::new_in(String::from("b"), &pool);
+/// let c = pool.try_insert(String::from("c")).unwrap();
+/// assert_eq!((pool.len(), pool.capacity()), (3, 3));
+/// assert_eq!(pool.try_insert(String::from("d")), Err(String::from("d")));
+/// assert!(Box::try_new_in(String::new(), &pool).is_err());
+///
+/// drop(b);
+/// assert_eq!(pool.live().collect::<Vec<_>>(), [0, 2]);
+/// let d = pool.try_insert(String::from("d")).unwrap();
+/// assert_eq!(pool.index_of(&d), 1);
+/// assert_eq!([&*a, &*c, &*d], ["a", "c", "d"]);
+///
+/// // Layouts larger than `T` are rejected.
+/// drop(a);
+/// assert!(Box::try_new_in([0u8; 64], &pool).is_err());
+/// ```
+///
+/// The `PooledRef`s borrow the pool, so it cannot be dropped while any of them
+/// are live:
+///
+/// ```rust,compile_fail,E0505
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::pool::SlotPool;
+/// let mut buf = [const { MaybeUninit::uninit() }; 2];
+/// let pool = SlotPool::new(&mut buf);
+/// let a = pool.try_insert(1).unwrap();
+/// drop(pool);
+/// assert_eq!(*a, 1);
+/// ```
+pub struct SlotPool<'a, T, const N: usize> {
+    slots: NonNull<T>,
+    used: [Cell<bool>; N],
+    next_free: [Cell<usize>; N],
+    free_head: Cell<usize>,
+    len: Cell<usize>,
+    _marker: PhantomData<&'a mut [MaybeUninit<T>; N]>,
+}
+
+// SAFETY: A `SlotPool<'a, T, N>` is equivalent to the `&'a mut [MaybeUninit<T>;
+// N]` it was created from, plus `Cell`s.
+unsafe impl<T: Send, const N: usize> Send for SlotPool<'_, T, N> {}
+
+impl<'a, T, const N: usize> SlotPool<'a, T, N> {
+    /// Creates a new `SlotPool<'a, T, N>` over `slots`, with every slot free.
+    pub fn new(slots: &'a mut [MaybeUninit<T>; N]) -> Self {
+        Self {
+            slots: NonNull::from(slots).cast(),
+            used: [const { Cell::new(false) }; N],
+            next_free: core::array::from_fn(|i| Cell::new(i + 1)),
+            free_head: Cell::new(0),
+            len: Cell::new(0),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of slots in use.
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    /// Returns `true` if no slots are in use.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of slots, `N`.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Moves `value` into a free slot, returning a [`PooledRef`] that owns it,
+    /// or gives `value` back if every slot is in use.
+    pub fn try_insert(&self, value: T) -> Result<PooledRef<'_, 'a, T, N>, T> {
+        match self.allocate(Layout::new::<T>()) {
+            Ok(ptr) => {
+                let ptr = ptr.cast::<T>().as_ptr();
+                // SAFETY: `ptr` was allocated for a `T` by `self`.
+                unsafe {
+                    ptr.write(value);
+                    Ok(Box::from_raw_in(ptr, self))
+                }
+            }
+            Err(AllocError) => Err(value),
+        }
+    }
+
+    /// Returns the indices of the slots in use, in increasing order.
+    ///
+    /// The values themselves are owned by their `PooledRef`s, which may be
+    /// mutating them, so the pool does not give out references to them.
+    pub fn live(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..N).filter(|&i| self.used[i].get())
+    }
+
+    /// Returns the index of the slot that `value` lives in.
+    pub fn index_of(&self, value: &PooledRef<'_, 'a, T, N>) -> usize {
+        assert!(
+            core::ptr::eq(*Box::allocator(value), self),
+            "`PooledRef` is from a different pool"
+        );
+        self.slot_index(NonNull::from(&**value).cast())
+    }
+
+    /// Returns the index of the slot `ptr` points to.
+    fn slot_index(&self, ptr: NonNull<u8>) -> usize {
+        let offset = ptr.as_ptr() as usize - self.slots.as_ptr() as usize;
+        offset / mem::size_of::<T>()
+    }
+}
+
+impl<T, const N: usize> core::fmt::Debug for SlotPool<'_, T, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SlotPool")
+            .field("len", &self.len())
+            .field("capacity", &N)
+            .finish()
+    }
+}
+
+unsafe impl<T, const N: usize> Allocator for SlotPool<'_, T, N> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if layout.size() == 0 {
+            return Ok(NonNull::slice_from_raw_parts(layout.dangling_ptr(), 0));
+        }
+        if layout.size() > mem::size_of::<T>() || layout.align() > mem::align_of::<T>() {
+            return Err(AllocError);
+        }
+        let index = self.free_head.get();
+        if index == N {
+            return Err(AllocError);
+        }
+        self.free_head.set(self.next_free[index].get());
+        self.used[index].set(true);
+        self.len.set(self.len.get() + 1);
+        let ptr = unsafe { self.slots.add(index) }.cast::<u8>();
+        Ok(NonNull::slice_from_raw_parts(ptr, mem::size_of::<T>()))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if layout.size() == 0 {
+            return;
+        }
+        let index = self.slot_index(ptr);
+        debug_assert!(
+            self.used[index].get(),
+            "deallocated a free slot of a `SlotPool`"
+        );
+        self.used[index].set(false);
+        self.next_free[index].set(self.free_head.get());
+        self.free_head.set(index);
+        self.len.set(self.len.get() - 1);
+    }
+}
+
+/// A pool of equally sized chunks of a mutably borrowed `[MaybeUninit<T>]`,
+/// checked out as empty [`PooledSlice`]s.
+///
+/// [`try_acquire`][Self::try_acquire] returns a [`PooledSlice`], an empty
+/// list of elements with a capacity of `chunk_len`. Dropping the `PooledSlice`
+/// drops the elements it holds and returns the chunk to the pool, so nothing
+/// leaks from one checkout into the next.
+///
+/// Occupancy is tracked with a `u64` bitmap, so a pool has at most
+/// [`MAX_CHUNKS`][Self::MAX_CHUNKS] chunks; any backing memory past that, or
+/// past the last whole chunk, is left unused.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::pool::BufferPool;
+/// let mut backing = [MaybeUninit::<u8>::uninit(); 4 * 1500];
+/// let pool = BufferPool::new(&mut backing, 1500);
+/// assert_eq!(pool.chunk_count(), 4);
+///
+/// let mut packet = pool.try_acquire().unwrap();
+/// assert!(packet.is_empty());
+/// assert_eq!(packet.capacity(), 1500);
+/// packet.extend_from_slice(b"\x45\x00\x00\x54").unwrap();
+/// assert_eq!(pool.available(), 3);
+/// drop(packet);
+/// assert_eq!(pool.available(), 4);
+/// ```
+pub struct BufferPool<'a, T> {
+    start: NonNull<T>,
+    chunk_len: usize,
+    chunk_count: usize,
+    used: Cell<u64>,
+    _marker: PhantomData<&'a mut [MaybeUninit<T>]>,
+}
+
+// SAFETY: A `BufferPool<'a, T>` is equivalent to the `&'a mut
+// [MaybeUninit<T>]` it was created from, plus a `Cell`.
+unsafe impl<T: Send> Send for BufferPool<'_, T> {}
+
+impl<'a, T> BufferPool<'a, T> {
+    /// The maximum number of chunks in a pool.
+    pub const MAX_CHUNKS: usize = u64::BITS as usize;
+
+    /// Creates a new `BufferPool<'a, T>` dividing `backing` into chunks of
+    /// `chunk_len` elements, all of them free.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_len` is 0.
+    pub fn new(backing: &'a mut [MaybeUninit<T>], chunk_len: usize) -> Self {
+        assert!(chunk_len > 0, "`chunk_len` must be non-zero");
+        Self {
+            chunk_count: (backing.len() / chunk_len).min(Self::MAX_CHUNKS),
+            start: NonNull::from(backing).cast(),
+            chunk_len,
+            used: Cell::new(0),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the capacity of each chunk.
+    pub fn chunk_len(&self) -> usize {
+        self.chunk_len
+    }
+
+    /// Returns the total number of chunks.
+    pub fn chunk_count(&self) -> usize {
+        self.chunk_count
+    }
+
+    /// Returns the number of chunks that are not checked out.
+    pub fn available(&self) -> usize {
+        self.chunk_count - self.used.get().count_ones() as usize
+    }
+
+    /// Checks out a free chunk as an empty `PooledSlice`, or returns `None` if
+    /// every chunk is checked out.
+    pub fn try_acquire(&self) -> Option<PooledSlice<'_, 'a, T>> {
+        let all = match self.chunk_count {
+            Self::MAX_CHUNKS => u64::MAX,
+            n => (1 << n) - 1,
+        };
+        let free = all & !self.used.get();
+        if free == 0 {
+            return None;
+        }
+        let index = free.trailing_zeros() as usize;
+        self.used.set(self.used.get() | 1 << index);
+        let chunk = self.chunk_ptr(index);
+        // SAFETY: The chunk was free, so nothing else refers to it.
+        let vec = unsafe {
+            Vec::from_raw_parts_in(chunk.as_ptr(), 0, self.chunk_len, NoopAllocator::new())
+        };
+        Some(PooledSlice {
+            vec: ManuallyDrop::new(vec),
+            pool: self,
+            index,
+        })
+    }
+
+    /// Returns a pointer to the start of chunk `index`.
+    fn chunk_ptr(&self, index: usize) -> NonNull<T> {
+        unsafe { self.start.add(index * self.chunk_len) }
+    }
+}
+
+impl<T> core::fmt::Debug for BufferPool<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BufferPool")
+            .field("chunk_len", &self.chunk_len)
+            .field("chunk_count", &self.chunk_count)
+            .field("available", &self.available())
+            .finish()
+    }
+}
+
+/// A chunk checked out of a [`BufferPool<'a, T>`][BufferPool].
+///
+/// Holds up to [`capacity`][Self::capacity] elements in the chunk, and
+/// dereferences to them as a `[T]`. Dropping it drops the elements and returns
+/// the chunk to the pool.
+///
+/// Unlike an [`OwningSlice`], a `PooledSlice` never gives out the storage it
+/// was created with: there is no way to move, replace, or shrink the chunk
+/// out from under it, so dropping it always returns the chunk.
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::pool::BufferPool;
+/// let mut backing = [MaybeUninit::<u32>::uninit(); 4];
+/// let pool = BufferPool::new(&mut backing, 2);
+/// let mut chunk = pool.try_acquire().unwrap();
+/// assert_eq!(chunk.push(1), Ok(()));
+/// assert_eq!(chunk.push(2), Ok(()));
+/// assert_eq!(chunk.push(3), Err(3));
+/// chunk[0] = 10;
+/// assert_eq!(*chunk, [10, 2]);
+/// ```
+pub struct PooledSlice<'p, 'a, T> {
+    vec: ManuallyDrop<OwningSlice<'a, T>>,
+    pool: &'p BufferPool<'a, T>,
+    index: usize,
+}
+
+impl<T> PooledSlice<'_, '_, T> {
+    /// Returns the index of the chunk in the pool.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the capacity of the chunk.
+    pub fn capacity(&self) -> usize {
+        self.vec.capacity()
+    }
+
+    /// Appends `value`, or returns it back if the chunk is full.
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        if self.vec.len() == self.vec.capacity() {
+            return Err(value);
+        }
+        self.vec.push(value);
+        Ok(())
+    }
+
+    /// Clones and appends the elements of `src`, or returns `Err(())` without
+    /// appending any if they do not all fit.
+    #[allow(clippy::result_unit_err)]
+    pub fn extend_from_slice(&mut self, src: &[T]) -> Result<(), ()>
+    where
+        T: Clone,
+    {
+        if src.len() > self.vec.capacity() - self.vec.len() {
+            return Err(());
+        }
+        // `Vec::extend_from_slice` does not reallocate when it fits.
+        self.vec.extend_from_slice(src);
+        Ok(())
+    }
+
+    /// Removes and returns the last element, or `None` if it is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        self.vec.pop()
+    }
+
+    /// Drops the elements after the first `len`, if any.
+    pub fn truncate(&mut self, len: usize) {
+        self.vec.truncate(len);
+    }
+
+    /// Drops all elements.
+    pub fn clear(&mut self) {
+        self.vec.clear();
+    }
+
+    /// Returns the elements as a slice.
+    pub fn as_slice(&self) -> &[T] {
+        &self.vec
+    }
+
+    /// Returns the elements as a mutable slice.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.vec
+    }
+}
+
+impl<T> Deref for PooledSlice<'_, '_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.vec
+    }
+}
+
+impl<T> DerefMut for PooledSlice<'_, '_, T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        &mut self.vec
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for PooledSlice<'_, '_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PooledSlice")
+            .field("index", &self.index)
+            .field("elements", &self.vec.as_slice())
+            .finish()
+    }
+}
+
+impl<T> Drop for PooledSlice<'_, '_, T> {
+    fn drop(&mut self) {
+        // Drop the elements before the chunk can be checked out again.
+        // SAFETY: `self.vec` is not used again.
+        unsafe { ManuallyDrop::drop(&mut self.vec) };
+        let pool = self.pool;
+        pool.used.set(pool.used.get() & !(1 << self.index));
+    }
+}
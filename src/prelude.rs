@@ -0,0 +1,31 @@
+//! Re-exports of the types and modules most code borrowing storage needs.
+//!
+//! ```rust
+//! # use std::mem::MaybeUninit;
+//! use noop_allocator::prelude::*;
+//! let mut slot = MaybeUninit::uninit();
+//! let uninit: OwningRef<'_, MaybeUninit<u32>> = (&mut slot).into();
+//! let mut value = unsafe {
+//!     owning_ref::assume_init({
+//!         let mut uninit = uninit;
+//!         uninit.write(5);
+//!         uninit
+//!     })
+//! };
+//! *value += 1;
+//! assert_eq!(*value, 6);
+//!
+//! let mut buf = [MaybeUninit::uninit(); 4];
+//! let mut vec: OwningSlice<'_, u8> = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+//! vec.extend_from_slice(b"abc");
+//! assert_eq!(vec, b"abc");
+//! ```
+//!
+//! The constructors are reached through their modules, e.g.
+//! `owning_ref::from_maybeuninit_write`, since the two modules share many
+//! function names.
+pub use crate::{
+    NoopAllocator,
+    owning_ref::{self, OwningRef},
+    owning_slice::{self, OwningSlice},
+};
@@ -0,0 +1,152 @@
+//! An allocator adapter that limits the number of outstanding bytes.
+use core::{
+    alloc::{AllocError, Allocator, Layout},
+    ptr::NonNull,
+};
+
+use crate::sync::{AtomicUsize, Ordering};
+
+/// An [`Allocator`] adapter that rejects requests that would take the total
+/// size of outstanding allocations from an inner allocator over a limit.
+///
+/// Allocations are accounted by the `size` of their [`Layout`]: `allocate`
+/// adds it, `deallocate` subtracts it, and `grow` and `shrink` add or subtract
+/// the difference between the old and new sizes. Space is reserved before the
+/// inner allocator is called and released again if it fails, so the count is
+/// unchanged by failed requests. The count is atomic, so a `&QuotaAllocator<A>`
+/// can be shared between threads if `A: Sync`.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::alloc::Global;
+/// use noop_allocator::quota::QuotaAllocator;
+/// let quota = QuotaAllocator::new(Global, 64);
+///
+/// let mut v = Vec::<u8, _>::with_capacity_in(32, &quota);
+/// let b = Box::new_in([0u64; 2], &quota);
+/// assert_eq!(quota.used(), 48);
+/// assert_eq!(quota.remaining(), 16);
+///
+/// // Growing to 64 bytes would take the total to 80.
+/// assert!(v.try_reserve_exact(64).is_err());
+/// assert_eq!(quota.used(), 48);
+///
+/// // Shrinking and freeing give the space back.
+/// v.extend([1, 2, 3, 4]);
+/// v.shrink_to_fit();
+/// drop(b);
+/// assert_eq!(quota.used(), 4);
+/// assert!(Box::try_new_in([0u8; 60], &quota).is_ok());
+/// assert_eq!(quota.used(), 4);
+/// ```
+pub struct QuotaAllocator<A> {
+    inner: A,
+    limit: usize,
+    used: AtomicUsize,
+}
+
+impl<A: Allocator> QuotaAllocator<A> {
+    /// Creates a new `QuotaAllocator<A>` allowing at most `limit` bytes to be
+    /// allocated from `inner` at a time.
+    pub const fn new(inner: A, limit: usize) -> Self {
+        Self {
+            inner,
+            limit,
+            used: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns a reference to the inner allocator.
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// Returns the maximum number of outstanding bytes.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Returns the number of outstanding bytes.
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of bytes that can still be allocated.
+    pub fn remaining(&self) -> usize {
+        self.limit - self.used()
+    }
+
+    /// Adds `size` to the count, unless that would exceed the limit.
+    fn reserve(&self, size: usize) -> Result<(), AllocError> {
+        self.used
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                used.checked_add(size).filter(|&total| total <= self.limit)
+            })
+            .map(drop)
+            .map_err(|_| AllocError)
+    }
+
+    fn release(&self, size: usize) {
+        self.used.fetch_sub(size, Ordering::Relaxed);
+    }
+
+    /// Runs `f` with `size` bytes reserved, releasing them if `f` fails.
+    fn with_reserved(
+        &self,
+        size: usize,
+        f: impl FnOnce() -> Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.reserve(size)?;
+        f().inspect_err(|_| self.release(size))
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for QuotaAllocator<A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.with_reserved(layout.size(), || self.inner.allocate(layout))
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.with_reserved(layout.size(), || self.inner.allocate_zeroed(layout))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.inner.deallocate(ptr, layout);
+        self.release(layout.size());
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.with_reserved(new_layout.size() - old_layout.size(), || {
+            self.inner.grow(ptr, old_layout, new_layout)
+        })
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.with_reserved(new_layout.size() - old_layout.size(), || {
+            self.inner.grow_zeroed(ptr, old_layout, new_layout)
+        })
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let new_ptr = self.inner.shrink(ptr, old_layout, new_layout)?;
+        self.release(old_layout.size() - new_layout.size());
+        Ok(new_ptr)
+    }
+}
@@ -0,0 +1,82 @@
+//! [`Serialize`] support for the owning types in this crate.
+//!
+//! serde implements [`Serialize`] for `Box<T>` and `Vec<T>`, but not for
+//! `Box<T, A>` and `Vec<T, A>` with a custom allocator, so
+//! [`OwningRef`][crate::owning_ref::OwningRef] and
+//! [`OwningSlice`][crate::owning_slice::OwningSlice] are not `Serialize`
+//! themselves. [`serialize_owning`] and [`Owning`] serialize them by value
+//! instead, producing the same output as the equivalent `Box` or `Vec`.
+//! [`StableOwningRef`][crate::stable::StableOwningRef] and
+//! [`StableOwningSlice`][crate::stable::StableOwningSlice] implement
+//! `Serialize` directly.
+//!
+//! `Deserialize` is not provided, since deserializing into an owning type
+//! would need to allocate, which a `NoopAllocator` cannot do. Deserialize
+//! into a value and move it into borrowed storage instead, e.g. with
+//! `owning_ref::from_maybeuninit_write`, or push the elements of a
+//! deserialized sequence into an `OwningSlice`.
+//!
+//! Requires the `serde` feature.
+use core::ops::Deref;
+
+use serde::{Serialize, Serializer};
+
+/// Serializes the value `value` points to, e.g. the `T` of an
+/// [`OwningRef<'_, T>`][crate::owning_ref::OwningRef] or the elements of an
+/// [`OwningSlice<'_, T>`][crate::owning_slice::OwningSlice].
+///
+/// For use with `#[serde(serialize_with = "...")]`:
+#[cfg_attr(feature = "nightly", doc = "```rust")]
+#[cfg_attr(not(feature = "nightly"), doc = "```rust,ignore")]
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::owning_slice::{self, OwningSlice};
+/// #[derive(serde::Serialize)]
+/// struct Frame<'a> {
+///     id: u32,
+///     #[serde(serialize_with = "noop_allocator::ser::serialize_owning")]
+///     samples: OwningSlice<'a, i16>,
+/// }
+///
+/// let mut buf = [MaybeUninit::uninit(); 4];
+/// let mut samples = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+/// samples.extend([1, -2, 3]);
+/// let frame = Frame { id: 7, samples };
+/// assert_eq!(
+///     serde_json::to_string(&frame).unwrap(),
+///     r#"{"id":7,"samples":[1,-2,3]}"#
+/// );
+/// ```
+pub fn serialize_owning<D, S>(value: &D, serializer: S) -> Result<S::Ok, S::Error>
+where
+    D: Deref + ?Sized,
+    D::Target: Serialize,
+    S: Serializer,
+{
+    (**value).serialize(serializer)
+}
+
+/// A wrapper that serializes the value a reference to an owning type points
+/// to, see [`serialize_owning`].
+#[cfg_attr(feature = "nightly", doc = "```rust")]
+#[cfg_attr(not(feature = "nightly"), doc = "```rust,ignore")]
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::{owning_ref, ser::Owning};
+/// let mut slot = MaybeUninit::uninit();
+/// let owned = owning_ref::from_maybeuninit_write(&mut slot, (1, "one"));
+/// assert_eq!(
+///     serde_json::to_string(&Owning(&owned)).unwrap(),
+///     serde_json::to_string(&Box::new((1, "one"))).unwrap(),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Owning<'r, D: ?Sized>(pub &'r D);
+
+impl<D> Serialize for Owning<'_, D>
+where
+    D: Deref + ?Sized,
+    D::Target: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_owning(self.0, serializer)
+    }
+}
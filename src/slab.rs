@@ -0,0 +1,467 @@
+//! A fixed-size block allocator and a typed slab over mutably borrowed
+//! buffers.
+use core::{
+    alloc::{AllocError, Allocator, Layout},
+    cell::Cell,
+    marker::PhantomData,
+    mem::{self, MaybeUninit},
+    ptr::NonNull,
+};
+
+/// A fixed-size block [`Allocator`] over a mutably borrowed byte buffer.
+///
+/// The buffer is divided into blocks of a layout given at construction, which
+/// are kept in an intrusive free list. Any allocation whose layout fits in a
+/// block is served from the free list, and
+/// [`deallocate`][SlabAllocator::deallocate] returns the block to it, so
+/// memory is reused. Allocations that do not fit in a block are rejected.
+///
+/// This suits node-based collections, which allocate many same-sized nodes and
+/// free them individually.
+///
+/// In debug builds, deallocating a block that is already free panics.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::{collections::LinkedList, mem::MaybeUninit};
+/// use noop_allocator::slab::SlabAllocator;
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 256];
+/// let slab = SlabAllocator::new(&mut buf, std::alloc::Layout::new::<[u64; 4]>());
+/// let blocks = slab.block_count();
+/// assert!(blocks >= 4);
+///
+/// // Allocate and free many more boxes than there are blocks.
+/// for i in 0..10 * blocks {
+///     let boxes: Vec<_> = (0..blocks)
+///         .map(|j| Box::new_in([i, j, 0, 0], &slab))
+///         .collect();
+///     assert_eq!(slab.free_blocks(), 0);
+///     assert!(Box::try_new_in(0u8, &slab).is_err());
+///     assert!(boxes.iter().enumerate().all(|(j, b)| b[..2] == [i, j]));
+/// }
+/// assert_eq!(slab.free_blocks(), blocks);
+///
+/// // Layouts larger than a block are rejected.
+/// assert!(Box::try_new_in([0u64; 5], &slab).is_err());
+///
+/// let mut list = LinkedList::new_in(&slab);
+/// for i in 0..100 {
+///     list.push_back(i);
+///     if list.len() > 2 {
+///         list.pop_front();
+///     }
+/// }
+/// assert!(list.iter().eq(&[98, 99]));
+/// ```
+///
+/// Double frees are detected in debug builds:
+///
+/// ```rust,should_panic
+/// #![feature(allocator_api)]
+/// # use std::{alloc::{Allocator, Layout}, mem::MaybeUninit};
+/// use noop_allocator::slab::SlabAllocator;
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 64];
+/// let layout = Layout::new::<u64>();
+/// let slab = SlabAllocator::new(&mut buf, layout);
+/// let ptr = slab.allocate(layout).unwrap().cast::<u8>();
+/// unsafe {
+///     slab.deallocate(ptr, layout);
+///     slab.deallocate(ptr, layout);
+/// }
+/// ```
+pub struct SlabAllocator<'a> {
+    block: Layout,
+    block_count: usize,
+    free_count: Cell<usize>,
+    free_list: Cell<Option<NonNull<u8>>>,
+    _marker: PhantomData<&'a mut [MaybeUninit<u8>]>,
+}
+
+// SAFETY: A `SlabAllocator` is equivalent to the `&mut [MaybeUninit<u8>]` it
+// was created from.
+unsafe impl Send for SlabAllocator<'_> {}
+
+impl<'a> SlabAllocator<'a> {
+    /// Creates a new `SlabAllocator<'a>` allocating blocks of `block` layout
+    /// from `buf`.
+    ///
+    /// Each block is at least large enough to hold a pointer, and blocks are
+    /// padded to a multiple of `block.align()`.
+    pub fn new(buf: &'a mut [MaybeUninit<u8>], block: Layout) -> Self {
+        let block = Layout::from_size_align(
+            block
+                .size()
+                .max(core::mem::size_of::<Option<NonNull<u8>>>()),
+            block.align(),
+        )
+        .map(|layout| layout.pad_to_align())
+        .expect("block layout overflowed");
+        let padding = buf.as_ptr().align_offset(block.align()).min(buf.len());
+        let buf = &mut buf[padding..];
+        let block_count = buf.len() / block.size();
+
+        let start = NonNull::from(buf).cast::<u8>();
+        let mut free_list = None;
+        for i in (0..block_count).rev() {
+            let ptr = unsafe { start.add(i * block.size()) };
+            unsafe { ptr.cast::<Option<NonNull<u8>>>().write_unaligned(free_list) };
+            free_list = Some(ptr);
+        }
+
+        Self {
+            block,
+            block_count,
+            free_count: Cell::new(block_count),
+            free_list: Cell::new(free_list),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the layout of each block, after padding.
+    pub fn block_layout(&self) -> Layout {
+        self.block
+    }
+
+    /// Returns the total number of blocks.
+    pub fn block_count(&self) -> usize {
+        self.block_count
+    }
+
+    /// Returns the number of blocks that are currently free.
+    pub fn free_blocks(&self) -> usize {
+        self.free_count.get()
+    }
+
+    /// Returns whether an allocation of `layout` at `ptr` fits in a block.
+    fn fits(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+        layout.size() <= self.block.size() && ptr.as_ptr() as usize & (layout.align() - 1) == 0
+    }
+
+    /// Returns whether `ptr` is currently in the free list.
+    #[cfg(debug_assertions)]
+    fn is_free(&self, ptr: NonNull<u8>) -> bool {
+        let mut next = self.free_list.get();
+        while let Some(block) = next {
+            if block == ptr {
+                return true;
+            }
+            next = unsafe { block.cast::<Option<NonNull<u8>>>().read_unaligned() };
+        }
+        false
+    }
+}
+
+unsafe impl Allocator for SlabAllocator<'_> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if layout.size() > self.block.size() || layout.align() > self.block.align() {
+            return Err(AllocError);
+        }
+        let ptr = self.free_list.get().ok_or(AllocError)?;
+        let next = unsafe { ptr.cast::<Option<NonNull<u8>>>().read_unaligned() };
+        self.free_list.set(next);
+        self.free_count.set(self.free_count.get() - 1);
+        Ok(NonNull::slice_from_raw_parts(ptr, self.block.size()))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, _layout: Layout) {
+        #[cfg(debug_assertions)]
+        assert!(!self.is_free(ptr), "double free of slab block {ptr:p}");
+        ptr.cast::<Option<NonNull<u8>>>()
+            .write_unaligned(self.free_list.get());
+        self.free_list.set(Some(ptr));
+        self.free_count.set(self.free_count.get() + 1);
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        debug_assert!(
+            new_layout.size() >= old_layout.size(),
+            "`new_layout.size()` must be greater than or equal to `old_layout.size()`"
+        );
+        if !self.fits(ptr, new_layout) {
+            return Err(AllocError);
+        }
+        Ok(NonNull::slice_from_raw_parts(ptr, self.block.size()))
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let new_ptr = self.grow(ptr, old_layout, new_layout)?;
+        ptr.add(old_layout.size())
+            .write_bytes(0, self.block.size() - old_layout.size());
+        Ok(new_ptr)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        debug_assert!(
+            new_layout.size() <= old_layout.size(),
+            "`new_layout.size()` must be smaller than or equal to `old_layout.size()`"
+        );
+        if !self.fits(ptr, new_layout) {
+            return Err(AllocError);
+        }
+        Ok(NonNull::slice_from_raw_parts(ptr, self.block.size()))
+    }
+}
+
+/// A key identifying a value in a [`Slab`].
+///
+/// Keys include a generation, so a key for a removed value does not refer to a
+/// value inserted later in the same entry. (The generation is a `u32`, so a key
+/// could be confused with a new one after 2<sup>32</sup> reuses of an entry.)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    index: usize,
+    generation: u32,
+}
+
+impl Key {
+    /// Returns the index of the entry this key refers to.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+/// An entry in the storage of a [`Slab`].
+///
+/// Its contents are private; it only appears in the type of the storage passed
+/// to [`Slab::new`].
+pub struct Entry<T> {
+    generation: u32,
+    state: State<T>,
+}
+
+enum State<T> {
+    Occupied(T),
+    Vacant { next_free: Option<usize> },
+}
+
+/// A container over a mutably borrowed `[MaybeUninit<Entry<T>>]` that stores
+/// values at stable [`Key`]s.
+///
+/// Removed entries are reused by later insertions, but keys for removed values
+/// are detected as stale instead of referring to the new value. Values still in
+/// the `Slab` are dropped when it is dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::slab::{Entry, Slab};
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// struct Counted(&'static str);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut storage: [MaybeUninit<Entry<Counted>>; 3] = [const { MaybeUninit::uninit() }; 3];
+/// let mut slab = Slab::new(&mut storage);
+///
+/// let a = slab.insert(Counted("a")).ok().unwrap();
+/// let b = slab.insert(Counted("b")).ok().unwrap();
+/// let c = slab.insert(Counted("c")).ok().unwrap();
+/// assert!(slab.insert(Counted("full")).is_err());
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+///
+/// assert_eq!(slab.remove(b).unwrap().0, "b");
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+///
+/// // The entry is reused, but the old key is stale.
+/// let d = slab.insert(Counted("d")).ok().unwrap();
+/// assert_eq!(d.index(), b.index());
+/// assert!(slab.get(b).is_none());
+/// assert!(slab.remove(b).is_none());
+/// assert_eq!(slab.get(d).unwrap().0, "d");
+///
+/// slab.get_mut(a).unwrap().0 = "A";
+/// let live: Vec<_> = slab.iter().map(|(key, value)| (key, value.0)).collect();
+/// assert_eq!(live, [(a, "A"), (d, "d"), (c, "c")]);
+///
+/// drop(slab);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 5);
+/// ```
+pub struct Slab<'a, T> {
+    entries: &'a mut [MaybeUninit<Entry<T>>],
+    /// The number of entries at the start of `entries` that are initialized.
+    initialized: usize,
+    free_head: Option<usize>,
+    len: usize,
+}
+
+impl<'a, T> Slab<'a, T> {
+    /// Creates a new, empty `Slab<'a, T>` storing values in `storage`.
+    pub fn new(storage: &'a mut [MaybeUninit<Entry<T>>]) -> Self {
+        Self {
+            entries: storage,
+            initialized: 0,
+            free_head: None,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of values in the slab.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the slab contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of values the slab can hold.
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn initialized(&self) -> &[Entry<T>] {
+        unsafe { self.entries[..self.initialized].assume_init_ref() }
+    }
+
+    fn initialized_mut(&mut self) -> &mut [Entry<T>] {
+        unsafe { self.entries[..self.initialized].assume_init_mut() }
+    }
+
+    /// Inserts `value` into the slab, returning its key, or returns `value`
+    /// back if the slab is full.
+    pub fn insert(&mut self, value: T) -> Result<Key, T> {
+        let key = if let Some(index) = self.free_head {
+            let entry = &mut self.initialized_mut()[index];
+            let State::Vacant { next_free } = entry.state else {
+                unreachable!("free list entry is occupied")
+            };
+            entry.state = State::Occupied(value);
+            let generation = entry.generation;
+            self.free_head = next_free;
+            Key { index, generation }
+        } else if self.initialized < self.entries.len() {
+            let index = self.initialized;
+            self.entries[index].write(Entry {
+                generation: 0,
+                state: State::Occupied(value),
+            });
+            self.initialized += 1;
+            Key {
+                index,
+                generation: 0,
+            }
+        } else {
+            return Err(value);
+        };
+        self.len += 1;
+        Ok(key)
+    }
+
+    /// Removes and returns the value for `key`, or returns `None` if `key` is
+    /// stale.
+    pub fn remove(&mut self, key: Key) -> Option<T> {
+        let free_head = self.free_head;
+        let entry = self
+            .initialized_mut()
+            .get_mut(key.index)
+            .filter(|entry| entry.generation == key.generation)?;
+        if let State::Vacant { .. } = entry.state {
+            return None;
+        }
+        let State::Occupied(value) = mem::replace(
+            &mut entry.state,
+            State::Vacant {
+                next_free: free_head,
+            },
+        ) else {
+            unreachable!()
+        };
+        entry.generation = entry.generation.wrapping_add(1);
+        self.free_head = Some(key.index);
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Returns whether `key` refers to a value in the slab.
+    pub fn contains(&self, key: Key) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns a reference to the value for `key`, or `None` if `key` is stale.
+    pub fn get(&self, key: Key) -> Option<&T> {
+        match self.initialized().get(key.index)? {
+            Entry {
+                generation,
+                state: State::Occupied(value),
+            } if *generation == key.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the value for `key`, or `None` if `key`
+    /// is stale.
+    pub fn get_mut(&mut self, key: Key) -> Option<&mut T> {
+        match self.initialized_mut().get_mut(key.index)? {
+            Entry {
+                generation,
+                state: State::Occupied(value),
+            } if *generation == key.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns an iterator over the keys and values in the slab, in order of
+    /// their entries.
+    pub fn iter(&self) -> impl Iterator<Item = (Key, &T)> + '_ {
+        self.initialized()
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| match &entry.state {
+                State::Occupied(value) => Some((
+                    Key {
+                        index,
+                        generation: entry.generation,
+                    },
+                    value,
+                )),
+                State::Vacant { .. } => None,
+            })
+    }
+
+    /// Returns an iterator over the keys and mutable references to the values
+    /// in the slab, in order of their entries.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Key, &mut T)> + '_ {
+        self.initialized_mut()
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, entry)| match &mut entry.state {
+                State::Occupied(value) => Some((
+                    Key {
+                        index,
+                        generation: entry.generation,
+                    },
+                    value,
+                )),
+                State::Vacant { .. } => None,
+            })
+    }
+}
+
+impl<T> Drop for Slab<'_, T> {
+    fn drop(&mut self) {
+        unsafe { self.entries[..self.initialized].assume_init_drop() }
+    }
+}
@@ -0,0 +1,400 @@
+//! Storage that tracks whether it holds a value, for producing
+//! [`OwningRef`]s without `unsafe`.
+
+use core::{
+    fmt,
+    mem::MaybeUninit,
+    ops::{Deref, DerefMut},
+};
+
+use crate::owning_ref::{self, OwningRef};
+
+/// A `MaybeUninit<T>` plus a flag recording whether it holds a value.
+///
+/// Unlike a bare `MaybeUninit<T>`, a `Slot<T>` knows whether it is occupied,
+/// so every operation on it is safe: [`insert`][Self::insert] drops the
+/// previous occupant before writing, [`take`][Self::take] and
+/// [`get`][Self::get] return `None` for an empty slot, and dropping the slot
+/// drops its occupant.
+///
+/// An [`OwningRef`] from [`insert`][Self::insert] or
+/// [`take_owning`][Self::take_owning] owns the value, so the slot is empty
+/// while the `OwningRef` exists and after it is dropped. Forgetting the
+/// `OwningRef` leaks the value, as it would for any `Box`.
+///
+/// # Examples
+///
+/// ```rust
+/// use noop_allocator::slot::Slot;
+/// let mut slot = Slot::new();
+/// assert!(slot.take().is_none());
+///
+/// assert_eq!(slot.replace(String::from("a")), None);
+/// slot.get_mut().unwrap().push('!');
+/// assert_eq!(slot.get().map(String::as_str), Some("a!"));
+///
+/// // Moves the value into an `OwningRef`; the slot no longer owns it.
+/// let owned = slot.take_owning().unwrap();
+/// assert_eq!(*owned, "a!");
+/// drop(owned);
+/// assert!(slot.get().is_none());
+///
+/// // `insert` drops the occupant, if any, before writing.
+/// slot.replace(String::from("b"));
+/// let owned = slot.insert(String::from("c"));
+/// assert_eq!(*owned, "c");
+/// ```
+///
+/// A `Slot` can be created in a `const` context, and costs only its flag:
+///
+/// ```rust
+/// use noop_allocator::slot::Slot;
+/// const EMPTY: Slot<u64> = Slot::new();
+/// assert!(!EMPTY.is_occupied());
+/// assert!(size_of::<Slot<u64>>() <= size_of::<Option<u64>>());
+/// ```
+pub struct Slot<T> {
+    occupied: bool,
+    value: MaybeUninit<T>,
+}
+
+impl<T> Slot<T> {
+    /// Creates an empty `Slot<T>`.
+    pub const fn new() -> Self {
+        Self {
+            occupied: false,
+            value: MaybeUninit::uninit(),
+        }
+    }
+
+    /// Creates a `Slot<T>` holding `value`.
+    pub const fn filled(value: T) -> Self {
+        Self {
+            occupied: true,
+            value: MaybeUninit::new(value),
+        }
+    }
+
+    /// Returns `true` if the slot holds a value.
+    pub const fn is_occupied(&self) -> bool {
+        self.occupied
+    }
+
+    /// Drops the occupant, if any, and moves `value` into an `OwningRef`
+    /// borrowing this slot.
+    ///
+    /// The slot is empty afterwards, since the `OwningRef` owns the value.
+    pub fn insert(&mut self, value: T) -> OwningRef<'_, T> {
+        self.clear();
+        owning_ref::from_maybeuninit_write(&mut self.value, value)
+    }
+
+    /// Stores `value` in the slot, returning the previous occupant, if any.
+    pub fn replace(&mut self, value: T) -> Option<T> {
+        let old = self.take();
+        self.value.write(value);
+        self.occupied = true;
+        old
+    }
+
+    /// Moves the occupant, if any, out of the slot.
+    pub fn take(&mut self) -> Option<T> {
+        if !self.occupied {
+            return None;
+        }
+        self.occupied = false;
+        // SAFETY: The slot was occupied, and is now marked as empty.
+        Some(unsafe { self.value.assume_init_read() })
+    }
+
+    /// Moves the occupant, if any, into an `OwningRef` borrowing this slot.
+    pub fn take_owning(&mut self) -> Option<OwningRef<'_, T>> {
+        if !self.occupied {
+            return None;
+        }
+        self.occupied = false;
+        // SAFETY: The slot was occupied, and is now marked as empty, so the
+        // `OwningRef` is the only owner of the value.
+        Some(unsafe { owning_ref::from_maybeuninit(&mut self.value) })
+    }
+
+    /// Returns a reference to the occupant, if any.
+    pub fn get(&self) -> Option<&T> {
+        // SAFETY: The slot is occupied.
+        self.occupied
+            .then(|| unsafe { self.value.assume_init_ref() })
+    }
+
+    /// Returns a mutable reference to the occupant, if any.
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: The slot is occupied.
+        self.occupied
+            .then(|| unsafe { self.value.assume_init_mut() })
+    }
+
+    /// Drops the occupant, if any.
+    pub fn clear(&mut self) {
+        if self.occupied {
+            // Mark the slot as empty first, so that a panicking destructor
+            // does not cause a double drop.
+            self.occupied = false;
+            // SAFETY: The slot was occupied.
+            unsafe { self.value.assume_init_drop() };
+        }
+    }
+}
+
+impl<T> Default for Slot<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Slot<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Slot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Slot").field(&self.get()).finish()
+    }
+}
+
+/// A slot that is filled, used, and emptied again, once per iteration.
+///
+/// A `ReusableSlot<T>` has no accessors of its own: the only way to reach the
+/// storage is [`fill`][Self::fill], which returns a [`FilledSlot`] guard
+/// owning the value and mutably borrowing the slot. Consuming the guard, by
+/// dropping it or with [`FilledSlot::take`], empties the slot. So a slot cannot
+/// be filled twice, or read after its value was consumed, since both would need
+/// a second borrow of it while the guard is alive.
+///
+/// # Examples
+///
+/// ```rust
+/// use noop_allocator::slot::ReusableSlot;
+/// let mut slot = ReusableSlot::new();
+/// let mut total = 0;
+/// for i in 0..4 {
+///     let mut filled = slot.fill(vec![i; 3]);
+///     filled.push(i);
+///     total += filled.iter().sum::<i32>();
+///     // `filled` is dropped at the end of each iteration, emptying the slot.
+/// }
+/// assert_eq!(total, 24);
+/// ```
+///
+/// Filling a slot that is already filled, moving a slot while it is filled,
+/// and keeping a reference to a value after its slot was refilled all fail to
+/// compile; see `tests/ui/reusable_slot_*.rs`.
+pub struct ReusableSlot<T> {
+    value: MaybeUninit<T>,
+}
+
+impl<T> ReusableSlot<T> {
+    /// Creates an empty `ReusableSlot<T>`.
+    pub const fn new() -> Self {
+        Self {
+            value: MaybeUninit::uninit(),
+        }
+    }
+
+    /// Fills the slot with `value`, returning a guard that owns it.
+    pub fn fill(&mut self, value: T) -> FilledSlot<'_, T> {
+        FilledSlot(owning_ref::from_maybeuninit_write(&mut self.value, value))
+    }
+}
+
+impl<T> Default for ReusableSlot<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for ReusableSlot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReusableSlot").finish_non_exhaustive()
+    }
+}
+
+/// A filled [`ReusableSlot`], owning the value in it.
+///
+/// Dereferences to the value. Dropping the guard drops the value and empties
+/// the slot; [`take`][Self::take] moves the value out instead. Like
+/// [`Box`][alloc::boxed::Box]'s, its methods are associated functions, so they
+/// do not shadow methods of `T`.
+pub struct FilledSlot<'a, T>(OwningRef<'a, T>);
+
+impl<'a, T> FilledSlot<'a, T> {
+    /// Moves the value out, emptying the slot.
+    pub fn take(this: Self) -> T {
+        *this.0
+    }
+
+    /// Converts the guard into an `OwningRef` owning the value.
+    ///
+    /// The slot stays borrowed until the `OwningRef` is dropped.
+    pub fn into_owning_ref(this: Self) -> OwningRef<'a, T> {
+        this.0
+    }
+
+    /// Leaks the value, returning a reference to it that keeps the slot
+    /// borrowed.
+    pub fn leak(this: Self) -> &'a mut T {
+        OwningRef::leak(this.0)
+    }
+}
+
+impl<T> Deref for FilledSlot<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for FilledSlot<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for FilledSlot<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FilledSlot").field(&**self).finish()
+    }
+}
+
+/// A `static` slot that can be filled once, producing an
+/// [`OwningRef<'static, T>`][OwningRef].
+///
+/// A `&'static mut` to a `static` cannot be created without `unsafe`, since
+/// nothing stops two places from doing it. A `StaticSlot` guards its storage
+/// with an atomic flag instead, like the `static_cell` crate's `StaticCell`:
+/// the first call to [`init`][Self::init] moves the value in and returns an
+/// `OwningRef` owning it, and every later call drops its value and returns
+/// `None`, even after the first `OwningRef` was dropped. Use the
+/// [`static_slot!`][crate::static_slot] macro to declare the `static` and
+/// fill it in one step, panicking instead if it is reached twice.
+///
+/// This type is only available on targets with native atomics, or with the
+/// `portable-atomic` feature enabled.
+///
+/// # Examples
+///
+/// ```rust
+/// use noop_allocator::{owning_ref::OwningRef, slot::StaticSlot};
+/// struct Driver {
+///     irq: u8,
+/// }
+/// static DRIVER: StaticSlot<Driver> = StaticSlot::new();
+///
+/// let driver: OwningRef<'static, Driver> = DRIVER.init(Driver { irq: 5 }).unwrap();
+/// assert!(DRIVER.init(Driver { irq: 6 }).is_none());
+/// std::thread::spawn(move || assert_eq!(driver.irq, 5))
+///     .join()
+///     .unwrap();
+/// ```
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+pub struct StaticSlot<T> {
+    value: core::cell::UnsafeCell<MaybeUninit<T>>,
+    claimed: crate::sync::AtomicBool,
+}
+
+// SAFETY: The value is only accessed through the single `OwningRef` handed out
+// by `init`, which may be sent to another thread.
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+unsafe impl<T: Send> Sync for StaticSlot<T> {}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<T> StaticSlot<T> {
+    /// Creates a new, empty `StaticSlot`.
+    pub const fn new() -> Self {
+        Self {
+            value: core::cell::UnsafeCell::new(MaybeUninit::uninit()),
+            claimed: crate::sync::AtomicBool::new(false),
+        }
+    }
+
+    /// Moves `value` into the slot and returns an `OwningRef` owning it the
+    /// first time it is called, and drops `value` and returns `None`
+    /// afterwards.
+    pub fn init(&'static self, value: T) -> Option<OwningRef<'static, T>> {
+        if self.claimed.swap(true, crate::sync::Ordering::Acquire) {
+            return None;
+        }
+        // SAFETY: The flag was unset, so this is the only reference to the
+        // storage there will ever be.
+        let slot = unsafe { &mut *self.value.get() };
+        Some(owning_ref::from_maybeuninit_write(slot, value))
+    }
+
+    /// Returns `true` if [`init`][Self::init] has succeeded.
+    pub fn is_claimed(&self) -> bool {
+        self.claimed.load(crate::sync::Ordering::Relaxed)
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<T> Default for StaticSlot<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+impl<T> fmt::Debug for StaticSlot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticSlot")
+            .field("claimed", &self.is_claimed())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Moves a value into a new `static` [`StaticSlot`][slot::StaticSlot],
+/// returning an [`OwningRef<'static, T>`][owning_ref::OwningRef] owning it.
+///
+/// `static_slot!(T, value)` declares a hidden `static` of type `StaticSlot<T>`
+/// and calls [`init`][slot::StaticSlot::init] on it. Each expansion has its own
+/// `static`, so it can only succeed once: if the same expansion is reached
+/// again (e.g. in a loop, or a function called twice), it panics, where
+/// `StaticSlot::init` would return `None`. Since the `static` is an item, `T`
+/// cannot mention generic parameters of the enclosing function.
+///
+/// This macro is only available on targets with native atomics, or with the
+/// `portable-atomic` feature enabled.
+///
+/// [slot::StaticSlot]: crate::slot::StaticSlot
+/// [slot::StaticSlot::init]: crate::slot::StaticSlot::init
+/// [owning_ref::OwningRef]: crate::owning_ref::OwningRef
+///
+/// # Examples
+///
+/// ```rust
+/// use noop_allocator::{owning_ref::OwningRef, static_slot};
+/// let buf: OwningRef<'static, [u8; 64]> = static_slot!([u8; 64], [0; 64]);
+/// assert_eq!(buf.len(), 64);
+/// ```
+///
+/// ```rust,should_panic
+/// use noop_allocator::static_slot;
+/// for i in 0..2 {
+///     // Panics on the second iteration.
+///     let _ = static_slot!(u32, i);
+/// }
+/// ```
+#[cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+#[macro_export]
+macro_rules! static_slot {
+    ($ty:ty, $value:expr $(,)?) => {{
+        static SLOT: $crate::slot::StaticSlot<$ty> = $crate::slot::StaticSlot::new();
+        match SLOT.init($value) {
+            ::core::option::Option::Some(owned) => owned,
+            ::core::option::Option::None => {
+                ::core::panic!("`static_slot!` was reached more than once")
+            }
+        }
+    }};
+}
@@ -0,0 +1,176 @@
+//! An allocator that uses a mutably borrowed byte buffer first, and falls back
+//! to another allocator.
+use core::{
+    alloc::{AllocError, Allocator, Layout},
+    mem::MaybeUninit,
+    ptr::NonNull,
+};
+
+use crate::stack::StackAllocator;
+use alloc::alloc::Global;
+
+/// An [`Allocator`] that allocates from a mutably borrowed byte buffer while
+/// allocations fit in it, and from another allocator (by default, [`Global`])
+/// otherwise.
+///
+/// The buffer is managed like a [`StackAllocator`], so a single growing
+/// collection (e.g. a `Vec`) grows in place in the buffer until it no longer
+/// fits, at which point it is moved to the fallback allocator. Allocations are
+/// not moved back into the buffer when they shrink.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::spill::SpillAllocator;
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 64];
+/// let range = buf.as_ptr_range();
+/// let in_buf = |ptr: *const u32| range.contains(&ptr.cast());
+/// let spill = SpillAllocator::new(&mut buf);
+///
+/// let mut v = Vec::new_in(&spill);
+/// v.extend(0..8u32);
+/// assert!(in_buf(v.as_ptr()));
+///
+/// // Growing past the buffer moves the `Vec` to the heap.
+/// v.extend(8..100);
+/// assert!(!in_buf(v.as_ptr()));
+/// assert!(v.iter().copied().eq(0..100));
+///
+/// // The buffer is free again, so small allocations go there.
+/// let small = Box::new_in(1u32, &spill);
+/// let big = Box::new_in([2u32; 32], &spill);
+/// assert!(in_buf(&*small));
+/// assert!(!in_buf(big.as_ptr()));
+/// drop(small);
+/// drop(big);
+/// ```
+pub struct SpillAllocator<'a, A: Allocator = Global> {
+    buffer: StackAllocator<'a>,
+    fallback: A,
+}
+
+impl<'a> SpillAllocator<'a> {
+    /// Creates a new `SpillAllocator<'a>` allocating from `buf`, then from
+    /// [`Global`].
+    pub fn new(buf: &'a mut [MaybeUninit<u8>]) -> Self {
+        Self::new_in(buf, Global)
+    }
+}
+
+impl<'a, A: Allocator> SpillAllocator<'a, A> {
+    /// Creates a new `SpillAllocator<'a, A>` allocating from `buf`, then from
+    /// `fallback`.
+    pub fn new_in(buf: &'a mut [MaybeUninit<u8>], fallback: A) -> Self {
+        Self {
+            buffer: StackAllocator::new(buf),
+            fallback,
+        }
+    }
+
+    /// Returns a reference to the fallback allocator.
+    pub fn fallback(&self) -> &A {
+        &self.fallback
+    }
+
+    /// Returns the number of bytes in the buffer that have not been handed out
+    /// yet.
+    pub fn buffer_remaining(&self) -> usize {
+        self.buffer.remaining()
+    }
+
+    /// Returns whether the allocation at `ptr` with `layout` is in the buffer.
+    /// Zero-sized allocations always come from the buffer, which hands them
+    /// out without using any of its space.
+    fn in_buffer(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+        layout.size() == 0 || self.buffer.contains(ptr)
+    }
+
+    /// Moves an allocation from the buffer to the fallback allocator.
+    unsafe fn spill(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        zeroed: bool,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let new_ptr = if zeroed {
+            self.fallback.allocate_zeroed(new_layout)?
+        } else {
+            self.fallback.allocate(new_layout)?
+        };
+        let count = old_layout.size().min(new_layout.size());
+        new_ptr.cast::<u8>().copy_from_nonoverlapping(ptr, count);
+        self.buffer.deallocate(ptr, old_layout);
+        Ok(new_ptr)
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for SpillAllocator<'_, A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.buffer
+            .allocate(layout)
+            .or_else(|AllocError| self.fallback.allocate(layout))
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.buffer
+            .allocate_zeroed(layout)
+            .or_else(|AllocError| self.fallback.allocate_zeroed(layout))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if self.in_buffer(ptr, layout) {
+            self.buffer.deallocate(ptr, layout)
+        } else {
+            self.fallback.deallocate(ptr, layout)
+        }
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        if !self.in_buffer(ptr, old_layout) {
+            return self.fallback.grow(ptr, old_layout, new_layout);
+        }
+        self.buffer
+            .grow(ptr, old_layout, new_layout)
+            .or_else(|AllocError| self.spill(ptr, old_layout, new_layout, false))
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        if !self.in_buffer(ptr, old_layout) {
+            return self.fallback.grow_zeroed(ptr, old_layout, new_layout);
+        }
+        self.buffer
+            .grow_zeroed(ptr, old_layout, new_layout)
+            .or_else(|AllocError| self.spill(ptr, old_layout, new_layout, true))
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        if !self.in_buffer(ptr, old_layout) {
+            if new_layout.size() == 0 {
+                self.fallback.deallocate(ptr, old_layout);
+                return Ok(NonNull::slice_from_raw_parts(new_layout.dangling_ptr(), 0));
+            }
+            return self.fallback.shrink(ptr, old_layout, new_layout);
+        }
+        self.buffer
+            .shrink(ptr, old_layout, new_layout)
+            .or_else(|AllocError| self.spill(ptr, old_layout, new_layout, false))
+    }
+}
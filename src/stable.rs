@@ -0,0 +1,335 @@
+//! Owning references and slices over borrowed storage that work on a stable
+//! toolchain.
+//!
+//! [`OwningRef`][crate::owning_ref::OwningRef] and
+//! [`OwningSlice`][crate::owning_slice::OwningSlice] are `Box` and `Vec` with a
+//! [`NoopAllocator`][crate::NoopAllocator], which needs the unstable
+//! `allocator_api` feature. [`StableOwningRef`] and [`StableOwningSlice`] have
+//! the same constructors, but are implemented directly with raw pointers, so
+//! they only provide their own (smaller) set of methods rather than all of
+//! `Box`'s and `Vec`'s.
+//!
+//! Without the default `nightly` feature, this module is available along with
+//! [`buffer`][crate::buffer], [`global`][crate::global] and
+//! [`owning_ring`][crate::owning_ring], and the crate builds on a stable
+//! toolchain.
+use core::{
+    fmt,
+    marker::PhantomData,
+    mem::{ManuallyDrop, MaybeUninit},
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
+};
+
+/// An owning reference borrowing a memory location but owning the value in it,
+/// like [`OwningRef`][crate::owning_ref::OwningRef].
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// use noop_allocator::stable::StableOwningRef;
+/// static DROPS: AtomicUsize = AtomicUsize::new(0);
+/// struct Counted(u32);
+/// impl Drop for Counted {
+///     fn drop(&mut self) {
+///         DROPS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut slot = MaybeUninit::uninit();
+/// let mut value = StableOwningRef::from_maybeuninit_write(&mut slot, Counted(1));
+/// value.0 += 1;
+/// assert_eq!(value.0, 2);
+/// drop(value);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+///
+/// let mut buf = [const { MaybeUninit::new(Counted(3)) }; 3];
+/// let slice = unsafe { StableOwningRef::from_maybeuninit_slice(&mut buf) };
+/// assert_eq!(slice.len(), 3);
+/// let moved = StableOwningRef::into_inner(StableOwningRef::from_maybeuninit_write(
+///     &mut slot,
+///     Counted(4),
+/// ));
+/// drop(slice);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 4);
+/// drop(moved);
+/// assert_eq!(DROPS.load(Ordering::Relaxed), 5);
+/// ```
+pub struct StableOwningRef<'a, T: ?Sized> {
+    ptr: NonNull<T>,
+    _borrow: PhantomData<&'a ()>,
+    _owned: PhantomData<T>,
+}
+
+// SAFETY: A `StableOwningRef<'_, T>` owns its `T` like a `Box<T>`.
+unsafe impl<T: ?Sized + Send> Send for StableOwningRef<'_, T> {}
+unsafe impl<T: ?Sized + Sync> Sync for StableOwningRef<'_, T> {}
+
+impl<'a, T: ?Sized> StableOwningRef<'a, T> {
+    /// Create a `StableOwningRef<'a, T>` from a `&'a mut ManuallyDrop<T>>`.
+    ///
+    /// # Safety
+    ///
+    /// Dropping or moving out of the `StableOwningRef` leaves the borrowed
+    /// `ManuallyDrop` semantically without a value; see [`ManuallyDrop::drop`]
+    /// and [`ManuallyDrop::take`].
+    pub unsafe fn from_manuallydrop(slot: &'a mut ManuallyDrop<T>) -> Self {
+        Self {
+            ptr: NonNull::from(&mut **slot),
+            _borrow: PhantomData,
+            _owned: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> StableOwningRef<'a, T> {
+    /// Create a `StableOwningRef<'a, T>` from a `&'a mut MaybeUninit<T>>`.
+    ///
+    /// # Safety
+    ///
+    /// The `T` must be initialized, see [`MaybeUninit::assume_init_mut`] and
+    /// [`MaybeUninit::assume_init_drop`].
+    pub unsafe fn from_maybeuninit(slot: &'a mut MaybeUninit<T>) -> Self {
+        Self {
+            ptr: NonNull::from(slot).cast(),
+            _borrow: PhantomData,
+            _owned: PhantomData,
+        }
+    }
+
+    /// Create a `StableOwningRef<'a, T>` from a `&'a mut MaybeUninit<T>>` by
+    /// writing a value into it.
+    pub fn from_maybeuninit_write(slot: &'a mut MaybeUninit<T>, value: T) -> Self {
+        slot.write(value);
+        unsafe { Self::from_maybeuninit(slot) }
+    }
+
+    /// Moves the value out of the `StableOwningRef`, leaving the borrowed slot
+    /// semantically without a value.
+    pub fn into_inner(this: Self) -> T {
+        let this = ManuallyDrop::new(this);
+        unsafe { this.ptr.read() }
+    }
+}
+
+impl<'a, T> StableOwningRef<'a, [T]> {
+    /// Create a `StableOwningRef<'a, [T]>` from a `&'a mut [MaybeUninit<T>]>`.
+    ///
+    /// # Safety
+    ///
+    /// All slice elements must be initialized, see
+    /// [`MaybeUninit::assume_init_mut`] and [`MaybeUninit::assume_init_drop`].
+    pub unsafe fn from_maybeuninit_slice(slot: &'a mut [MaybeUninit<T>]) -> Self {
+        Self {
+            ptr: NonNull::new_unchecked(slot as *mut [MaybeUninit<T>] as *mut [T]),
+            _borrow: PhantomData,
+            _owned: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for StableOwningRef<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for StableOwningRef<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for StableOwningRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: ?Sized + serde::Serialize> serde::Serialize for StableOwningRef<'_, T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (**self).serialize(serializer)
+    }
+}
+
+impl<T: ?Sized> Drop for StableOwningRef<'_, T> {
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(self.ptr.as_ptr()) }
+    }
+}
+
+/// An owning slice borrowing a memory location but owning the values in it,
+/// like [`OwningSlice`][crate::owning_slice::OwningSlice].
+///
+/// Its capacity is fixed to the length of the borrowed slice, so
+/// [`push`][Self::push] returns the value back instead of growing.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::stable::StableOwningSlice;
+/// let mut buf: [MaybeUninit<String>; 2] = [const { MaybeUninit::uninit() }; 2];
+/// let mut vec = StableOwningSlice::empty_from_maybeuninit_slice(&mut buf);
+/// assert!(vec.is_empty());
+/// assert_eq!(vec.capacity(), 2);
+/// vec.push("Hello".to_string()).unwrap();
+/// vec.push("world".to_string()).unwrap();
+/// assert_eq!(vec.push("!".to_string()), Err("!".to_string()));
+/// assert_eq!(*vec, ["Hello", "world"]);
+/// assert_eq!(vec.pop().as_deref(), Some("world"));
+/// vec.truncate(0);
+/// assert!(vec.is_empty());
+/// ```
+pub struct StableOwningSlice<'a, T> {
+    ptr: NonNull<T>,
+    len: usize,
+    capacity: usize,
+    _borrow: PhantomData<&'a ()>,
+    _owned: PhantomData<T>,
+}
+
+// SAFETY: A `StableOwningSlice<'_, T>` owns its elements like a `Vec<T>`.
+unsafe impl<T: Send> Send for StableOwningSlice<'_, T> {}
+unsafe impl<T: Sync> Sync for StableOwningSlice<'_, T> {}
+
+impl<'a, T> StableOwningSlice<'a, T> {
+    /// Create a `StableOwningSlice<'a, T>` with a length and capacity of 1 from
+    /// a `&'a mut MaybeUninit<T>>`.
+    ///
+    /// # Safety
+    ///
+    /// The `T` must be initialized, see [`MaybeUninit::assume_init_mut`] and
+    /// [`MaybeUninit::assume_init_drop`].
+    pub unsafe fn from_maybeuninit(slot: &'a mut MaybeUninit<T>) -> Self {
+        Self::from_maybeuninit_slice(core::slice::from_mut(slot), 1)
+    }
+
+    /// Create a `StableOwningSlice<'a, T>` with a given length from a `&'a mut
+    /// [MaybeUninit<T>]>`. The capacity is the length of the given slice.
+    ///
+    /// # Safety
+    ///
+    /// All slice elements in `[0..length]` must be initialized, see
+    /// [`MaybeUninit::assume_init_mut`] and [`MaybeUninit::assume_init_drop`].
+    pub unsafe fn from_maybeuninit_slice(slot: &'a mut [MaybeUninit<T>], length: usize) -> Self {
+        debug_assert!(length <= slot.len());
+        Self {
+            capacity: slot.len(),
+            ptr: NonNull::new_unchecked(slot.as_mut_ptr().cast()),
+            len: length,
+            _borrow: PhantomData,
+            _owned: PhantomData,
+        }
+    }
+
+    /// Create a `StableOwningSlice<'a, T>` with a length of 0 and a capacity
+    /// of 1 from a `&'a mut MaybeUninit<T>>`.
+    pub fn empty_from_maybeuninit(slot: &'a mut MaybeUninit<T>) -> Self {
+        unsafe { Self::from_maybeuninit_slice(core::slice::from_mut(slot), 0) }
+    }
+
+    /// Create a `StableOwningSlice<'a, T>` with a length of 0 from a `&'a mut
+    /// [MaybeUninit<T>]>`. The capacity is the length of the given slice.
+    pub fn empty_from_maybeuninit_slice(slot: &'a mut [MaybeUninit<T>]) -> Self {
+        unsafe { Self::from_maybeuninit_slice(slot, 0) }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the length of the borrowed slice.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Appends `value`, or returns it back if the slice is full.
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        if self.len == self.capacity {
+            return Err(value);
+        }
+        unsafe { self.ptr.add(self.len).write(value) };
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes and returns the last element, or `None` if it is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(unsafe { self.ptr.add(self.len).read() })
+    }
+
+    /// Drops the elements after the first `len`, if any.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        let tail =
+            ptr::slice_from_raw_parts_mut(unsafe { self.ptr.add(len).as_ptr() }, self.len - len);
+        self.len = len;
+        unsafe { ptr::drop_in_place(tail) };
+    }
+
+    /// Drops all elements.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Returns the elements as a slice.
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { core::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// Returns the elements as a mutable slice.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { core::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> Deref for StableOwningSlice<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> DerefMut for StableOwningSlice<'_, T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for StableOwningSlice<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for StableOwningSlice<'_, T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_slice().serialize(serializer)
+    }
+}
+
+impl<T> Drop for StableOwningSlice<'_, T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
@@ -0,0 +1,336 @@
+//! A LIFO allocator over a mutably borrowed byte buffer.
+use core::{
+    alloc::{AllocError, Allocator, Layout},
+    cell::Cell,
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ptr::NonNull,
+};
+
+use crate::buffer::fit;
+
+/// The size of the header stored before each allocation, holding the offset
+/// to rewind to when that allocation is deallocated.
+const HEADER: usize = core::mem::size_of::<usize>();
+
+/// A LIFO [`Allocator`] over a mutably borrowed byte buffer.
+///
+/// Like a [`BumpAllocator`][crate::bump::BumpAllocator], each allocation is
+/// placed after the previous one, but the most recent (top) allocation can
+/// also be deallocated, reclaiming its space, and grown or shrunk in place.
+/// Deallocating any other allocation is a no-op.
+///
+/// This makes e.g. a single growing `Vec` work well: as long as it is the top
+/// allocation, every reallocation happens in place.
+///
+/// Each allocation uses `size_of::<usize>()` extra bytes (before the allocation
+/// itself) to record where the previous allocation ended, so that deallocating
+/// several allocations in reverse order reclaims all of their space.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::stack::StackAllocator;
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 1024];
+/// let stack = StackAllocator::new(&mut buf);
+///
+/// let a = Box::new_in(1u8, &stack);
+///
+/// // The `Vec` is the top allocation, so it grows in place.
+/// let mut v = Vec::with_capacity_in(1, &stack);
+/// v.push(0u32);
+/// let ptr = v.as_ptr();
+/// for i in 1..32 {
+///     v.push(i);
+/// }
+/// assert_eq!(v.as_ptr(), ptr);
+///
+/// // Boxes allocated and freed on top of it don't use up space.
+/// for i in 0..100 {
+///     let b = Box::new_in(i, &stack);
+///     assert_eq!(*b, i);
+/// }
+/// v.push(32);
+/// assert_eq!(v.as_ptr(), ptr);
+///
+/// // Once something else is on top, the `Vec` moves when it grows, and its
+/// // old block is not reclaimed.
+/// let used = stack.used();
+/// let b = Box::new_in(2u8, &stack);
+/// v.extend(33..100);
+/// assert_ne!(v.as_ptr(), ptr);
+/// assert!(v.iter().copied().eq(0..100));
+///
+/// // Freeing in reverse order reclaims the space used since then.
+/// drop(v);
+/// drop(b);
+/// assert_eq!(stack.used(), used);
+/// assert_eq!(*a, 1);
+/// ```
+///
+/// Growing the top allocation with
+/// [`grow_zeroed`][Allocator::grow_zeroed] zeroes the newly exposed bytes,
+/// even if they were used by an earlier, deallocated allocation:
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::{alloc::{Allocator, Layout}, mem::MaybeUninit};
+/// use noop_allocator::stack::StackAllocator;
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 64];
+/// let stack = StackAllocator::new(&mut buf);
+///
+/// let small = Layout::new::<[u8; 4]>();
+/// let big = Layout::new::<[u8; 16]>();
+/// let ptr = stack.allocate(big).unwrap().cast::<u8>();
+/// unsafe {
+///     ptr.write_bytes(0xFF, 16);
+///     stack.deallocate(ptr, big);
+/// }
+/// assert_eq!(stack.used(), 0);
+///
+/// let ptr2 = stack.allocate(small).unwrap().cast::<u8>();
+/// assert_eq!(ptr2, ptr);
+/// let grown = unsafe { stack.grow_zeroed(ptr2, small, big) }.unwrap();
+/// assert_eq!(grown.cast::<u8>(), ptr);
+/// let bytes = unsafe { grown.as_ref() };
+/// assert_eq!(bytes[..4], [0xFF; 4]);
+/// assert_eq!(bytes[4..], [0; 12]);
+/// ```
+pub struct StackAllocator<'a> {
+    start: NonNull<u8>,
+    capacity: usize,
+    offset: Cell<usize>,
+    #[cfg(feature = "stats")]
+    peak: Cell<usize>,
+    #[cfg(feature = "stats")]
+    served: Cell<usize>,
+    _marker: PhantomData<&'a mut [MaybeUninit<u8>]>,
+}
+
+// SAFETY: A `StackAllocator` is equivalent to the `&mut [MaybeUninit<u8>]` it
+// was created from.
+unsafe impl Send for StackAllocator<'_> {}
+
+impl<'a> StackAllocator<'a> {
+    /// Creates a new `StackAllocator<'a>` allocating from `buf`.
+    pub fn new(buf: &'a mut [MaybeUninit<u8>]) -> Self {
+        Self {
+            capacity: buf.len(),
+            start: NonNull::from(buf).cast(),
+            offset: Cell::new(0),
+            #[cfg(feature = "stats")]
+            peak: Cell::new(0),
+            #[cfg(feature = "stats")]
+            served: Cell::new(0),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the total size of the borrowed buffer in bytes.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of bytes that are currently in use, including
+    /// headers, alignment padding, and allocations below the top that have
+    /// been deallocated but not reclaimed.
+    pub fn used(&self) -> usize {
+        self.offset.get()
+    }
+
+    /// Returns the number of bytes that have not been handed out yet.
+    ///
+    /// Note that an allocation needs some of these bytes for its header and
+    /// alignment padding.
+    pub fn remaining(&self) -> usize {
+        self.capacity - self.offset.get()
+    }
+
+    /// Returns the maximum number of bytes that were ever in use at once,
+    /// including headers and alignment padding. Requires the `stats` feature.
+    #[cfg(feature = "stats")]
+    pub fn high_water_mark(&self) -> usize {
+        self.peak.get()
+    }
+
+    /// Returns the number of successful allocations, not counting in-place
+    /// growth. Requires the `stats` feature.
+    #[cfg(feature = "stats")]
+    pub fn allocations_served(&self) -> usize {
+        self.served.get()
+    }
+
+    /// Returns the usage statistics of this allocator. Requires the `stats`
+    /// feature.
+    ///
+    /// # Examples
+    #[cfg_attr(feature = "stats", doc = "```rust")]
+    #[cfg_attr(not(feature = "stats"), doc = "```rust,ignore")]
+    /// #![feature(allocator_api)]
+    /// # use std::mem::size_of;
+    /// use noop_allocator::{buffer::BufferFor, stack::StackAllocator};
+    /// const H: usize = size_of::<usize>(); // the size of each header
+    /// let mut buf = BufferFor::<usize, 32>::uninit();
+    /// let stack = StackAllocator::new(buf.as_uninit_bytes());
+    ///
+    /// let a = Box::new_in(1usize, &stack); // 0..H header, H..2H value
+    /// let b = Box::new_in([0u8; 20], &stack); // 2H..3H header, then 20 bytes
+    /// assert_eq!(stack.used(), 3 * H + 20);
+    /// drop(b);
+    /// drop(a);
+    /// let c = Box::new_in([0u8; 4], &stack);
+    /// let report = stack.report();
+    /// assert_eq!(report.used, H + 4);
+    /// assert_eq!(report.high_water_mark, 3 * H + 20);
+    /// assert_eq!(report.allocations_served, 3);
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn report(&self) -> crate::bump::UsageReport {
+        crate::bump::UsageReport {
+            capacity: self.capacity,
+            used: self.used(),
+            high_water_mark: self.high_water_mark(),
+            allocations_served: self.allocations_served(),
+        }
+    }
+
+    /// Sets the offset to `end` after an allocation or in-place growth.
+    fn bump_to(&self, end: usize) {
+        self.offset.set(end);
+        #[cfg(feature = "stats")]
+        self.peak.set(self.peak.get().max(end));
+    }
+
+    /// Returns whether `ptr` could have been allocated from the borrowed
+    /// buffer. This excludes the first header, since every allocation is
+    /// preceded by one, and the end of the buffer, which may be the start of
+    /// another one. Zero-sized allocations are never in the buffer.
+    pub(crate) fn contains(&self, ptr: NonNull<u8>) -> bool {
+        let start = self.start.as_ptr() as usize;
+        (start + HEADER..start + self.capacity).contains(&(ptr.as_ptr() as usize))
+    }
+
+    /// Returns the offset of `ptr` from the start of the buffer.
+    fn offset_of(&self, ptr: NonNull<u8>) -> usize {
+        ptr.as_ptr() as usize - self.start.as_ptr() as usize
+    }
+
+    /// Returns whether the allocation at `ptr` with size `size` is the top
+    /// allocation.
+    fn is_top(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        self.offset_of(ptr) + size == self.offset.get()
+    }
+
+    /// Reallocates by allocating a new block and copying `old_layout.size()`
+    /// or `new_layout.size()` bytes, whichever is smaller.
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let new_ptr = self.allocate(new_layout)?;
+        let count = old_layout.size().min(new_layout.size());
+        new_ptr.cast::<u8>().copy_from_nonoverlapping(ptr, count);
+        self.deallocate(ptr, old_layout);
+        Ok(new_ptr)
+    }
+}
+
+unsafe impl Allocator for StackAllocator<'_> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if layout.size() == 0 {
+            #[cfg(feature = "stats")]
+            self.served.set(self.served.get() + 1);
+            return Ok(NonNull::slice_from_raw_parts(layout.dangling_ptr(), 0));
+        }
+        let offset = self.offset.get();
+        let (start, end) = fit(
+            self.start.as_ptr() as usize,
+            self.capacity,
+            offset.checked_add(HEADER).ok_or(AllocError)?,
+            layout,
+        )
+        .ok_or(AllocError)?;
+        unsafe {
+            let header = self.start.add(start - HEADER).cast::<usize>();
+            header.write_unaligned(offset);
+        }
+        self.bump_to(end);
+        #[cfg(feature = "stats")]
+        self.served.set(self.served.get() + 1);
+        let ptr = unsafe { self.start.add(start) };
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if layout.size() != 0 && self.is_top(ptr, layout.size()) {
+            let header = ptr.sub(HEADER).cast::<usize>();
+            self.offset.set(header.read_unaligned());
+        }
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        debug_assert!(
+            new_layout.size() >= old_layout.size(),
+            "`new_layout.size()` must be greater than or equal to `old_layout.size()`"
+        );
+        if old_layout.size() == 0 {
+            return self.allocate(new_layout);
+        }
+        if self.is_top(ptr, old_layout.size())
+            && ptr.as_ptr() as usize & (new_layout.align() - 1) == 0
+        {
+            let end = self.offset_of(ptr) + new_layout.size();
+            if end <= self.capacity {
+                self.bump_to(end);
+                return Ok(NonNull::slice_from_raw_parts(ptr, new_layout.size()));
+            }
+        }
+        self.reallocate(ptr, old_layout, new_layout)
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let new_ptr = self.grow(ptr, old_layout, new_layout)?;
+        new_ptr
+            .cast::<u8>()
+            .add(old_layout.size())
+            .write_bytes(0, new_layout.size() - old_layout.size());
+        Ok(new_ptr)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        debug_assert!(
+            new_layout.size() <= old_layout.size(),
+            "`new_layout.size()` must be smaller than or equal to `old_layout.size()`"
+        );
+        if new_layout.size() == 0 {
+            self.deallocate(ptr, old_layout);
+            return Ok(NonNull::slice_from_raw_parts(new_layout.dangling_ptr(), 0));
+        }
+        if ptr.as_ptr() as usize & (new_layout.align() - 1) != 0 {
+            return self.reallocate(ptr, old_layout, new_layout);
+        }
+        if self.is_top(ptr, old_layout.size()) {
+            self.offset.set(self.offset_of(ptr) + new_layout.size());
+        }
+        Ok(NonNull::slice_from_raw_parts(ptr, new_layout.size()))
+    }
+}
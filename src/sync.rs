@@ -0,0 +1,7 @@
+//! Atomic types used by this crate, from [`portable_atomic`] if the
+//! `portable-atomic` feature is enabled, otherwise from [`core::sync::atomic`].
+
+#[cfg(not(feature = "portable-atomic"))]
+pub(crate) use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+#[cfg(feature = "portable-atomic")]
+pub(crate) use portable_atomic::{AtomicBool, AtomicUsize, Ordering};
@@ -0,0 +1,899 @@
+//! Scriptable allocators for testing code that is generic over [`Allocator`].
+use core::{
+    alloc::{AllocError, Allocator, Layout},
+    cell::Cell,
+    mem::MaybeUninit,
+    ptr::NonNull,
+};
+
+/// A call made to a [`FnAllocator`], as recorded by
+/// [`FnAllocator::recording`].
+///
+/// The default `grow` and `shrink` implementations allocate a new block, copy,
+/// and deallocate the old one, so they are recorded as an
+/// [`Allocate`][Call::Allocate] followed (on success) by a
+/// [`Deallocate`][Call::Deallocate].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Call {
+    /// `allocate` or `allocate_zeroed` was called with `layout`, and returned
+    /// `Ok` if `ok` is true.
+    Allocate {
+        /// The requested layout.
+        layout: Layout,
+        /// Whether the allocation succeeded.
+        ok: bool,
+    },
+    /// `deallocate` was called with `layout`.
+    Deallocate {
+        /// The layout passed to `deallocate`.
+        layout: Layout,
+    },
+}
+
+/// An [`Allocator`] whose `allocate` and `deallocate` are user closures.
+///
+/// `allocate` is called for every allocation, including the new block of a
+/// `grow` or `shrink`; `deallocate` defaults to doing nothing, and can be
+/// replaced with [`with_deallocate`][Self::with_deallocate]. This makes it easy
+/// to script a sequence of responses, e.g. to inject a failure at a particular
+/// allocation.
+///
+/// With [`recording`][Self::recording], each call is also recorded into a
+/// borrowed buffer of [`Call`]s, like
+/// [`TracingNoopAllocator`][crate::diagnostic::TracingNoopAllocator]: when the
+/// buffer is full, the oldest entry is overwritten.
+///
+/// # Examples
+///
+/// Succeed the first two allocations from a buffer and fail the third:
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::{alloc::{AllocError, Allocator, Layout}, cell::Cell, mem::MaybeUninit};
+/// use noop_allocator::{
+///     bump::BumpAllocator,
+///     testing::{Call, FnAllocator},
+/// };
+/// let mut buf = [MaybeUninit::uninit(); 256];
+/// let bump = BumpAllocator::new(&mut buf);
+/// let count = Cell::new(0);
+/// let mut log = [MaybeUninit::uninit(); 8];
+/// // SAFETY: Successful allocations come from `bump`, which outlives `alloc`.
+/// let alloc = unsafe {
+///     FnAllocator::new(|layout| {
+///         count.set(count.get() + 1);
+///         if count.get() == 3 {
+///             Err(AllocError)
+///         } else {
+///             bump.allocate(layout)
+///         }
+///     })
+/// }
+/// .recording(&mut log);
+///
+/// let boxed = Box::new_in(7_u32, &alloc);
+/// let mut v: Vec<u16, _> = Vec::new_in(&alloc);
+/// v.try_reserve_exact(4).unwrap();
+/// assert!(v.try_reserve_exact(8).is_err());
+/// assert_eq!(*boxed, 7);
+/// drop((boxed, v));
+///
+/// assert_eq!(
+///     alloc.calls().collect::<Vec<_>>(),
+///     [
+///         Call::Allocate {
+///             layout: Layout::new::<u32>(),
+///             ok: true
+///         },
+///         Call::Allocate {
+///             layout: Layout::new::<[u16; 4]>(),
+///             ok: true
+///         },
+///         Call::Allocate {
+///             layout: Layout::new::<[u16; 8]>(),
+///             ok: false
+///         },
+///         Call::Deallocate {
+///             layout: Layout::new::<u32>()
+///         },
+///         Call::Deallocate {
+///             layout: Layout::new::<[u16; 4]>()
+///         },
+///     ],
+/// );
+/// ```
+///
+/// A custom `deallocate`, e.g. to count outstanding allocations:
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::{alloc::{Allocator, Global}, cell::Cell};
+/// use noop_allocator::testing::FnAllocator;
+/// let live = Cell::new(0_i32);
+/// // SAFETY: Every block is allocated by and returned to `Global`.
+/// let alloc = unsafe {
+///     FnAllocator::new(|layout| {
+///         live.set(live.get() + 1);
+///         Global.allocate(layout)
+///     })
+///     .with_deallocate(|ptr, layout| {
+///         live.set(live.get() - 1);
+///         Global.deallocate(ptr, layout)
+///     })
+/// };
+/// let mut v = Vec::new_in(&alloc);
+/// v.extend(0..100_u64);
+/// assert_eq!(live.get(), 1);
+/// drop(v);
+/// assert_eq!(live.get(), 0);
+/// ```
+pub struct FnAllocator<'a, A, D = fn(NonNull<u8>, Layout)> {
+    allocate: A,
+    deallocate: D,
+    log: &'a [Cell<MaybeUninit<Call>>],
+    total: Cell<usize>,
+}
+
+impl<A> FnAllocator<'static, A>
+where
+    A: Fn(Layout) -> Result<NonNull<[u8]>, AllocError>,
+{
+    /// Creates a new `FnAllocator` that calls `allocate` for every allocation,
+    /// and does nothing on deallocation.
+    ///
+    /// # Safety
+    ///
+    /// Every pointer returned by `allocate` must satisfy the requirements of
+    /// [`Allocator::allocate`]: it must be valid for reads and writes of
+    /// `layout.size()` bytes, aligned to `layout.align()`, and remain valid
+    /// (and not be handed out again) until it is passed to `deallocate`.
+    pub unsafe fn new(allocate: A) -> Self {
+        Self {
+            allocate,
+            deallocate: |_, _| {},
+            log: &[],
+            total: Cell::new(0),
+        }
+    }
+}
+
+impl<'a, A, D> FnAllocator<'a, A, D>
+where
+    A: Fn(Layout) -> Result<NonNull<[u8]>, AllocError>,
+    D: Fn(NonNull<u8>, Layout),
+{
+    /// Replaces the `deallocate` closure.
+    ///
+    /// # Safety
+    ///
+    /// `deallocate` is called with pointers returned by `allocate` and their
+    /// layouts, and must not invalidate any other block returned by `allocate`.
+    pub unsafe fn with_deallocate<D2>(self, deallocate: D2) -> FnAllocator<'a, A, D2>
+    where
+        D2: Fn(NonNull<u8>, Layout),
+    {
+        FnAllocator {
+            allocate: self.allocate,
+            deallocate,
+            log: self.log,
+            total: self.total,
+        }
+    }
+
+    /// Records every subsequent call into `log`, replacing any previous log.
+    pub fn recording<'b>(self, log: &'b mut [MaybeUninit<Call>]) -> FnAllocator<'b, A, D> {
+        FnAllocator {
+            allocate: self.allocate,
+            deallocate: self.deallocate,
+            log: Cell::from_mut(log).as_slice_of_cells(),
+            total: Cell::new(0),
+        }
+    }
+
+    /// Returns the number of calls recorded since
+    /// [`recording`][Self::recording] or the last [`clear`][Self::clear],
+    /// including those whose entries were overwritten.
+    pub fn total(&self) -> usize {
+        self.total.get()
+    }
+
+    /// Returns the retained calls, oldest first.
+    pub fn calls(&self) -> impl Iterator<Item = Call> + '_ {
+        let total = self.total.get();
+        let len = total.min(self.log.len());
+        (total - len..total).map(|n| {
+            // SAFETY: The last `len` entries have been written.
+            unsafe { self.log[n % self.log.len()].get().assume_init() }
+        })
+    }
+
+    /// Forgets all recorded calls.
+    pub fn clear(&self) {
+        self.total.set(0);
+    }
+
+    fn record(&self, call: Call) {
+        if self.log.is_empty() {
+            return;
+        }
+        let total = self.total.get();
+        self.log[total % self.log.len()].set(MaybeUninit::new(call));
+        self.total.set(total.wrapping_add(1));
+    }
+}
+
+unsafe impl<A, D> Allocator for FnAllocator<'_, A, D>
+where
+    A: Fn(Layout) -> Result<NonNull<[u8]>, AllocError>,
+    D: Fn(NonNull<u8>, Layout),
+{
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let result = (self.allocate)(layout);
+        self.record(Call::Allocate {
+            layout,
+            ok: result.is_ok(),
+        });
+        result
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.record(Call::Deallocate { layout });
+        (self.deallocate)(ptr, layout)
+    }
+}
+
+/// An [`Allocator`] adapter that delegates the first `n` allocations to an
+/// inner allocator, and fails every allocation after that.
+///
+/// `allocate`, `allocate_zeroed`, `grow`, and `grow_zeroed` calls are counted,
+/// whether or not the inner allocator succeeds. `deallocate` and `shrink` are
+/// always delegated, so that collections can still release memory after the
+/// failure. The counters are [`Cell`]s, so a single `&FailAfterN<A>` can be
+/// shared by several collections.
+///
+/// See [`exhaust_failures`] to run a test with a failure injected at every
+/// possible point.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::alloc::Global;
+/// use noop_allocator::testing::FailAfterN;
+/// let alloc = FailAfterN::new(Global, 2);
+/// let a = Box::try_new_in(1_u8, &alloc).unwrap();
+/// let mut v = Vec::<u8, _>::new_in(&alloc);
+/// v.try_reserve_exact(4).unwrap();
+/// assert!(v.try_reserve_exact(8).is_err());
+/// assert!(Box::try_new_in(2_u8, &alloc).is_err());
+/// assert_eq!(alloc.failures_triggered(), 2);
+///
+/// // Shrinking and deallocating still work.
+/// v.shrink_to_fit();
+/// drop((a, v));
+///
+/// alloc.reset();
+/// assert_eq!(alloc.failures_triggered(), 0);
+/// assert!(Box::try_new_in(3_u8, &alloc).is_ok());
+/// ```
+pub struct FailAfterN<A> {
+    inner: A,
+    limit: Cell<usize>,
+    calls: Cell<usize>,
+    failures: Cell<usize>,
+}
+
+impl<A: Allocator> FailAfterN<A> {
+    /// Creates a new `FailAfterN<A>` that lets the first `n` allocations
+    /// through to `inner`.
+    pub const fn new(inner: A, n: usize) -> Self {
+        Self {
+            inner,
+            limit: Cell::new(n),
+            calls: Cell::new(0),
+            failures: Cell::new(0),
+        }
+    }
+
+    /// Returns a reference to the inner allocator.
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// Returns the number of allocations that are let through.
+    pub fn limit(&self) -> usize {
+        self.limit.get()
+    }
+
+    /// Returns the number of allocations attempted since creation or the last
+    /// [`reset`][Self::reset], including failed ones.
+    pub fn calls(&self) -> usize {
+        self.calls.get()
+    }
+
+    /// Returns the number of allocations failed by this adapter since creation
+    /// or the last [`reset`][Self::reset].
+    pub fn failures_triggered(&self) -> usize {
+        self.failures.get()
+    }
+
+    /// Resets the counters, so that the next `limit` allocations are let
+    /// through again.
+    pub fn reset(&self) {
+        self.calls.set(0);
+        self.failures.set(0);
+    }
+
+    fn admit(&self) -> Result<(), AllocError> {
+        let calls = self.calls.get();
+        self.calls.set(calls + 1);
+        if calls < self.limit.get() {
+            Ok(())
+        } else {
+            self.failures.set(self.failures.get() + 1);
+            Err(AllocError)
+        }
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for FailAfterN<A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.admit()?;
+        self.inner.allocate(layout)
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.admit()?;
+        self.inner.allocate_zeroed(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.inner.deallocate(ptr, layout)
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.admit()?;
+        self.inner.grow(ptr, old_layout, new_layout)
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.admit()?;
+        self.inner.grow_zeroed(ptr, old_layout, new_layout)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.inner.shrink(ptr, old_layout, new_layout)
+    }
+}
+
+/// Run `f` with a [`FailAfterN`] over `inner` that lets through 0, 1, 2, ...
+/// allocations, until `f` returns `Ok`, and return the number of allocations
+/// that were needed along with the result.
+///
+/// This checks the error path of every allocation `f` makes. `f` should report
+/// allocation failures as `Err`, e.g. by using `try_reserve` and
+/// `Box::try_new_in`, since the infallible APIs abort the process instead.
+///
+/// # Panics
+///
+/// Panics if `f` returns `Err` although no allocation was failed, since that
+/// would otherwise loop forever.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::{alloc::{Allocator, Global}, collections::TryReserveError};
+/// use noop_allocator::testing;
+/// fn squares<A: Allocator>(n: u32, alloc: A) -> Result<Vec<u32, A>, TryReserveError> {
+///     let mut v = Vec::new_in(alloc);
+///     for i in 0..n {
+///         v.try_reserve(1)?;
+///         v.push(i * i);
+///     }
+///     Ok(v)
+/// }
+///
+/// let mut failures = 0;
+/// let (allocations, v) = testing::exhaust_failures(Global, |alloc| {
+///     let result = squares(10, alloc);
+///     failures += result.is_err() as usize;
+///     result.map(|v| v.to_vec())
+/// });
+/// // Capacity 4, then 8, then 16.
+/// assert_eq!(allocations, 3);
+/// assert_eq!(failures, 3);
+/// assert_eq!(v[9], 81);
+/// ```
+pub fn exhaust_failures<A: Allocator, T, E>(
+    inner: A,
+    mut f: impl FnMut(&FailAfterN<A>) -> Result<T, E>,
+) -> (usize, T) {
+    let alloc = FailAfterN::new(inner, 0);
+    for n in 0.. {
+        alloc.limit.set(n);
+        alloc.reset();
+        match f(&alloc) {
+            Ok(value) => return (n, value),
+            Err(_) if alloc.failures_triggered() > 0 => {}
+            Err(_) => panic!("`f` failed without an injected allocation failure"),
+        }
+    }
+    unreachable!()
+}
+
+/// A live allocation tracked by [`DeallocCheck`].
+#[derive(Clone, Copy)]
+struct Live {
+    ptr: NonNull<u8>,
+    layout: Layout,
+    len: usize,
+}
+
+impl Live {
+    /// Whether `layout` fits this allocation, as required by
+    /// [`Allocator::deallocate`].
+    fn fits(&self, layout: Layout) -> bool {
+        layout.align() == self.layout.align()
+            && (self.layout.size()..=self.len).contains(&layout.size())
+    }
+}
+
+/// An [`Allocator`] adapter that checks that every pointer passed to
+/// `deallocate`, `grow`, or `shrink` is currently allocated, with a layout that
+/// fits it.
+///
+/// Up to `N` live allocations are recorded in a fixed table. The adapter
+/// panics:
+/// * if `deallocate`, `grow`, `grow_zeroed`, or `shrink` is called with a
+///   pointer that is not currently allocated, e.g. on a double free;
+/// * if the layout does not fit the allocation: its alignment must be the one
+///   it was allocated with, and its size must be between the requested size and
+///   the size returned by the inner allocator;
+/// * if more than `N` allocations are live at once;
+/// * when dropped with live allocations, if enabled with
+///   [`assert_empty_on_drop`][Self::assert_empty_on_drop].
+///
+/// Allocations are checked before being passed to the inner allocator, so a
+/// mismatched deallocation never reaches it.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::{bump::BumpAllocator, testing::DeallocCheck};
+/// let mut buf = [MaybeUninit::uninit(); 256];
+/// let bump = BumpAllocator::new(&mut buf);
+/// let check = DeallocCheck::<_>::new(&bump).assert_empty_on_drop();
+///
+/// let mut v = Vec::new_in(&check);
+/// v.extend([1_u32, 2, 3]);
+/// let b = Box::new_in(4_u64, &check);
+/// assert_eq!(check.live(), 2);
+/// v.shrink_to_fit();
+/// drop((v, b));
+/// assert_eq!(check.live(), 0);
+/// ```
+///
+/// Deallocating with the wrong layout panics:
+///
+/// ```rust,should_panic
+/// #![feature(allocator_api)]
+/// # use std::{alloc::{Allocator, Global, Layout}};
+/// use noop_allocator::testing::DeallocCheck;
+/// let check = DeallocCheck::<_>::new(Global);
+/// let ptr = check.allocate(Layout::new::<[u32; 4]>()).unwrap();
+/// unsafe { check.deallocate(ptr.cast(), Layout::new::<[u8; 16]>()) };
+/// ```
+pub struct DeallocCheck<A, const N: usize = 64> {
+    inner: A,
+    live: [Cell<Option<Live>>; N],
+    assert_empty_on_drop: bool,
+}
+
+impl<A: Allocator, const N: usize> DeallocCheck<A, N> {
+    /// Creates a new `DeallocCheck<A, N>` allocating from `inner`.
+    pub const fn new(inner: A) -> Self {
+        Self {
+            inner,
+            live: [const { Cell::new(None) }; N],
+            assert_empty_on_drop: false,
+        }
+    }
+
+    /// Makes the adapter panic when dropped while any allocation is live.
+    ///
+    /// The check is skipped if the thread is already panicking (with the `std`
+    /// feature), to avoid aborting the process.
+    pub const fn assert_empty_on_drop(mut self) -> Self {
+        self.assert_empty_on_drop = true;
+        self
+    }
+
+    /// Returns a reference to the inner allocator.
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// Returns the number of live allocations.
+    pub fn live(&self) -> usize {
+        self.live
+            .iter()
+            .filter(|entry| entry.get().is_some())
+            .count()
+    }
+
+    fn insert(&self, ptr: NonNull<[u8]>, layout: Layout) {
+        let Some(entry) = self.live.iter().find(|entry| entry.get().is_none()) else {
+            panic!("more than {N} live allocations");
+        };
+        entry.set(Some(Live {
+            ptr: ptr.cast(),
+            layout,
+            len: ptr.len(),
+        }));
+    }
+
+    /// Returns the entry for `ptr`, panicking unless it is live and `layout`
+    /// fits it.
+    fn find(&self, ptr: NonNull<u8>, layout: Layout, op: &str) -> &Cell<Option<Live>> {
+        // Zero-sized allocations may share an address with each other or with
+        // another allocation, so prefer an entry that `layout` fits.
+        let mut same_ptr = None;
+        for entry in &self.live {
+            match entry.get() {
+                Some(live) if live.ptr == ptr && live.fits(layout) => return entry,
+                Some(live) if live.ptr == ptr => same_ptr = Some(live),
+                _ => {}
+            }
+        }
+        match same_ptr {
+            Some(live) => panic!(
+                "{op} of {ptr:p} with {layout:?}, but it was allocated with {:?} ({} bytes returned)",
+                live.layout, live.len
+            ),
+            None => panic!("{op} of {ptr:p}, which is not currently allocated (double free?)"),
+        }
+    }
+
+    fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        op: &str,
+        f: impl FnOnce() -> Result<NonNull<[u8]>, AllocError>,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let entry = self.find(ptr, old_layout, op);
+        let new_ptr = f()?;
+        entry.set(None);
+        self.insert(new_ptr, new_layout);
+        Ok(new_ptr)
+    }
+}
+
+impl<A, const N: usize> Drop for DeallocCheck<A, N> {
+    fn drop(&mut self) {
+        #[cfg(feature = "std")]
+        if std::thread::panicking() {
+            return;
+        }
+        if self.assert_empty_on_drop {
+            let leaked = self
+                .live
+                .iter()
+                .filter(|entry| entry.get().is_some())
+                .count();
+            assert!(leaked == 0, "dropped with {leaked} live allocations");
+        }
+    }
+}
+
+unsafe impl<A: Allocator, const N: usize> Allocator for DeallocCheck<A, N> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = self.inner.allocate(layout)?;
+        self.insert(ptr, layout);
+        Ok(ptr)
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = self.inner.allocate_zeroed(layout)?;
+        self.insert(ptr, layout);
+        Ok(ptr)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.find(ptr, layout, "deallocation").set(None);
+        self.inner.deallocate(ptr, layout)
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.reallocate(ptr, old_layout, new_layout, "grow", || {
+            self.inner.grow(ptr, old_layout, new_layout)
+        })
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.reallocate(ptr, old_layout, new_layout, "grow_zeroed", || {
+            self.inner.grow_zeroed(ptr, old_layout, new_layout)
+        })
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.reallocate(ptr, old_layout, new_layout, "shrink", || {
+            self.inner.shrink(ptr, old_layout, new_layout)
+        })
+    }
+}
+
+/// The [`Allocator`] method called, in a [`Record`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum RecordOp {
+    /// [`Allocator::allocate`].
+    Allocate,
+    /// [`Allocator::allocate_zeroed`].
+    AllocateZeroed,
+    /// [`Allocator::deallocate`].
+    Deallocate,
+    /// [`Allocator::grow`].
+    Grow,
+    /// [`Allocator::grow_zeroed`].
+    GrowZeroed,
+    /// [`Allocator::shrink`].
+    Shrink,
+}
+
+/// A call made to a [`RecordingAllocator`].
+///
+/// This is `#[repr(C)]` (and [`RecordOp`] is `#[repr(u8)]`), so a log can be
+/// read directly from memory with a debugger or from a core dump. Pointers are
+/// stored as addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct Record {
+    /// The position of this call among all recorded calls, counting from 0.
+    pub seq: usize,
+    /// The returned address, or the deallocated address for `deallocate`. 0
+    /// if the call failed.
+    pub addr: usize,
+    /// The address passed to `grow`, `grow_zeroed`, or `shrink`, or 0.
+    pub old_addr: usize,
+    /// The requested size (the new size, for `grow` and `shrink`).
+    pub size: usize,
+    /// The requested alignment (the new alignment, for `grow` and `shrink`).
+    pub align: usize,
+    /// The old size, for `grow`, `grow_zeroed`, and `shrink`, or 0.
+    pub old_size: usize,
+    /// The method called.
+    pub op: RecordOp,
+    /// Whether the call succeeded. Always `true` for `deallocate`.
+    pub ok: bool,
+}
+
+impl Record {
+    /// Returns whether this is a successful `grow`, `grow_zeroed`, or `shrink`
+    /// that moved the allocation.
+    pub fn moved(&self) -> bool {
+        self.ok && self.old_addr != 0 && self.addr != self.old_addr
+    }
+}
+
+/// An [`Allocator`] adapter that records every call to an inner allocator into
+/// a borrowed buffer of [`Record`]s.
+///
+/// Like [`TracingNoopAllocator`][crate::diagnostic::TracingNoopAllocator], the
+/// buffer is a ring: when it is full, the oldest record is overwritten, and the
+/// record with sequence number `n` is at index `n % capacity`. Recording can be
+/// paused and resumed with [`set_enabled`][Self::set_enabled]; calls made while
+/// it is paused are passed to the inner allocator without being recorded.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::{
+///     bump::BumpAllocator,
+///     testing::{RecordOp, RecordingAllocator},
+/// };
+/// let mut buf = [MaybeUninit::uninit(); 512];
+/// let bump = BumpAllocator::new(&mut buf);
+/// let mut log = [MaybeUninit::uninit(); 16];
+/// let alloc = RecordingAllocator::new(&bump, &mut log);
+///
+/// let mut v = Vec::new_in(&alloc);
+/// v.push(0_u32);
+/// let _other = Box::new_in(0_u8, &alloc);
+/// for i in 1..20 {
+///     v.push(i);
+/// }
+///
+/// let ops: Vec<RecordOp> = alloc.records().map(|record| record.op).collect();
+/// assert_eq!(
+///     ops,
+///     [
+///         RecordOp::Allocate,
+///         RecordOp::Allocate,
+///         RecordOp::Grow,
+///         RecordOp::Grow,
+///         RecordOp::Grow
+///     ]
+/// );
+/// // `v` is not the last allocation in the arena, so every grow moves it.
+/// assert_eq!(alloc.records().filter(|record| record.moved()).count(), 3);
+/// ```
+pub struct RecordingAllocator<'a, A> {
+    inner: A,
+    log: &'a [Cell<MaybeUninit<Record>>],
+    total: Cell<usize>,
+    enabled: Cell<bool>,
+}
+
+impl<'a, A: Allocator> RecordingAllocator<'a, A> {
+    /// Creates a new `RecordingAllocator<'a, A>` forwarding to `inner` and
+    /// recording into `log`.
+    pub fn new(inner: A, log: &'a mut [MaybeUninit<Record>]) -> Self {
+        Self {
+            inner,
+            log: Cell::from_mut(log).as_slice_of_cells(),
+            total: Cell::new(0),
+            enabled: Cell::new(true),
+        }
+    }
+
+    /// Returns a reference to the inner allocator.
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    /// Pauses (`false`) or resumes (`true`) recording.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.set(enabled);
+    }
+
+    /// Returns whether calls are being recorded.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.get()
+    }
+
+    /// Returns the number of calls recorded since creation or the last
+    /// [`clear`][Self::clear], including those whose records were overwritten.
+    pub fn total(&self) -> usize {
+        self.total.get()
+    }
+
+    /// Returns the retained records, oldest first.
+    pub fn records(&self) -> impl Iterator<Item = Record> + '_ {
+        let total = self.total.get();
+        let len = total.min(self.log.len());
+        (total - len..total).map(|n| {
+            // SAFETY: The last `len` records have been written.
+            unsafe { self.log[n % self.log.len()].get().assume_init() }
+        })
+    }
+
+    /// Forgets all records, and restarts the sequence numbers from 0.
+    pub fn clear(&self) {
+        self.total.set(0);
+    }
+
+    fn record(
+        &self,
+        op: RecordOp,
+        result: Result<NonNull<[u8]>, AllocError>,
+        old: Option<(NonNull<u8>, Layout)>,
+        layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        if !self.enabled.get() || self.log.is_empty() {
+            return result;
+        }
+        let seq = self.total.get();
+        let (old_addr, old_size) = old.map_or((0, 0), |(ptr, layout)| {
+            (ptr.as_ptr() as usize, layout.size())
+        });
+        self.log[seq % self.log.len()].set(MaybeUninit::new(Record {
+            seq,
+            addr: result.map_or(0, |ptr| ptr.cast::<u8>().as_ptr() as usize),
+            old_addr,
+            size: layout.size(),
+            align: layout.align(),
+            old_size,
+            op,
+            ok: result.is_ok(),
+        }));
+        self.total.set(seq.wrapping_add(1));
+        result
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for RecordingAllocator<'_, A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let result = self.inner.allocate(layout);
+        self.record(RecordOp::Allocate, result, None, layout)
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let result = self.inner.allocate_zeroed(layout);
+        self.record(RecordOp::AllocateZeroed, result, None, layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.inner.deallocate(ptr, layout);
+        let dangling = NonNull::slice_from_raw_parts(ptr, layout.size());
+        let _ = self.record(RecordOp::Deallocate, Ok(dangling), None, layout);
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let result = self.inner.grow(ptr, old_layout, new_layout);
+        self.record(RecordOp::Grow, result, Some((ptr, old_layout)), new_layout)
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let result = self.inner.grow_zeroed(ptr, old_layout, new_layout);
+        self.record(
+            RecordOp::GrowZeroed,
+            result,
+            Some((ptr, old_layout)),
+            new_layout,
+        )
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let result = self.inner.shrink(ptr, old_layout, new_layout);
+        self.record(
+            RecordOp::Shrink,
+            result,
+            Some((ptr, old_layout)),
+            new_layout,
+        )
+    }
+}
@@ -0,0 +1,129 @@
+//! A [`NoopAllocator`] variant that zeroes memory when it is released.
+use core::{
+    alloc::{AllocError, Allocator, Layout},
+    marker::PhantomData,
+    ptr::NonNull,
+    sync::atomic::{Ordering, compiler_fence},
+};
+
+use crate::NoopAllocator;
+
+/// Overwrites `len` bytes at `ptr` with zeroes, in a way that the compiler will
+/// not optimize out even if the memory is never read again.
+///
+/// # Safety
+///
+/// `ptr` must be valid for writes of `len` bytes.
+unsafe fn zeroize(ptr: NonNull<u8>, len: usize) {
+    for i in 0..len {
+        ptr.add(i).write_volatile(0);
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// An [`Allocator`] that behaves like [`NoopAllocator`], but zeroes memory
+/// when it is released, e.g. to wipe key material as soon as its owner is
+/// dropped.
+///
+/// [`deallocate`][Allocator::deallocate] zeroes the `layout.size()` bytes at
+/// `ptr`, and [`shrink`][Allocator::shrink] zeroes the bytes between the new
+/// and old sizes. The writes are volatile and followed by a compiler fence, so
+/// they are not removed as dead stores. Only the bytes described by the layout
+/// are zeroed: copies made elsewhere (e.g. by moving a value out) are not.
+///
+/// Like `NoopAllocator`, this allocator cannot grow allocations, so a
+/// collection can never move secret data to a new buffer and leave a copy
+/// behind.
+///
+/// Unlike `NoopAllocator`, `deallocate` and `shrink` require that `ptr` be
+/// valid for writes of `layout.size()` (resp. `old_layout.size()`) bytes.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(allocator_api)]
+/// # use std::mem::MaybeUninit;
+/// use noop_allocator::zeroizing::ZeroizingNoopAllocator;
+/// let mut slot = MaybeUninit::new([0xA5u8; 32]);
+/// let ptr = slot.as_ptr();
+/// let key = unsafe { Box::from_raw_in(slot.as_mut_ptr(), ZeroizingNoopAllocator::new()) };
+/// assert_eq!(*key, [0xA5; 32]);
+/// drop(key);
+/// assert_eq!(unsafe { ptr.read_volatile() }, [0; 32]);
+///
+/// let mut buf = MaybeUninit::new([0xA5u8; 8]);
+/// let ptr = buf.as_ptr();
+/// let mut v = unsafe {
+///     Vec::from_raw_parts_in(
+///         buf.as_mut_ptr().cast::<u8>(),
+///         8,
+///         8,
+///         ZeroizingNoopAllocator::new(),
+///     )
+/// };
+/// v.truncate(3);
+/// v.shrink_to_fit();
+/// assert_eq!(
+///     unsafe { ptr.read_volatile() },
+///     [0xA5, 0xA5, 0xA5, 0, 0, 0, 0, 0]
+/// );
+/// drop(v);
+/// assert_eq!(unsafe { ptr.read_volatile() }, [0; 8]);
+/// ```
+#[repr(transparent)]
+pub struct ZeroizingNoopAllocator<'a>(PhantomData<&'a ()>);
+
+impl<'a> ZeroizingNoopAllocator<'a> {
+    /// Creates a new `ZeroizingNoopAllocator<'a>`.
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl Default for ZeroizingNoopAllocator<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl Allocator for ZeroizingNoopAllocator<'_> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        NoopAllocator::new().allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        zeroize(ptr, layout.size());
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        NoopAllocator::new().grow(ptr, old_layout, new_layout)
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        NoopAllocator::new().grow_zeroed(ptr, old_layout, new_layout)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let new_ptr = NoopAllocator::new().shrink(ptr, old_layout, new_layout)?;
+        zeroize(
+            ptr.add(new_layout.size()),
+            old_layout.size() - new_layout.size(),
+        );
+        Ok(new_ptr)
+    }
+}
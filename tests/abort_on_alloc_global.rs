@@ -0,0 +1,60 @@
+//! Checks that `AbortOnAllocGlobal` aborts with a message on a forbidden
+//! allocation, by running this binary again as a subprocess.
+//!
+//! This uses a custom `main` (`harness = false`), since the default test
+//! harness allocates.
+
+use std::{alloc::System, env, hint::black_box, process::Command};
+
+use noop_allocator::global::{AbortOnAllocGlobal, stderr_output};
+
+#[global_allocator]
+static GLOBAL: AbortOnAllocGlobal<System> = AbortOnAllocGlobal::new(System, stderr_output);
+
+fn child(case: &str) {
+    match case {
+        "allowed" => {
+            let v = GLOBAL.allow_allocations(|| vec![1u8, 2, 3]);
+            GLOBAL.enable();
+            let sum: u32 = GLOBAL.allow_allocations(|| {
+                let boxed = black_box(Box::new(v.len() as u32));
+                *boxed
+            });
+            drop(v);
+            GLOBAL.disable();
+            assert_eq!(sum, 3);
+        }
+        "forbidden" => {
+            GLOBAL.enable();
+            black_box(Box::new(0u64));
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn run(case: &str) -> std::process::Output {
+    Command::new(env::current_exe().unwrap())
+        .env("ABORT_ON_ALLOC_CASE", case)
+        .output()
+        .unwrap()
+}
+
+fn main() {
+    if let Ok(case) = env::var("ABORT_ON_ALLOC_CASE") {
+        return child(&case);
+    }
+
+    let allowed = run("allowed");
+    assert!(allowed.status.success(), "{allowed:?}");
+    assert!(allowed.stderr.is_empty(), "{allowed:?}");
+
+    let forbidden = run("forbidden");
+    assert!(!forbidden.status.success(), "{forbidden:?}");
+    let stderr = String::from_utf8(forbidden.stderr).unwrap();
+    assert!(
+        stderr.contains(
+            "memory allocation of 8 bytes with alignment 8 while allocations are forbidden"
+        ),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,76 @@
+//! Tests the arena-allocated tree from the `arena_tree` example.
+#![cfg_attr(feature = "nightly", feature(allocator_api))]
+#![cfg(feature = "nightly")]
+
+#[path = "../examples/arena_tree/tree.rs"]
+mod tree;
+
+use std::mem::MaybeUninit;
+
+use noop_allocator::bump::ScopedArena;
+use tree::Node;
+
+#[test]
+fn depth_3_tree() {
+    let mut buf = [MaybeUninit::<u8>::uninit(); 2048];
+    let range = buf.as_ptr_range();
+    let range = range.start as usize..range.end as usize;
+    let mut arena = ScopedArena::new(&mut buf);
+
+    arena.scope(|arena| {
+        let mut root = Node::new_in(1, arena).unwrap();
+        for i in 0..3 {
+            let child = root.add_child(10 + i).unwrap();
+            for j in 0..2 {
+                child.add_child(100 + 10 * i + j).unwrap();
+            }
+        }
+        assert_eq!(root.count(), 10);
+        assert_eq!(root.depth(), 3);
+
+        let mut visited = vec![];
+        root.visit(&mut |depth, value| visited.push((depth, value)));
+        assert_eq!(
+            visited,
+            [
+                (0, 1),
+                (1, 10),
+                (2, 100),
+                (2, 101),
+                (1, 11),
+                (2, 110),
+                (2, 111),
+                (1, 12),
+                (2, 120),
+                (2, 121),
+            ]
+        );
+
+        // Nodes live in the arena's buffer.
+        let node = &*root.children[1].children[0] as *const Node<'_> as usize;
+        assert!(range.contains(&node));
+    });
+    assert_eq!(arena.used(), 0);
+}
+
+#[test]
+fn exhaustion() {
+    let mut buf = [MaybeUninit::<u8>::uninit(); 512];
+    let mut arena = ScopedArena::new(&mut buf);
+
+    for _ in 0..3 {
+        arena.scope(|arena| {
+            let mut root = Node::new_in(0, arena).unwrap();
+            let mut added = 0;
+            while root.add_child(added).is_ok() {
+                added += 1;
+            }
+            assert!(added > 0);
+            // A failed insertion leaves the tree unchanged and usable.
+            assert_eq!(root.count(), 1 + added as usize);
+            assert!(root.add_child(added).is_err());
+            assert_eq!(root.children.last().unwrap().value, added - 1);
+        });
+        assert_eq!(arena.used(), 0);
+    }
+}
@@ -0,0 +1,87 @@
+//! Checks that `BufferPool` chunks are reused, and that elements do not leak
+//! from one checkout of a chunk into the next.
+#![cfg(all(feature = "alloc", feature = "nightly"))]
+
+use std::{mem::MaybeUninit, rc::Rc};
+
+use noop_allocator::pool::BufferPool;
+
+#[test]
+fn acquire_release_reacquire() {
+    let marker = Rc::new(());
+    let mut backing = [const { MaybeUninit::uninit() }; 10];
+    let pool = BufferPool::new(&mut backing, 3);
+    assert_eq!(pool.chunk_count(), 3);
+
+    let mut chunks: Vec<_> = std::iter::from_fn(|| pool.try_acquire()).collect();
+    assert_eq!(chunks.len(), 3);
+    assert_eq!(pool.available(), 0);
+    for chunk in &mut chunks {
+        assert!(chunk.is_empty());
+        assert_eq!(chunk.capacity(), 3);
+        chunk
+            .extend_from_slice(&[marker.clone(), marker.clone()])
+            .unwrap();
+    }
+    assert_eq!(Rc::strong_count(&marker), 7);
+
+    // Release the first and last chunks; their elements are dropped.
+    let middle = chunks.remove(1);
+    drop(chunks);
+    assert_eq!(Rc::strong_count(&marker), 3);
+    assert_eq!(pool.available(), 2);
+
+    let again: Vec<_> = std::iter::from_fn(|| pool.try_acquire()).collect();
+    let mut indices: Vec<_> = again.iter().map(|c| c.index()).collect();
+    indices.sort();
+    assert_eq!(indices, [0, 2]);
+    assert!(again.iter().all(|c| c.is_empty()));
+    assert_eq!(middle.len(), 2);
+    assert!(pool.try_acquire().is_none());
+
+    drop((middle, again));
+    assert_eq!(Rc::strong_count(&marker), 1);
+    assert_eq!(pool.available(), 3);
+}
+
+#[test]
+fn full_chunk_rejects_elements() {
+    let mut backing = [MaybeUninit::<u8>::uninit(); 4];
+    let pool = BufferPool::new(&mut backing, 3);
+    let mut a = pool.try_acquire().unwrap();
+    a.extend_from_slice(b"ab").unwrap();
+    assert_eq!(a.extend_from_slice(b"cd"), Err(()));
+    assert_eq!(*a, *b"ab");
+    a.push(b'c').unwrap();
+    assert_eq!(a.push(b'd'), Err(b'd'));
+    assert_eq!(a.pop(), Some(b'c'));
+}
+
+#[test]
+fn cleared_chunk_is_returned() {
+    let mut backing = [MaybeUninit::<u16>::uninit(); 4];
+    let pool = BufferPool::new(&mut backing, 4);
+    let mut a = pool.try_acquire().unwrap();
+    a.push(1).unwrap();
+    // Unlike an `OwningSlice`, this cannot `shrink_to_fit` the chunk away.
+    a.clear();
+    drop(a);
+    assert_eq!(pool.available(), 1);
+    assert_eq!(pool.try_acquire().unwrap().capacity(), 4);
+}
+
+#[test]
+fn swapped_chunks_are_returned() {
+    let mut backing = [MaybeUninit::<u16>::uninit(); 4];
+    let pool = BufferPool::new(&mut backing, 2);
+    let mut a = pool.try_acquire().unwrap();
+    let mut b = pool.try_acquire().unwrap();
+    a.push(1).unwrap();
+    b.push(2).unwrap();
+    a.swap_with_slice(&mut b);
+    std::mem::swap(&mut a, &mut b);
+    assert_eq!((a.index(), b.index()), (1, 0));
+    assert_eq!((&*a, &*b), (&[1][..], &[2][..]));
+    drop((a, b));
+    assert_eq!(pool.available(), 2);
+}
@@ -0,0 +1,41 @@
+//! Checks that `ChainAllocator` attributes pointers at the boundary between two
+//! adjacent regions to the right region.
+#![cfg(feature = "nightly")]
+#![feature(allocator_api)]
+
+use std::{
+    alloc::{Allocator, Layout},
+    mem::{MaybeUninit, size_of},
+};
+
+use noop_allocator::chain::ChainAllocator;
+
+#[test]
+fn end_of_region_is_not_contained() {
+    let mut buf = [MaybeUninit::<u8>::uninit(); 64];
+    let (first, second) = buf.split_at_mut(32);
+    let boundary = second.as_ptr().cast::<u8>();
+    let chain = ChainAllocator::new([first, second]);
+    assert_eq!(chain.region_of(boundary), None);
+
+    let a = Box::new_in([0u8; 4], &chain);
+    assert_eq!(chain.region_of(a.as_ptr()), Some(0));
+    assert_eq!(chain.region_of(boundary), None);
+}
+
+#[test]
+fn zero_sized_allocations_are_not_in_a_region() {
+    let mut buf = [MaybeUninit::<u8>::uninit(); 2 * size_of::<usize>()];
+    let (first, second) = buf.split_at_mut(size_of::<usize>());
+    let boundary = second.as_ptr().cast::<u8>();
+    let chain = ChainAllocator::new([first, second]);
+
+    // Zero-sized allocations do not take up a header's worth of space, even
+    // in a region that only has room for one.
+    let zst = chain.allocate(Layout::new::<()>()).unwrap().cast::<u8>();
+    assert_eq!(chain.remaining(), [size_of::<usize>(); 2]);
+    assert_eq!(chain.region_of(zst.as_ptr()), None);
+    assert_eq!(chain.region_of(boundary), None);
+    unsafe { chain.deallocate(zst, Layout::new::<()>()) };
+    assert_eq!(chain.remaining(), [size_of::<usize>(); 2]);
+}
@@ -0,0 +1,98 @@
+//! Asserts that the allocation-free APIs do not allocate, with
+//! `CountingGlobalAlloc` as the global allocator.
+//!
+//! The counters are shared by all threads, so this uses a custom `main`
+//! (`harness = false`) instead of the default, multi-threaded test harness.
+
+use std::{alloc::System, mem::MaybeUninit, panic};
+
+use noop_allocator::global::CountingGlobalAlloc;
+
+#[global_allocator]
+static GLOBAL: CountingGlobalAlloc<System> = CountingGlobalAlloc::new(System);
+
+#[cfg(feature = "nightly")]
+fn no_alloc() -> u64 {
+    use noop_allocator::{owning_ref, owning_slice};
+
+    let mut slot = MaybeUninit::uninit();
+    let boxed = owning_ref::from_maybeuninit_write(&mut slot, [1u64, 2, 3]);
+    let mut buf = [MaybeUninit::<u64>::uninit(); 8];
+    let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+    vec.extend(boxed.iter().copied());
+    vec.extend(4..6);
+    vec.retain(|x| x % 2 == 1);
+    vec.iter().sum()
+}
+
+#[cfg(not(feature = "nightly"))]
+fn no_alloc() -> u64 {
+    use noop_allocator::stable::{StableOwningRef, StableOwningSlice};
+
+    let mut slot = MaybeUninit::uninit();
+    let boxed = StableOwningRef::from_maybeuninit_write(&mut slot, [1u64, 2, 3]);
+    let mut buf = [MaybeUninit::<u64>::uninit(); 8];
+    let mut vec = StableOwningSlice::empty_from_maybeuninit_slice(&mut buf);
+    for &x in boxed.iter().chain(&[4, 5]) {
+        if x % 2 == 1 {
+            vec.push(x).unwrap();
+        }
+    }
+    vec.iter().sum()
+}
+
+#[cfg(feature = "nightly")]
+fn reorder_no_alloc() {
+    use noop_allocator::owning_slice::{self, reorder};
+
+    let mut buf = [const { MaybeUninit::<String>::uninit() }; 8];
+    let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+    vec.extend((0..7).map(|_| String::new()));
+    for (i, s) in vec.iter_mut().enumerate() {
+        s.push(char::from(b'a' + i as u8));
+    }
+    // Creating the `String`s allocated, so only count the reordering.
+    let concat = |v: &[String]| v.iter().map(|s| s.as_bytes()[0]).collect::<Vec<_>>();
+    let expected = concat(&vec);
+
+    GLOBAL.assert_no_alloc(|| {
+        reorder::rotate_left(&mut vec, 3);
+        reorder::rotate_right(&mut vec, 3);
+        reorder::reverse(&mut vec);
+        reorder::reverse(&mut vec);
+        reorder::swap(&mut vec, 0, 6);
+        reorder::swap(&mut vec, 6, 0);
+    });
+    assert_eq!(concat(&vec), expected);
+
+    let guard = GLOBAL.guard();
+    let slots = reorder::into_maybeuninit_slice(vec);
+    let delta = guard.delta();
+    // Only the `String`s' own buffers are freed.
+    assert_eq!((delta.allocs, delta.reallocs, delta.deallocs), (0, 0, 7));
+    assert_eq!(slots.len(), 8);
+}
+
+fn main() {
+    assert_eq!(GLOBAL.assert_no_alloc(no_alloc), 9);
+    #[cfg(feature = "nightly")]
+    reorder_no_alloc();
+
+    let guard = GLOBAL.guard();
+    drop(std::hint::black_box(Box::new(5u8)));
+    let delta = guard.delta();
+    assert_eq!((delta.allocs, delta.reallocs, delta.deallocs), (1, 0, 1));
+
+    // Silence the default hook, which would print the expected panic.
+    panic::set_hook(Box::new(|_| {}));
+    let result = panic::catch_unwind(|| {
+        GLOBAL.assert_no_alloc(|| std::hint::black_box(Box::new(5u8)));
+    });
+    drop(panic::take_hook());
+    let payload = result.unwrap_err();
+    let message = payload.downcast_ref::<String>().unwrap();
+    assert_eq!(
+        message,
+        "unexpected allocator calls: 1 allocs, 0 reallocs, 0 deallocs"
+    );
+}
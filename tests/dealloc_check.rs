@@ -0,0 +1,124 @@
+//! Triggers each failure mode of `testing::DeallocCheck`.
+#![cfg(all(feature = "testing", feature = "nightly"))]
+#![feature(allocator_api)]
+
+use std::{
+    alloc::{Allocator, Global, Layout},
+    mem::MaybeUninit,
+    ptr::NonNull,
+};
+
+use noop_allocator::{
+    bump::BumpAllocator,
+    testing::{DeallocCheck, FnAllocator},
+};
+
+#[test]
+fn accepts_sizes_up_to_returned_length() {
+    let mut buf = [MaybeUninit::uninit(); 256];
+    let bump = BumpAllocator::new(&mut buf);
+    // Returns twice the requested size.
+    let doubling = unsafe {
+        FnAllocator::new(|layout: Layout| {
+            bump.allocate(Layout::from_size_align(layout.size() * 2, layout.align()).unwrap())
+        })
+    };
+    let check = DeallocCheck::<_>::new(&doubling).assert_empty_on_drop();
+    let ptr = check.allocate(Layout::new::<[u8; 8]>()).unwrap();
+    assert_eq!(ptr.len(), 16);
+    unsafe { check.deallocate(ptr.cast(), Layout::new::<[u8; 12]>()) };
+    assert_eq!(check.live(), 0);
+}
+
+#[test]
+fn zero_sized_allocations_share_addresses() {
+    let check = DeallocCheck::<_>::new(Global).assert_empty_on_drop();
+    let empty = Layout::new::<()>();
+    let empty_u16 = Layout::new::<[u16; 0]>();
+    let [a, b, c] =
+        [empty, empty, empty_u16].map(|layout| check.allocate(layout).unwrap().cast::<u8>());
+    assert_eq!(check.live(), 3);
+    unsafe {
+        check.deallocate(c, empty_u16);
+        check.deallocate(a, empty);
+        check.deallocate(b, empty);
+    }
+}
+
+#[test]
+#[should_panic(expected = "but it was allocated with")]
+fn wrong_alignment() {
+    let check = DeallocCheck::<_>::new(Global);
+    let ptr = check.allocate(Layout::new::<u64>()).unwrap();
+    unsafe { check.deallocate(ptr.cast(), Layout::new::<[u32; 2]>()) };
+}
+
+#[test]
+#[should_panic(expected = "but it was allocated with")]
+fn wrong_size() {
+    let check = DeallocCheck::<_>::new(Global);
+    let ptr = check.allocate(Layout::new::<[u8; 8]>()).unwrap();
+    unsafe { check.deallocate(ptr.cast(), Layout::new::<[u8; 9]>()) };
+}
+
+#[test]
+#[should_panic(expected = "not currently allocated (double free?)")]
+fn double_free() {
+    let check = DeallocCheck::<_>::new(Global);
+    let layout = Layout::new::<u32>();
+    let ptr = check.allocate(layout).unwrap().cast();
+    unsafe {
+        check.deallocate(ptr, layout);
+        check.deallocate(ptr, layout);
+    }
+}
+
+#[test]
+#[should_panic(expected = "grow of")]
+fn grow_unknown_pointer() {
+    let check = DeallocCheck::<_>::new(Global);
+    let mut slot = 0u32;
+    let layout = Layout::new::<u32>();
+    unsafe {
+        let _ = check.grow(
+            NonNull::from(&mut slot).cast(),
+            layout,
+            Layout::new::<u64>(),
+        );
+    }
+}
+
+#[test]
+#[should_panic(expected = "grow_zeroed of")]
+fn grow_zeroed_with_wrong_layout() {
+    let check = DeallocCheck::<_>::new(Global);
+    let ptr = check.allocate(Layout::new::<[u16; 2]>()).unwrap().cast();
+    unsafe {
+        let _ = check.grow_zeroed(ptr, Layout::new::<[u16; 4]>(), Layout::new::<[u16; 8]>());
+    }
+}
+
+#[test]
+#[should_panic(expected = "shrink of")]
+fn shrink_with_wrong_layout() {
+    let check = DeallocCheck::<_>::new(Global);
+    let ptr = check.allocate(Layout::new::<[u16; 8]>()).unwrap().cast();
+    unsafe {
+        let _ = check.shrink(ptr, Layout::new::<[u16; 4]>(), Layout::new::<[u16; 2]>());
+    }
+}
+
+#[test]
+#[should_panic(expected = "more than 2 live allocations")]
+fn table_full() {
+    let check = DeallocCheck::<_, 2>::new(Global);
+    let _boxes = [(); 3].map(|()| Box::new_in(0u8, &check));
+}
+
+#[test]
+#[should_panic(expected = "dropped with 1 live allocations")]
+fn leak_on_drop() {
+    let check = DeallocCheck::<_>::new(Global).assert_empty_on_drop();
+    std::mem::forget(Box::new_in(0u8, &check));
+    drop(check);
+}
@@ -0,0 +1,95 @@
+#![cfg(all(feature = "alloc", feature = "nightly"))]
+
+use std::{cell::Cell, mem::MaybeUninit};
+
+use noop_allocator::{dst_owning_ref, owning_ref::DstBuilder};
+
+dst_owning_ref! {
+    #[derive(Debug)]
+    struct Padded {
+        tag: u8,
+        values: [u64],
+    }
+}
+
+#[derive(Debug)]
+struct Counted<'c>(&'c Cell<usize>);
+
+impl Drop for Counted<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+dst_owning_ref! {
+    struct CountedDst<'c> {
+        header: Counted<'c>,
+        items: [Counted<'c>],
+    }
+}
+
+#[test]
+fn items_follow_header_padding() {
+    let mut buf = [MaybeUninit::<u64>::uninit(); 5];
+    // Start one byte into the buffer, so the builder has to realign.
+    let bytes = unsafe {
+        std::slice::from_raw_parts_mut(buf.as_mut_ptr().cast::<MaybeUninit<u8>>().add(1), 39)
+    };
+    let mut builder = DstBuilder::new(bytes, 0xAAu8).unwrap();
+    // 39 bytes from offset 1 leave 32 aligned bytes: the tag, padding, and 3 items.
+    assert_eq!(builder.capacity(), 3);
+    for i in 1..=3 {
+        builder.push(i * 100).unwrap();
+    }
+    assert_eq!(builder.push(400), Err(400));
+    let padded = builder.finish::<Padded>();
+    assert_eq!(
+        (&raw const *padded).cast::<u8>(),
+        buf.as_ptr().wrapping_add(1).cast()
+    );
+    assert_eq!(padded.tag, 0xAA);
+    assert_eq!(padded.values, [100, 200, 300]);
+    assert_eq!(std::mem::size_of_val(&*padded), 32);
+}
+
+#[test]
+fn header_that_does_not_fit_is_returned() {
+    let mut buf = [MaybeUninit::<u8>::uninit(); 4];
+    let err = DstBuilder::<u64, u8>::new(&mut buf, 5).unwrap_err();
+    assert_eq!(err, 5);
+}
+
+#[test]
+fn finished_value_drops_header_and_items() {
+    let drops = Cell::new(0);
+    let mut buf = [MaybeUninit::uninit(); 64];
+    let mut builder = DstBuilder::new(&mut buf, Counted(&drops)).unwrap();
+    builder.push(Counted(&drops)).ok().unwrap();
+    builder.push(Counted(&drops)).ok().unwrap();
+    let value = builder.finish::<CountedDst<'_>>();
+    assert_eq!(value.items.len(), 2);
+    assert_eq!(drops.get(), 0);
+    drop(value);
+    assert_eq!(drops.get(), 3);
+}
+
+#[test]
+fn unfinished_builder_drops_what_was_written() {
+    let drops = Cell::new(0);
+    let mut buf = [MaybeUninit::uninit(); 64];
+    let mut builder = DstBuilder::<_, Counted<'_>>::new(&mut buf, Counted(&drops)).unwrap();
+    builder.push(Counted(&drops)).ok().unwrap();
+    drop(builder);
+    assert_eq!(drops.get(), 2);
+}
+
+#[test]
+fn zero_sized_items_never_run_out() {
+    let mut buf = [MaybeUninit::uninit(); 2];
+    let mut builder = DstBuilder::<u16, ()>::new(&mut buf, 1).unwrap();
+    assert_eq!(builder.capacity(), usize::MAX);
+    for _ in 0..1000 {
+        builder.push(()).unwrap();
+    }
+    assert_eq!(builder.len(), 1000);
+}
@@ -0,0 +1,57 @@
+//! Exhaustive allocation-failure tests using `testing::FailAfterN`.
+#![cfg(all(feature = "testing", feature = "nightly"))]
+#![feature(allocator_api)]
+
+use std::{alloc::Global, collections::TryReserveError};
+
+use noop_allocator::{buffer::AlignedBuffer, spill::SpillAllocator, testing};
+
+fn collect_in<A: std::alloc::Allocator>(n: u64, alloc: A) -> Result<Vec<u64, A>, TryReserveError> {
+    let mut v = Vec::new_in(alloc);
+    for i in 0..n {
+        v.try_reserve(1)?;
+        v.push(i);
+    }
+    Ok(v)
+}
+
+#[test]
+fn vec_every_failure_point() {
+    let (allocations, v) = testing::exhaust_failures(Global, |alloc| {
+        let v = collect_in(100, alloc)?;
+        assert_eq!(alloc.failures_triggered(), 0);
+        Ok::<_, TryReserveError>(v.to_vec())
+    });
+    // Capacity 4, 8, 16, 32, 64, 128.
+    assert_eq!(allocations, 6);
+    assert!(v.iter().copied().eq(0..100));
+}
+
+#[test]
+fn spill_fallback_every_failure_point() {
+    let mut buf = AlignedBuffer::<72, 8>::uninit();
+    let (allocations, v) = testing::exhaust_failures(Global, |alloc| {
+        let spill = SpillAllocator::new_in(buf.as_uninit_bytes(), alloc);
+        let v = collect_in(100, &spill)?;
+        assert_eq!(spill.buffer_remaining(), 72);
+        Ok::<_, TryReserveError>(v.to_vec())
+    });
+    // Capacity 4 and 8 fit in the buffer (after its 8-byte header); 16, 32,
+    // 64, and 128 spill.
+    assert_eq!(allocations, 4);
+    assert!(v.iter().copied().eq(0..100));
+}
+
+#[test]
+fn spill_buffer_is_reusable_after_failure() {
+    let mut buf = AlignedBuffer::<72, 8>::uninit();
+    let fail = testing::FailAfterN::new(Global, 0);
+    let spill = SpillAllocator::new_in(buf.as_uninit_bytes(), &fail);
+    assert!(collect_in(100, &spill).is_err());
+    assert_eq!(fail.failures_triggered(), 1);
+    assert_eq!(spill.buffer_remaining(), 72);
+    let v = collect_in(8, &spill).unwrap();
+    assert_eq!(spill.buffer_remaining(), 0);
+    drop(v);
+    assert_eq!(fail.calls(), 1);
+}
@@ -0,0 +1,41 @@
+#![cfg(all(feature = "alloc", feature = "nightly"))]
+
+use std::{
+    any::Any,
+    mem::{align_of, size_of},
+};
+
+use noop_allocator::{NoopAllocator, owning_ref::OwningRef, owning_slice::OwningSlice};
+
+#[test]
+fn noop_allocator_is_zero_sized() {
+    assert_eq!(size_of::<NoopAllocator<'_>>(), 0);
+    assert_eq!(align_of::<NoopAllocator<'_>>(), 1);
+}
+
+#[test]
+fn owning_ref_is_box_sized() {
+    assert_eq!(size_of::<OwningRef<'_, u8>>(), size_of::<usize>());
+    assert_eq!(size_of::<OwningRef<'_, u8>>(), size_of::<Box<u8>>());
+    assert_eq!(size_of::<OwningRef<'_, [u32]>>(), 2 * size_of::<usize>());
+    assert_eq!(size_of::<OwningRef<'_, [u32]>>(), size_of::<Box<[u32]>>());
+    assert_eq!(size_of::<OwningRef<'_, str>>(), size_of::<Box<str>>());
+    assert_eq!(
+        size_of::<OwningRef<'_, dyn Any>>(),
+        size_of::<Box<dyn Any>>()
+    );
+    assert_eq!(
+        size_of::<Option<OwningRef<'_, [u32]>>>(),
+        size_of::<OwningRef<'_, [u32]>>()
+    );
+}
+
+#[test]
+fn owning_slice_is_vec_sized() {
+    assert_eq!(size_of::<OwningSlice<'_, u8>>(), 3 * size_of::<usize>());
+    assert_eq!(size_of::<OwningSlice<'_, u8>>(), size_of::<Vec<u8>>());
+    assert_eq!(
+        size_of::<OwningSlice<'_, String>>(),
+        size_of::<Vec<String>>()
+    );
+}
@@ -0,0 +1,72 @@
+#![cfg(all(feature = "alloc", feature = "nightly"))]
+#![cfg(any(feature = "portable-atomic", target_has_atomic = "ptr"))]
+
+use std::mem::{MaybeUninit, size_of};
+
+use noop_allocator::owning_ref::{self, LeakCounter, LeakTracked, OwningRef};
+
+#[test]
+#[cfg_attr(
+    not(debug_assertions),
+    ignore = "leaks are only tracked in debug builds"
+)]
+fn forget_counts_as_leak_and_drop_does_not() {
+    let counter = LeakCounter::new();
+    let mut slots = [const { MaybeUninit::<Vec<u8>>::uninit() }; 3];
+    let [a, b, c] = slots.each_mut();
+
+    let a =
+        owning_ref::with_leak_tracking(owning_ref::from_maybeuninit_write(a, vec![1]), &counter);
+    let b =
+        owning_ref::with_leak_tracking(owning_ref::from_maybeuninit_write(b, vec![2]), &counter);
+    let c =
+        owning_ref::with_leak_tracking(owning_ref::from_maybeuninit_write(c, vec![3]), &counter);
+    assert_eq!(
+        (counter.tracked(), counter.dropped(), counter.leaked()),
+        (3, 0, 3)
+    );
+
+    drop(a);
+    assert_eq!(
+        (counter.tracked(), counter.dropped(), counter.leaked()),
+        (3, 1, 2)
+    );
+
+    std::mem::forget(b);
+    assert_eq!(
+        (counter.tracked(), counter.dropped(), counter.leaked()),
+        (3, 1, 2)
+    );
+
+    let c = LeakTracked::into_inner(c);
+    assert_eq!(*c, [3]);
+    assert_eq!(
+        (counter.tracked(), counter.dropped(), counter.leaked()),
+        (2, 1, 1)
+    );
+}
+
+#[test]
+fn tracked_handle_behaves_like_the_box() {
+    let counter = LeakCounter::new();
+    let mut slot = MaybeUninit::uninit();
+    let addr = slot.as_ptr();
+    let mut tracked = owning_ref::with_leak_tracking(
+        owning_ref::from_maybeuninit_write(&mut slot, [1u32, 2]),
+        &counter,
+    );
+    tracked[1] = 5;
+    assert_eq!(*tracked, [1, 5]);
+    assert_eq!(&raw const *tracked, addr);
+    let inner: OwningRef<'_, [u32; 2]> = LeakTracked::into_inner(tracked);
+    assert_eq!(*inner, [1, 5]);
+}
+
+#[test]
+#[cfg_attr(debug_assertions, ignore = "release builds only")]
+fn release_wrapper_is_box_sized() {
+    assert_eq!(
+        size_of::<LeakTracked<'_, '_, [u8]>>(),
+        size_of::<OwningRef<'_, [u8]>>()
+    );
+}
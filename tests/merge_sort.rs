@@ -0,0 +1,82 @@
+#![cfg(all(feature = "alloc", feature = "nightly"))]
+
+use std::{cell::Cell, mem::MaybeUninit};
+
+use noop_allocator::owning_slice;
+
+#[derive(Debug)]
+struct Counted<'c> {
+    key: u32,
+    seq: usize,
+    drops: &'c Cell<usize>,
+}
+
+impl Drop for Counted<'_> {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+    }
+}
+
+fn keys(len: usize) -> impl Iterator<Item = u32> {
+    let mut state = 0x2545_f491_u32;
+    (0..len).map(move |_| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state % 7
+    })
+}
+
+#[test]
+fn stable_for_every_length() {
+    for len in 0..=40 {
+        let drops = Cell::new(0);
+        let mut buf = [const { MaybeUninit::uninit() }; 40];
+        let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+        vec.extend(keys(len).enumerate().map(|(seq, key)| Counted {
+            key,
+            seq,
+            drops: &drops,
+        }));
+        let mut scratch = [const { MaybeUninit::uninit() }; 40];
+        owning_slice::merge_sort_with(&mut vec, &mut scratch[..len], |a, b| a.key.cmp(&b.key))
+            .unwrap();
+        assert_eq!(vec.len(), len);
+        assert!(
+            vec.windows(2)
+                .all(|w| (w[0].key, w[0].seq) < (w[1].key, w[1].seq))
+        );
+        // Nothing was dropped or duplicated through the scratch buffer.
+        assert_eq!(drops.get(), 0);
+        drop(vec);
+        assert_eq!(drops.get(), len);
+    }
+}
+
+#[test]
+fn panicking_cmp_keeps_every_element() {
+    let drops = Cell::new(0);
+    let mut buf = [const { MaybeUninit::uninit() }; 32];
+    let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+    vec.extend(keys(32).enumerate().map(|(seq, key)| Counted {
+        key,
+        seq,
+        drops: &drops,
+    }));
+    let mut scratch = [const { MaybeUninit::uninit() }; 32];
+    let mut calls = 0;
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        owning_slice::merge_sort_with(&mut vec, &mut scratch, |a, b| {
+            calls += 1;
+            assert!(calls < 60);
+            a.key.cmp(&b.key)
+        })
+    }));
+    assert!(result.is_err());
+    let mut seqs: Vec<usize> = vec.iter().map(|c| c.seq).collect();
+    seqs.sort();
+    assert_eq!(seqs, (0..32).collect::<Vec<_>>());
+    assert_eq!(drops.get(), 0);
+    drop(vec);
+    assert_eq!(drops.get(), 32);
+}
@@ -0,0 +1,101 @@
+//! Pointer-identity checks for `OwningRef`s, meant to be run under Miri as
+//! well as natively:
+//!
+//! ```text
+//! cargo +nightly miri test --test miri
+//! ```
+//!
+//! Every `OwningRef` must point at the slot it was created from, for as long
+//! as it lives. Natively, these tests catch a box construction that copies the
+//! value elsewhere; under Miri, accessing the slot through a pointer derived
+//! from the original borrow also checks that the `OwningRef` did not lose the
+//! slot's provenance.
+#![cfg(all(feature = "alloc", feature = "nightly"))]
+#![feature(allocator_api)]
+
+use std::mem::{ManuallyDrop, MaybeUninit};
+
+use noop_allocator::owning_ref::{self, OwningRef};
+
+fn data_ptr<T: ?Sized>(b: &OwningRef<'_, T>) -> *const u8 {
+    (&raw const **b).cast()
+}
+
+#[test]
+fn from_maybeuninit_points_at_slot() {
+    let mut slot = MaybeUninit::new([1u64, 2, 3, 4]);
+    let addr = slot.as_ptr().cast::<u8>();
+    let mut b = unsafe { owning_ref::from_maybeuninit(&mut slot) };
+    assert_eq!(data_ptr(&b), addr);
+
+    b[0] = 10;
+    assert_eq!(data_ptr(&b), addr);
+    b.reverse();
+    assert_eq!(data_ptr(&b), addr);
+    assert_eq!(b.iter().sum::<u64>(), 19);
+    assert_eq!(data_ptr(&b), addr);
+
+    let raw = OwningRef::into_raw_with_allocator(b).0;
+    assert_eq!(raw.cast::<u8>().cast_const(), addr);
+    let b = unsafe { OwningRef::from_raw_in(raw, noop_allocator::NoopAllocator::new()) };
+    assert_eq!(*b, [4, 3, 2, 10]);
+    drop(b);
+}
+
+#[test]
+fn from_maybeuninit_write_through_slot_pointer() {
+    let mut slot = MaybeUninit::<u32>::uninit();
+    let slot_ptr = slot.as_mut_ptr();
+    let mut b = owning_ref::from_maybeuninit_write(&mut slot, 1);
+    assert_eq!(data_ptr(&b).cast::<u32>(), slot_ptr.cast_const());
+
+    // Write through the box, then read the same address back through it:
+    // under Miri this is only allowed if the box's pointer is the slot's.
+    *b += 1;
+    let through_box = &raw mut *b;
+    assert_eq!(through_box, slot_ptr);
+    unsafe { through_box.write(*through_box * 10) };
+    assert_eq!(*b, 20);
+}
+
+#[test]
+fn from_manuallydrop_points_at_slot() {
+    let mut slot = ManuallyDrop::new(String::from("hello"));
+    let addr = (&raw const slot).cast::<u8>();
+    let mut b = unsafe { owning_ref::from_manuallydrop(&mut slot) };
+    assert_eq!(data_ptr(&b), addr);
+
+    b.push_str(", world");
+    assert_eq!(data_ptr(&b), addr);
+    b.shrink_to_fit();
+    assert_eq!(data_ptr(&b), addr);
+    // The `String`'s heap buffer may move, but the `String` itself does not.
+    assert_eq!(*b, "hello, world");
+    drop(b);
+}
+
+#[test]
+fn unsized_from_manuallydrop_points_at_slot() {
+    let mut slot = ManuallyDrop::new([1u8, 2, 3]);
+    let addr = (&raw const slot).cast::<u8>();
+    let b: OwningRef<'_, [u8]> = unsafe { owning_ref::from_manuallydrop(&mut slot) };
+    assert_eq!(data_ptr(&b), addr);
+    assert_eq!(b.len(), 3);
+}
+
+#[test]
+fn moving_the_box_does_not_move_the_value() {
+    let mut slots = [const { MaybeUninit::<[u8; 64]>::uninit() }; 2];
+    let addrs = slots.each_ref().map(|s| s.as_ptr().cast::<u8>());
+    let [a, b] = slots.each_mut();
+    let a = owning_ref::from_maybeuninit_write(a, [0xAA; 64]);
+    let b = owning_ref::from_maybeuninit_write(b, [0xBB; 64]);
+
+    let mut boxes = vec![b, a];
+    boxes.swap(0, 1);
+    let moved = boxes.pop().unwrap();
+    assert_eq!(data_ptr(&boxes[0]), addrs[0]);
+    assert_eq!(data_ptr(&moved), addrs[1]);
+    assert!(boxes[0].iter().all(|&x| x == 0xAA));
+    assert!(moved.iter().all(|&x| x == 0xBB));
+}
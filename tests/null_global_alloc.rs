@@ -0,0 +1,95 @@
+//! Runs the allocation-free APIs with `NullGlobalAlloc` as the global
+//! allocator.
+//!
+//! The `std` runtime allocates before `main` is called, so the global allocator
+//! here forwards to `System` until `main` arms it, and to `NullGlobalAlloc`
+//! afterwards. This uses a custom `main` (`harness = false`), since the default
+//! test harness allocates.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    mem::MaybeUninit,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use noop_allocator::global::NullGlobalAlloc;
+
+struct ArmedNull(AtomicBool);
+
+unsafe impl GlobalAlloc for ArmedNull {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if self.0.load(Ordering::Relaxed) {
+            NullGlobalAlloc.alloc(layout)
+        } else {
+            System.alloc(layout)
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // Allocations made after arming never succeed, so `ptr` is from
+        // `System`.
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if self.0.load(Ordering::Relaxed) {
+            NullGlobalAlloc.realloc(ptr, layout, new_size)
+        } else {
+            System.realloc(ptr, layout, new_size)
+        }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: ArmedNull = ArmedNull(AtomicBool::new(false));
+
+#[cfg(feature = "nightly")]
+fn exercise() {
+    use noop_allocator::{owning_ref, owning_slice};
+
+    let mut slot = MaybeUninit::uninit();
+    let boxed = owning_ref::from_maybeuninit_write(&mut slot, [1u32, 2, 3]);
+    assert_eq!(boxed.iter().sum::<u32>(), 6);
+    drop(boxed);
+
+    let mut buf = [MaybeUninit::<u64>::uninit(); 8];
+    let mut vec = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+    vec.extend(0..8);
+    vec.retain(|x| x % 2 == 0);
+    vec.truncate(3);
+    vec.shrink_to_fit();
+    assert_eq!(vec, [0, 2, 4]);
+    assert!(vec.try_reserve(8).is_err());
+    drop(vec);
+}
+
+#[cfg(not(feature = "nightly"))]
+fn exercise() {
+    use noop_allocator::stable::{StableOwningRef, StableOwningSlice};
+
+    let mut slot = MaybeUninit::uninit();
+    let boxed = StableOwningRef::from_maybeuninit_write(&mut slot, [1u32, 2, 3]);
+    assert_eq!(boxed.iter().sum::<u32>(), 6);
+    drop(boxed);
+
+    let mut buf = [MaybeUninit::<u64>::uninit(); 8];
+    let mut vec = StableOwningSlice::empty_from_maybeuninit_slice(&mut buf);
+    for i in 0..8 {
+        vec.push(i).unwrap();
+    }
+    assert_eq!(vec.push(8), Err(8));
+    vec.truncate(3);
+    assert_eq!(*vec, [0, 1, 2]);
+    drop(vec);
+}
+
+fn main() {
+    GLOBAL.0.store(true, Ordering::Relaxed);
+
+    exercise();
+
+    let mut v: Vec<u8> = Vec::new();
+    assert!(v.try_reserve(1).is_err());
+
+    GLOBAL.0.store(false, Ordering::Relaxed);
+}
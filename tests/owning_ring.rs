@@ -0,0 +1,84 @@
+//! Boundary conditions of `OwningRing`, checked against `VecDeque`.
+
+use std::{collections::VecDeque, mem::MaybeUninit};
+
+use noop_allocator::owning_ring::OwningRing;
+
+#[test]
+fn zero_capacity() {
+    let mut buf: [MaybeUninit<String>; 0] = [];
+    let mut ring = OwningRing::new(&mut buf);
+    assert!(ring.is_empty() && ring.is_full());
+    assert_eq!(ring.push_back("a".into()), Err("a".into()));
+    assert_eq!(ring.push_front("b".into()), Err("b".into()));
+    assert_eq!(ring.pop_front(), None);
+    assert_eq!(ring.pop_back(), None);
+    assert_eq!(ring.front(), None);
+    assert_eq!(ring.back(), None);
+}
+
+#[test]
+fn capacity_one() {
+    let mut buf = [const { MaybeUninit::uninit() }; 1];
+    let mut ring = OwningRing::new(&mut buf);
+    for i in 0..4 {
+        ring.push_front(i).unwrap();
+        assert!(ring.is_full());
+        assert_eq!(ring.push_back(10), Err(10));
+        assert_eq!((ring.front(), ring.back()), (Some(&i), Some(&i)));
+        assert_eq!(ring.pop_back(), Some(i));
+        assert!(ring.is_empty());
+    }
+}
+
+#[test]
+fn zero_sized() {
+    let mut buf = [MaybeUninit::<()>::uninit(); 2];
+    let mut ring = OwningRing::new(&mut buf);
+    assert_eq!(ring.push_back(()), Ok(()));
+    assert_eq!(ring.push_front(()), Ok(()));
+    assert_eq!(ring.push_back(()), Err(()));
+    assert_eq!(ring.iter().count(), 2);
+}
+
+#[test]
+fn matches_vec_deque() {
+    let mut buf = [const { MaybeUninit::uninit() }; 5];
+    let mut ring = OwningRing::new(&mut buf);
+    let mut model = VecDeque::new();
+    // A fixed pseudo-random sequence of operations.
+    let mut state = 12345_u32;
+    for step in 0..1000 {
+        state = state.wrapping_mul(1103515245).wrapping_add(12345);
+        match state >> 29 {
+            0 | 1 => {
+                let full = model.len() == 5;
+                assert_eq!(ring.push_back(step).is_err(), full);
+                if !full {
+                    model.push_back(step);
+                }
+            }
+            2 | 3 => {
+                let full = model.len() == 5;
+                assert_eq!(ring.push_front(step).is_err(), full);
+                if !full {
+                    model.push_front(step);
+                }
+            }
+            4 | 5 => assert_eq!(ring.pop_front(), model.pop_front()),
+            6 => assert_eq!(ring.pop_back(), model.pop_back()),
+            _ => {
+                let middle = model.len() / 2;
+                if let Some(x) = ring.get_mut(middle) {
+                    *x += 1;
+                    model[middle] += 1;
+                }
+            }
+        }
+        assert!(ring.iter().eq(&model));
+        assert!(ring.iter().rev().eq(model.iter().rev()));
+        assert_eq!(ring.len(), model.len());
+    }
+    ring.clear();
+    assert!(ring.is_empty());
+}
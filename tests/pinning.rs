@@ -0,0 +1,58 @@
+//! Checks that `PinningAllocator` never moves a `Vec`'s buffer.
+#![cfg(feature = "nightly")]
+#![feature(allocator_api)]
+
+use std::alloc::Global;
+
+use noop_allocator::{pinning::PinningAllocator, quota::QuotaAllocator};
+
+#[test]
+fn vec_address_is_stable() {
+    let quota = QuotaAllocator::new(Global, 4096);
+    let alloc = PinningAllocator::new(&quota);
+    let mut v = Vec::<u16, _>::with_capacity_in(100, &alloc);
+    let addr = v.as_ptr();
+
+    v.extend(0..50);
+    v.retain(|x| x % 3 != 0);
+    v.extend(0..10);
+    v.insert(0, 7);
+    v.swap_remove(5);
+    v.dedup();
+    assert!(v.try_reserve(v.capacity() - v.len()).is_ok());
+    assert!(v.try_reserve(v.capacity() - v.len() + 1).is_err());
+    assert_eq!(v.as_ptr(), addr);
+
+    v.shrink_to(60);
+    assert_eq!(v.capacity(), 60);
+    v.shrink_to_fit();
+    assert_eq!(v.capacity(), v.len());
+    assert_eq!(v.as_ptr(), addr);
+
+    let boxed = v.into_boxed_slice();
+    assert_eq!(boxed.as_ptr(), addr);
+    drop(boxed);
+    assert_eq!(quota.used(), 0);
+}
+
+#[test]
+fn over_aligned_and_zero_sized() {
+    #[repr(align(64))]
+    struct Page([u8; 64]);
+
+    let quota = QuotaAllocator::new(Global, 4096);
+    let alloc = PinningAllocator::new(&quota);
+
+    let mut pages = Vec::with_capacity_in(4, &alloc);
+    pages.extend((0..3).map(|n| Page([n; 64])));
+    assert_eq!(pages.as_ptr() as usize % 64, 0);
+    let addr = pages.as_ptr();
+    pages.shrink_to_fit();
+    assert_eq!(pages.as_ptr(), addr);
+    assert_eq!(pages[2].0, [2; 64]);
+
+    let unit = Box::new_in((), &alloc);
+    let empty = Vec::<u64, _>::with_capacity_in(0, &alloc);
+    drop((pages, unit, empty));
+    assert_eq!(quota.used(), 0);
+}
@@ -0,0 +1,94 @@
+//! Checks that `QuotaAllocator`'s count matches the live allocations.
+#![cfg(feature = "nightly")]
+#![feature(allocator_api)]
+
+use std::{alloc::Global, mem::MaybeUninit};
+
+use noop_allocator::{bump::BumpAllocator, quota::QuotaAllocator};
+
+#[test]
+fn mixed_workload() {
+    let quota = QuotaAllocator::new(Global, 1024);
+    let mut boxes = Vec::new();
+    let mut v = Vec::<u32, _>::new_in(&quota);
+    let live =
+        |boxes: &Vec<Box<[u8; 100], _>>, v: &Vec<u32, _>| boxes.len() * 100 + v.capacity() * 4;
+
+    for round in 0..3 {
+        // Alternate between growing the `Vec` and adding boxes until both fail.
+        loop {
+            let grew = v.try_reserve(v.capacity() + 1).is_ok();
+            let boxed = Box::try_new_in([round; 100], &quota)
+                .map(|b| boxes.push(b))
+                .is_ok();
+            assert_eq!(quota.used(), live(&boxes, &v));
+            assert!(quota.used() <= quota.limit());
+            if !grew && !boxed {
+                break;
+            }
+        }
+        assert!(quota.remaining() < 100);
+
+        // Free every other box and shrink the `Vec`, then go again.
+        let mut keep = false;
+        boxes.retain(|_| {
+            keep = !keep;
+            keep
+        });
+        v.truncate(v.len() / 2);
+        v.shrink_to_fit();
+        assert_eq!(quota.used(), live(&boxes, &v));
+    }
+
+    drop(boxes);
+    drop(v);
+    assert_eq!(quota.used(), 0);
+}
+
+/// The bytes a `BumpAllocator` adds around each allocation: with the `canary`
+/// feature, a `[usize; 3]` header and the default redzone.
+#[cfg(feature = "canary")]
+const OVERHEAD: usize = 3 * std::mem::size_of::<usize>() + noop_allocator::bump::DEFAULT_REDZONE;
+#[cfg(not(feature = "canary"))]
+const OVERHEAD: usize = 0;
+
+#[test]
+fn inner_failures_are_not_counted() {
+    // Room for three 64-byte allocations, and 64 bytes to spare.
+    let mut buf = [MaybeUninit::<u8>::uninit(); 3 * (64 + OVERHEAD) + 64];
+    let bump = BumpAllocator::new(&mut buf);
+    let quota = QuotaAllocator::new(&bump, usize::MAX);
+
+    let a = Box::new_in([0u8; 64], &quota);
+    let mut v = Vec::<u8, _>::with_capacity_in(64, &quota);
+    let b = Box::new_in([0u8; 64], &quota);
+    assert_eq!(quota.used(), 192);
+
+    // `v` is not the last allocation, so growing it needs a new block, which
+    // does not fit.
+    assert!(v.try_reserve_exact(128).is_err());
+    assert_eq!(quota.used(), 192);
+    assert!(Box::try_new_in([0u8; 128], &quota).is_err());
+    assert_eq!(quota.used(), 192);
+
+    drop((a, v, b));
+    assert_eq!(quota.used(), 0);
+}
+
+#[test]
+fn shared_between_threads() {
+    let quota = QuotaAllocator::new(Global, 4096);
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                for n in 0..200 {
+                    let mut v = Vec::<u64, _>::new_in(&quota);
+                    let _ = v.try_reserve_exact(n % 50);
+                    let _ = Box::try_new_in([0u8; 256], &quota);
+                    assert!(quota.used() <= quota.limit());
+                }
+            });
+        }
+    });
+    assert_eq!(quota.used(), 0);
+}
@@ -0,0 +1,124 @@
+//! Checks how `Rc`s allocated in borrowed buffers count references and drop
+//! their values, including in reference cycles.
+#![cfg(feature = "nightly")]
+#![feature(allocator_api)]
+
+use std::{
+    cell::{Cell, RefCell},
+    mem::MaybeUninit,
+    rc::{Rc, Weak},
+};
+
+use noop_allocator::bump::BumpAllocator;
+
+struct Node<'d, 'b> {
+    drops: &'d Cell<usize>,
+    next: RefCell<Option<Rc<Node<'d, 'b>, &'b BumpAllocator<'b>>>>,
+    back: RefCell<Option<Weak<Node<'d, 'b>, &'b BumpAllocator<'b>>>>,
+}
+
+impl<'d, 'b> Node<'d, 'b> {
+    fn new_in(
+        drops: &'d Cell<usize>,
+        bump: &'b BumpAllocator<'b>,
+    ) -> Rc<Self, &'b BumpAllocator<'b>> {
+        Rc::new_in(
+            Node {
+                drops,
+                next: RefCell::new(None),
+                back: RefCell::new(None),
+            },
+            bump,
+        )
+    }
+}
+
+impl Drop for Node<'_, '_> {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+    }
+}
+
+#[test]
+fn counts_across_clone_and_drop() {
+    let mut buf = [MaybeUninit::uninit(); 1024];
+    let bump = BumpAllocator::new(&mut buf);
+    let drops = Cell::new(0);
+
+    let a = Node::new_in(&drops, &bump);
+    assert_eq!((Rc::strong_count(&a), Rc::weak_count(&a)), (1, 0));
+    let b = Rc::clone(&a);
+    let w = Rc::downgrade(&a);
+    assert_eq!((Rc::strong_count(&a), Rc::weak_count(&a)), (2, 1));
+    drop(b);
+    assert_eq!((Rc::strong_count(&a), Rc::weak_count(&a)), (1, 1));
+    assert!(w.upgrade().is_some());
+
+    drop(a);
+    assert_eq!(drops.get(), 1);
+    assert_eq!(w.strong_count(), 0);
+    assert!(w.upgrade().is_none());
+}
+
+#[test]
+fn try_unwrap_and_get_mut() {
+    let mut buf = [MaybeUninit::uninit(); 256];
+    let bump = BumpAllocator::new(&mut buf);
+
+    let mut a = Rc::new_in(String::from("a"), &bump);
+    Rc::get_mut(&mut a).unwrap().push('b');
+    let b = Rc::clone(&a);
+    assert!(Rc::get_mut(&mut a).is_none());
+    let a = Rc::try_unwrap(a).unwrap_err();
+    drop(b);
+    assert_eq!(Rc::try_unwrap(a).unwrap(), "ab");
+}
+
+#[test]
+fn cycles_skip_destructors() {
+    let mut buf = [MaybeUninit::uninit(); 1024];
+    let bump = BumpAllocator::new(&mut buf);
+    let drops = Cell::new(0);
+
+    let a = Node::new_in(&drops, &bump);
+    let b = Node::new_in(&drops, &bump);
+    *a.next.borrow_mut() = Some(Rc::clone(&b));
+    *b.next.borrow_mut() = Some(Rc::clone(&a));
+    assert_eq!(Rc::strong_count(&a), 2);
+    drop((a, b));
+    // Each node is kept alive by the other, so neither is ever dropped.
+    assert_eq!(drops.get(), 0);
+}
+
+#[test]
+fn weak_back_edges_break_cycles() {
+    let mut buf = [MaybeUninit::uninit(); 1024];
+    let bump = BumpAllocator::new(&mut buf);
+    let drops = Cell::new(0);
+
+    let a = Node::new_in(&drops, &bump);
+    let b = Node::new_in(&drops, &bump);
+    *a.next.borrow_mut() = Some(Rc::clone(&b));
+    *b.back.borrow_mut() = Some(Rc::downgrade(&a));
+    drop(b);
+    assert_eq!(drops.get(), 0);
+    drop(a);
+    assert_eq!(drops.get(), 2);
+}
+
+#[test]
+fn clearing_a_cycle_drops_it() {
+    let mut buf = [MaybeUninit::uninit(); 1024];
+    let bump = BumpAllocator::new(&mut buf);
+    let drops = Cell::new(0);
+
+    let a = Node::new_in(&drops, &bump);
+    let b = Node::new_in(&drops, &bump);
+    *a.next.borrow_mut() = Some(Rc::clone(&b));
+    *b.next.borrow_mut() = Some(Rc::clone(&a));
+    drop(b);
+    a.next.borrow_mut().take();
+    assert_eq!(drops.get(), 1);
+    drop(a);
+    assert_eq!(drops.get(), 2);
+}
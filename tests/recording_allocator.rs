@@ -0,0 +1,109 @@
+//! Checks the exact log `testing::RecordingAllocator` produces for a scripted
+//! `Vec` workload.
+#![cfg(all(feature = "testing", feature = "nightly"))]
+#![feature(allocator_api)]
+
+use std::mem::MaybeUninit;
+
+use noop_allocator::{
+    buffer::AlignedBuffer,
+    stack::StackAllocator,
+    testing::{Record, RecordOp, RecordingAllocator},
+};
+
+/// The size of `StackAllocator`'s per-allocation header.
+const HEADER: usize = size_of::<usize>();
+
+#[test]
+fn scripted_vec_workload() {
+    let mut buf = AlignedBuffer::<256, 8>::uninit();
+    let base = buf.as_ptr() as usize;
+    let stack = StackAllocator::new(buf.as_uninit_bytes());
+    let mut log = [MaybeUninit::uninit(); 16];
+    let alloc = RecordingAllocator::new(&stack, &mut log);
+
+    let mut v = Vec::<u32, _>::with_capacity_in(4, &alloc);
+    v.extend(0..5);
+    v.truncate(2);
+    v.shrink_to_fit();
+    drop(v);
+
+    let addr = base + HEADER;
+    let record = |seq, op, addr, old_addr, size, old_size| Record {
+        seq,
+        addr,
+        old_addr,
+        size,
+        align: 4,
+        old_size,
+        op,
+        ok: true,
+    };
+    assert_eq!(
+        alloc.records().collect::<Vec<_>>(),
+        [
+            record(0, RecordOp::Allocate, addr, 0, 16, 0),
+            // The `Vec` is the top allocation, so it grows and shrinks in place.
+            record(1, RecordOp::Grow, addr, addr, 32, 16),
+            record(2, RecordOp::Shrink, addr, addr, 8, 32),
+            record(3, RecordOp::Deallocate, addr, 0, 8, 0),
+        ]
+    );
+    assert!(alloc.records().all(|record| !record.moved()));
+}
+
+#[test]
+fn failures_and_moves() {
+    let mut buf = AlignedBuffer::<64, 8>::uninit();
+    let stack = StackAllocator::new(buf.as_uninit_bytes());
+    let mut log = [MaybeUninit::uninit(); 16];
+    let alloc = RecordingAllocator::new(&stack, &mut log);
+
+    let mut v = Vec::<u8, _>::with_capacity_in(8, &alloc);
+    let b = Box::new_in(0_u8, &alloc);
+    // `b` is on top, so growing `v` moves it.
+    v.reserve_exact(16);
+    assert!(v.try_reserve_exact(64).is_err());
+    drop((b, v));
+
+    let records: Vec<Record> = alloc.records().collect();
+    let ops: Vec<(RecordOp, bool, bool)> =
+        records.iter().map(|r| (r.op, r.ok, r.moved())).collect();
+    assert_eq!(
+        ops,
+        [
+            (RecordOp::Allocate, true, false),
+            (RecordOp::Allocate, true, false),
+            (RecordOp::Grow, true, true),
+            (RecordOp::Grow, false, false),
+            (RecordOp::Deallocate, true, false),
+            (RecordOp::Deallocate, true, false),
+        ]
+    );
+    assert_eq!(records[3].addr, 0);
+    assert_eq!(records[3].old_addr, records[2].addr);
+    assert_eq!(records[5].addr, records[2].addr);
+}
+
+#[test]
+fn toggle_and_ring() {
+    let mut buf = [MaybeUninit::uninit(); 256];
+    let stack = StackAllocator::new(&mut buf);
+    let mut log = [MaybeUninit::uninit(); 3];
+    let alloc = RecordingAllocator::new(&stack, &mut log);
+
+    alloc.set_enabled(false);
+    drop(Box::new_in(0_u8, &alloc));
+    assert_eq!(alloc.total(), 0);
+
+    alloc.set_enabled(true);
+    for _ in 0..3 {
+        drop(Box::new_in(0_u8, &alloc));
+    }
+    assert_eq!(alloc.total(), 6);
+    let seqs: Vec<usize> = alloc.records().map(|record| record.seq).collect();
+    assert_eq!(seqs, [3, 4, 5]);
+
+    alloc.clear();
+    assert_eq!(alloc.records().count(), 0);
+}
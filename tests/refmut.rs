@@ -0,0 +1,41 @@
+#![cfg(all(feature = "alloc", feature = "nightly"))]
+
+use std::{
+    cell::{RefCell, RefMut},
+    mem::MaybeUninit,
+};
+
+use noop_allocator::owning_slice;
+
+#[test]
+fn refcell_stays_borrowed_while_slice_lives() {
+    let pool = RefCell::new([MaybeUninit::<u32>::uninit(); 8]);
+    {
+        let mut guard = RefMut::map(pool.borrow_mut(), |slots| &mut slots[2..6]);
+        let mut vec = owning_slice::from_refmut(&mut guard);
+        vec.extend([1, 2, 3, 4]);
+        assert_eq!(vec.capacity(), 4);
+        assert!(pool.try_borrow().is_err());
+        assert!(pool.try_borrow_mut().is_err());
+        drop(vec);
+        // The guard still holds the borrow after the slice is gone.
+        assert!(pool.try_borrow_mut().is_err());
+    }
+    assert!(pool.try_borrow_mut().is_ok());
+}
+
+#[test]
+fn values_are_written_into_the_refcell() {
+    let pool = RefCell::new([MaybeUninit::<u8>::uninit(); 4]);
+    let mut guard = RefMut::map(pool.borrow_mut(), |slots| &mut slots[..]);
+    let vec = owning_slice::from_refmut(&mut guard);
+    let leaked = owning_slice::leak({
+        let mut vec = vec;
+        vec.extend_from_slice(b"cell");
+        vec
+    });
+    assert_eq!(leaked, b"cell");
+    drop(guard);
+    let bytes = pool.borrow().map(|b| unsafe { b.assume_init() });
+    assert_eq!(&bytes, b"cell");
+}
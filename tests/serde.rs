@@ -0,0 +1,55 @@
+//! Checks that the owning types serialize like the equivalent `Box` and `Vec`.
+#![cfg(feature = "serde")]
+
+use std::mem::MaybeUninit;
+
+#[cfg(feature = "nightly")]
+#[test]
+fn owning_ref() {
+    use noop_allocator::{owning_ref, ser::Owning};
+
+    let mut slot = MaybeUninit::uninit();
+    let owned = owning_ref::from_maybeuninit_write(&mut slot, [1.5f32, -2.0]);
+    assert_eq!(
+        serde_json::to_string(&Owning(&owned)).unwrap(),
+        serde_json::to_string(&Box::new([1.5f32, -2.0])).unwrap(),
+    );
+}
+
+#[cfg(feature = "nightly")]
+#[test]
+fn owning_slice() {
+    use noop_allocator::{owning_slice, ser::Owning};
+
+    let mut buf: [MaybeUninit<String>; 4] = [const { MaybeUninit::uninit() }; 4];
+    let mut owned = owning_slice::empty_from_maybeuninit_slice(&mut buf);
+    owned.extend(["a", "b\"c"].map(String::from));
+    assert_eq!(
+        serde_json::to_string(&Owning(&owned)).unwrap(),
+        serde_json::to_string(&vec!["a", "b\"c"]).unwrap(),
+    );
+
+    owned.clear();
+    assert_eq!(serde_json::to_string(&Owning(&owned)).unwrap(), "[]");
+}
+
+#[test]
+fn stable_owning_types() {
+    use noop_allocator::stable::{StableOwningRef, StableOwningSlice};
+
+    let mut slot = MaybeUninit::uninit();
+    let owned = StableOwningRef::from_maybeuninit_write(&mut slot, (1, "one"));
+    assert_eq!(
+        serde_json::to_string(&owned).unwrap(),
+        serde_json::to_string(&Box::new((1, "one"))).unwrap(),
+    );
+
+    let mut buf = [MaybeUninit::uninit(); 4];
+    let mut owned = StableOwningSlice::empty_from_maybeuninit_slice(&mut buf);
+    owned.push(Some(1u8)).unwrap();
+    owned.push(None).unwrap();
+    assert_eq!(
+        serde_json::to_string(&owned).unwrap(),
+        serde_json::to_string(&vec![Some(1u8), None]).unwrap(),
+    );
+}
@@ -0,0 +1,112 @@
+//! Checks that a `Slot` drops each value exactly once, whether it is dropped
+//! by the slot or by an `OwningRef` borrowing it.
+#![cfg(all(feature = "alloc", feature = "nightly"))]
+
+use std::{cell::RefCell, rc::Rc};
+
+use noop_allocator::slot::{FilledSlot, ReusableSlot, Slot};
+
+#[derive(Debug)]
+struct Logged(&'static str, Rc<RefCell<Vec<&'static str>>>);
+
+impl Drop for Logged {
+    fn drop(&mut self) {
+        self.1.borrow_mut().push(self.0);
+    }
+}
+
+fn log() -> (
+    Rc<RefCell<Vec<&'static str>>>,
+    impl Fn(&'static str) -> Logged,
+) {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let make = {
+        let log = log.clone();
+        move |name| Logged(name, log.clone())
+    };
+    (log, make)
+}
+
+#[test]
+fn double_insert() {
+    let (log, make) = log();
+    let mut slot = Slot::new();
+    drop(slot.insert(make("a")));
+    assert_eq!(*log.borrow(), ["a"]);
+    let b = slot.insert(make("b"));
+    assert_eq!(b.0, "b");
+    drop(b);
+    assert_eq!(*log.borrow(), ["a", "b"]);
+
+    slot.replace(make("c"));
+    // Dropped by `insert`, before "d" is written over it.
+    let d = slot.insert(make("d"));
+    assert_eq!(*log.borrow(), ["a", "b", "c"]);
+    drop(d);
+    assert!(!slot.is_occupied());
+    drop(slot);
+    assert_eq!(*log.borrow(), ["a", "b", "c", "d"]);
+}
+
+#[test]
+fn take_then_reinsert() {
+    let (log, make) = log();
+    let mut slot = Slot::filled(make("a"));
+    let a = slot.take().unwrap();
+    assert!(slot.take().is_none());
+    assert!(slot.take_owning().is_none());
+    assert!(log.borrow().is_empty());
+
+    assert!(slot.replace(make("b")).is_none());
+    let b = slot.replace(a).unwrap();
+    assert_eq!(b.0, "b");
+    drop(b);
+    assert_eq!(slot.get().unwrap().0, "a");
+    assert_eq!(*log.borrow(), ["b"]);
+}
+
+#[test]
+fn drop_on_scope_exit() {
+    let (log, make) = log();
+    {
+        let _empty = Slot::<Logged>::new();
+        let _full = Slot::filled(make("full"));
+        let mut emptied = Slot::filled(make("emptied"));
+        let owned = emptied.take_owning().unwrap();
+        drop(owned);
+        assert!(!emptied.is_occupied());
+    }
+    assert_eq!(*log.borrow(), ["emptied", "full"]);
+}
+
+#[test]
+fn forgotten_owning_ref_leaks() {
+    let (log, make) = log();
+    let mut slot = Slot::new();
+    std::mem::forget(slot.insert(make("leaked")));
+    assert!(!slot.is_occupied());
+    drop(slot);
+    assert!(log.borrow().is_empty());
+}
+
+#[test]
+fn reusable_slot_cycle() {
+    let (log, make) = log();
+    let mut slot = ReusableSlot::new();
+    let mut taken = Vec::new();
+    for (i, name) in ["a", "b", "c", "d", "e", "f"].into_iter().enumerate() {
+        let filled = slot.fill(make(name));
+        assert_eq!(filled.0, name);
+        match i % 3 {
+            0 => drop(filled),
+            1 => taken.push(FilledSlot::take(filled)),
+            _ => {
+                let owned = FilledSlot::into_owning_ref(filled);
+                assert_eq!(owned.0, name);
+            }
+        }
+    }
+    assert_eq!(*log.borrow(), ["a", "c", "d", "f"]);
+    drop(taken);
+    assert_eq!(*log.borrow(), ["a", "c", "d", "f", "b", "e"]);
+}
@@ -0,0 +1,99 @@
+//! Checks `SlotPool`'s slot accounting, whether slots are checked out with
+//! `try_insert` or allocated through the `Allocator` impl.
+#![cfg(all(feature = "alloc", feature = "nightly"))]
+#![feature(allocator_api)]
+
+use std::{
+    alloc::{Allocator, Layout},
+    collections::LinkedList,
+    mem::MaybeUninit,
+    rc::Rc,
+};
+
+use noop_allocator::pool::SlotPool;
+
+#[test]
+fn exhaust_release_reclaim() {
+    let mut buf = [const { MaybeUninit::uninit() }; 4];
+    let pool = SlotPool::new(&mut buf);
+    let mut held: Vec<_> = (0..4).map(|i| pool.try_insert(i).unwrap()).collect();
+    assert_eq!(pool.len(), 4);
+    assert_eq!(pool.try_insert(4), Err(4));
+
+    // Release the middle two; the freed slots are reused, most recent first.
+    let released: Vec<_> = held.drain(1..3).map(|b| pool.index_of(&b)).collect();
+    assert_eq!(released, [1, 2]);
+    assert_eq!(pool.live().collect::<Vec<_>>(), [0, 3]);
+    let x = pool.try_insert(10).unwrap();
+    let y = pool.try_insert(11).unwrap();
+    assert_eq!((pool.index_of(&x), pool.index_of(&y)), (2, 1));
+    assert_eq!(pool.try_insert(12), Err(12));
+    held.extend([x, y]);
+    assert_eq!(held.iter().map(|b| **b).collect::<Vec<_>>(), [0, 3, 10, 11]);
+
+    drop(held);
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn mixed_with_box_new_in() {
+    let mut buf = [const { MaybeUninit::<u64>::uninit() }; 3];
+    let pool = SlotPool::new(&mut buf);
+    let a = pool.try_insert(1).unwrap();
+    // Any layout that fits in a `u64` uses a slot.
+    let b = Box::new_in(2u32, &pool);
+    let c = Box::new_in([3u8; 8], &pool);
+    assert_eq!(pool.len(), 3);
+    assert!(Box::try_new_in(4u64, &pool).is_err());
+    drop(b);
+    assert_eq!(pool.len(), 2);
+
+    // Layouts that do not fit fail without touching the free list.
+    assert!(pool.allocate(Layout::new::<u128>()).is_err());
+    assert!(
+        pool.allocate(Layout::from_size_align(4, 16).unwrap())
+            .is_err()
+    );
+    assert_eq!(pool.len(), 2);
+    // Zero-sized allocations do not use a slot.
+    let unit = Box::new_in((), &pool);
+    assert_eq!(pool.len(), 2);
+    drop(unit);
+
+    let d = pool.try_insert(4).unwrap();
+    assert_eq!(pool.len(), 3);
+    assert_eq!((*a, c[0], *d), (1, 3, 4));
+}
+
+#[test]
+fn linked_list_nodes() {
+    // A `LinkedList<usize>` node is a value and two pointers.
+    let mut buf = [const { MaybeUninit::<[usize; 3]>::uninit() }; 4];
+    let pool = SlotPool::new(&mut buf);
+    let mut list = LinkedList::new_in(&pool);
+    for i in 0..100usize {
+        if list.len() == 4 {
+            list.pop_front();
+        }
+        list.push_back(i);
+        assert_eq!(pool.len(), list.len());
+    }
+    assert!(list.iter().eq(&[96, 97, 98, 99]));
+    drop(list);
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn pool_dropped_with_leaked_entry() {
+    let value = Rc::new(());
+    let mut buf = [const { MaybeUninit::uninit() }; 2];
+    {
+        let pool = SlotPool::new(&mut buf);
+        std::mem::forget(pool.try_insert(value.clone()).unwrap());
+        drop(pool.try_insert(value.clone()).unwrap());
+        assert_eq!(pool.len(), 1);
+    }
+    // The pool does not drop values it does not own, so the forgotten one
+    // leaks, like a forgotten `OwningRef`.
+    assert_eq!(Rc::strong_count(&value), 2);
+}
@@ -0,0 +1,82 @@
+//! Checks that `SpillAllocator` routes blocks from its fallback allocator back
+//! to it, even when they start right after the buffer.
+#![cfg(all(feature = "alloc", feature = "nightly"))]
+#![feature(allocator_api)]
+
+use std::{
+    alloc::{AllocError, Allocator, Layout},
+    cell::Cell,
+    mem::MaybeUninit,
+    ptr::NonNull,
+};
+
+use noop_allocator::spill::SpillAllocator;
+
+/// A fallback that hands out one fixed block, and records whether it was
+/// deallocated.
+struct Fixed {
+    block: NonNull<u8>,
+    len: usize,
+    live: Cell<bool>,
+}
+
+unsafe impl Allocator for Fixed {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if self.live.get() || layout.size() > self.len {
+            return Err(AllocError);
+        }
+        self.live.set(true);
+        Ok(NonNull::slice_from_raw_parts(self.block, layout.size()))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, _layout: Layout) {
+        assert_eq!(ptr, self.block);
+        self.live.set(false);
+    }
+}
+
+#[test]
+fn fallback_block_at_end_of_buffer() {
+    let mut buf = [MaybeUninit::<u8>::uninit(); 64];
+    let (first, second) = buf.split_at_mut(32);
+    let fallback = Fixed {
+        block: NonNull::from(second).cast(),
+        len: 32,
+        live: Cell::new(false),
+    };
+    let spill = SpillAllocator::new_in(first, fallback);
+
+    // Fill the buffer exactly, after its header.
+    let layout = Layout::array::<u8>(32 - size_of::<usize>()).unwrap();
+    let a = spill.allocate(layout).unwrap().cast::<u8>();
+    assert_eq!(spill.buffer_remaining(), 0);
+
+    // The next block comes from the fallback, starting at the buffer's end.
+    let b = Box::new_in([1u8; 8], &spill);
+    assert_eq!(b.as_ptr(), spill.fallback().block.as_ptr());
+    assert!(spill.fallback().live.get());
+
+    // It is returned to the fallback, and the buffer's top is untouched.
+    drop(b);
+    assert!(!spill.fallback().live.get());
+    assert_eq!(spill.buffer_remaining(), 0);
+    unsafe { spill.deallocate(a, layout) };
+    assert_eq!(spill.buffer_remaining(), 32);
+}
+
+#[test]
+fn zero_sized_allocations_use_no_space() {
+    let mut buf = [MaybeUninit::<u8>::uninit(); 16];
+    let spill = SpillAllocator::new(&mut buf);
+    let zst = Layout::new::<[u64; 0]>();
+    let ptr = spill.allocate(zst).unwrap().cast::<u8>();
+    assert_eq!(ptr.as_ptr() as usize % 8, 0);
+    assert_eq!(spill.buffer_remaining(), 16);
+
+    // Growing a zero-sized allocation allocates a new block.
+    let grown = unsafe { spill.grow(ptr, zst, Layout::new::<u64>()) }.unwrap();
+    assert_eq!(spill.buffer_remaining(), 0);
+    let ptr = unsafe { spill.shrink(grown.cast(), Layout::new::<u64>(), zst) }.unwrap();
+    assert_eq!(spill.buffer_remaining(), 16);
+    unsafe { spill.deallocate(ptr.cast(), zst) };
+}
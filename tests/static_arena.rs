@@ -0,0 +1,51 @@
+//! Checks that a `StaticArena` can be claimed exactly once, and that the
+//! claimed allocator's collections are `'static`.
+#![cfg(feature = "nightly")]
+#![feature(allocator_api)]
+
+use std::{
+    alloc::{Allocator, Layout},
+    sync::{Mutex, OnceLock},
+};
+
+use noop_allocator::bump::{BumpAllocator, StaticArena};
+
+#[test]
+fn concurrent_claims() {
+    static ARENA: StaticArena<64> = StaticArena::new();
+    assert!(!ARENA.is_claimed());
+
+    let winners = std::thread::scope(|s| {
+        let handles: Vec<_> = (0..8)
+            .map(|_| s.spawn(|| ARENA.claim().is_some()))
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .filter(|&won| won)
+            .count()
+    });
+    assert_eq!(winners, 1);
+    assert!(ARENA.is_claimed());
+    assert!(ARENA.claim().is_none());
+    assert!(ARENA.claim_bytes().is_none());
+}
+
+#[test]
+fn stored_in_static() {
+    static ARENA: StaticArena<1024, 64> = StaticArena::new();
+    static NAMES: OnceLock<Mutex<Vec<&'static str, BumpAllocator<'static>>>> = OnceLock::new();
+
+    let bump = ARENA.claim().unwrap();
+    let block = bump
+        .allocate(Layout::from_size_align(1, 64).unwrap())
+        .unwrap();
+    assert_eq!(block.as_ptr().cast::<u8>() as usize % 64, 0);
+
+    let names = NAMES.get_or_init(|| Mutex::new(Vec::with_capacity_in(4, bump)));
+    std::thread::spawn(|| NAMES.get().unwrap().lock().unwrap().push("spawned"))
+        .join()
+        .unwrap();
+    names.lock().unwrap().push("main");
+    assert_eq!(*names.lock().unwrap(), ["spawned", "main"]);
+}
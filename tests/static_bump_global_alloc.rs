@@ -0,0 +1,51 @@
+//! Allocates through `Box` and `Vec` with `StaticBumpGlobalAlloc` as the
+//! global allocator.
+//!
+//! This uses a custom `main` (`harness = false`), since the default test
+//! harness allocates far more than a small static buffer holds.
+
+mod imp {
+    use noop_allocator::global::StaticBumpGlobalAlloc;
+
+    #[global_allocator]
+    static HEAP: StaticBumpGlobalAlloc<{ 64 * 1024 }> = StaticBumpGlobalAlloc::new();
+
+    pub fn run() {
+        let before = HEAP.used();
+        let boxed = Box::new(42u64);
+        assert_eq!(*boxed, 42);
+        assert_eq!(&*boxed as *const u64 as usize % 8, 0);
+        assert!(HEAP.used() >= before + 8);
+
+        let aligned = Box::new(Aligned([7; 64]));
+        assert_eq!(&*aligned as *const Aligned as usize % 64, 0);
+        assert_eq!(aligned.0[63], 7);
+
+        // Growing the most recent allocation happens in place.
+        let mut vec: Vec<u32> = Vec::with_capacity(4);
+        vec.extend(0..4);
+        let ptr = vec.as_ptr();
+        vec.push(4);
+        assert_eq!(vec.as_ptr(), ptr);
+        vec.extend(5..100);
+        assert_eq!(vec.iter().sum::<u32>(), 4950);
+
+        // Memory is never freed, so the high-water mark only grows.
+        let used = HEAP.used();
+        drop((boxed, aligned, vec));
+        assert_eq!(HEAP.used(), used);
+
+        let mut big: Vec<u8> = Vec::new();
+        assert!(big.try_reserve(HEAP.remaining() + 1).is_err());
+        big.try_reserve_exact(HEAP.remaining()).unwrap();
+        assert_eq!(HEAP.remaining(), 0);
+        assert!(Vec::<u8>::new().try_reserve(1).is_err());
+    }
+
+    #[repr(align(64))]
+    struct Aligned([u8; 64]);
+}
+
+fn main() {
+    imp::run();
+}
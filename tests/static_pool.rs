@@ -0,0 +1,64 @@
+//! Checks that a `StaticPool` frees slots when their boxes are dropped, and
+//! reuses them across threads.
+#![cfg(all(feature = "alloc", feature = "nightly"))]
+
+use std::{
+    collections::HashSet,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use noop_allocator::pool::StaticPool;
+
+#[test]
+fn exhaustion_returns_value() {
+    static POOL: StaticPool<String, 3> = StaticPool::new();
+    let held: Vec<_> = (0..3)
+        .map(|i| POOL.try_claim(i.to_string()).unwrap())
+        .collect();
+    assert_eq!(
+        POOL.try_claim(String::from("extra")),
+        Err(String::from("extra"))
+    );
+    assert_eq!(POOL.available(), 0);
+    drop(held);
+    assert_eq!(POOL.available(), 3);
+}
+
+#[test]
+fn claim_and_release_across_threads() {
+    static POOL: StaticPool<[usize; 4], 4> = StaticPool::new();
+    static CLAIMS: AtomicUsize = AtomicUsize::new(0);
+
+    let addrs: Vec<HashSet<usize>> = std::thread::scope(|s| {
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                s.spawn(move || {
+                    let mut addrs = HashSet::new();
+                    let mut claimed = 0;
+                    while claimed < 200 {
+                        let Ok(b) = POOL.try_claim([t; 4]) else {
+                            std::thread::yield_now();
+                            continue;
+                        };
+                        assert_eq!(*b, [t; 4]);
+                        addrs.insert(&raw const *b as usize);
+                        claimed += 1;
+                        // Send every other box to a new thread to be dropped.
+                        if claimed % 2 == 0 {
+                            std::thread::spawn(move || drop(b)).join().unwrap();
+                        }
+                        CLAIMS.fetch_add(1, Ordering::Relaxed);
+                    }
+                    addrs
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    assert_eq!(CLAIMS.load(Ordering::Relaxed), 800);
+    assert_eq!(POOL.available(), 4);
+    // 800 claims were served by the same 4 slots.
+    let all: HashSet<_> = addrs.into_iter().flatten().collect();
+    assert!(all.len() <= 4);
+}
@@ -0,0 +1,57 @@
+//! Checks that a `StaticSlot` can be initialized exactly once, even when
+//! threads race to initialize it.
+#![cfg(all(feature = "alloc", feature = "nightly"))]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use noop_allocator::{owning_ref::OwningRef, slot::StaticSlot, static_slot};
+
+static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted(usize);
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn racing_init() {
+    static SLOT: StaticSlot<Counted> = StaticSlot::new();
+    assert!(!SLOT.is_claimed());
+
+    let winners: Vec<OwningRef<'static, Counted>> = std::thread::scope(|s| {
+        let handles: Vec<_> = (0..8)
+            .map(|i| s.spawn(move || SLOT.init(Counted(i))))
+            .collect();
+        handles
+            .into_iter()
+            .filter_map(|h| h.join().unwrap())
+            .collect()
+    });
+    assert_eq!(winners.len(), 1);
+    assert!(winners[0].0 < 8);
+    // The losers' values were dropped.
+    assert_eq!(DROPS.load(Ordering::Relaxed), 7);
+    assert!(SLOT.is_claimed());
+
+    // Dropping the `OwningRef` drops the value, but does not reopen the slot.
+    drop(winners);
+    assert_eq!(DROPS.load(Ordering::Relaxed), 8);
+    assert!(SLOT.init(Counted(8)).is_none());
+    assert_eq!(DROPS.load(Ordering::Relaxed), 9);
+}
+
+#[test]
+fn macro_reuse_panics() {
+    fn claim() -> OwningRef<'static, Vec<u8>> {
+        static_slot!(Vec<u8>, vec![1, 2, 3])
+    }
+    let first = claim();
+    assert_eq!(*first, [1, 2, 3]);
+    let second = std::panic::catch_unwind(claim);
+    assert!(second.is_err());
+    // Distinct expansions have distinct slots.
+    assert_eq!(*static_slot!(Vec<u8>, vec![4]), [4]);
+}
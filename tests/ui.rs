@@ -0,0 +1,9 @@
+//! Checks that misuse of the crate's guards is rejected at compile time, with
+//! the expected errors.
+#![cfg(all(feature = "alloc", feature = "nightly"))]
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
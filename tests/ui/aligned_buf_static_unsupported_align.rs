@@ -0,0 +1,3 @@
+noop_allocator::aligned_buf!(static BUF: 16 bytes, align 8192);
+
+fn main() {}
@@ -0,0 +1,3 @@
+fn main() {
+    let _buf = noop_allocator::aligned_buf!(16, 24);
+}
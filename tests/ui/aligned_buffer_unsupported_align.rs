@@ -0,0 +1,5 @@
+use noop_allocator::buffer::AlignedBuffer;
+
+fn main() {
+    let _buf = AlignedBuffer::<16, 3>::uninit();
+}
@@ -0,0 +1,8 @@
+use noop_allocator::slot::ReusableSlot;
+
+fn main() {
+    let mut slot = ReusableSlot::new();
+    let a = slot.fill(1);
+    let b = slot.fill(2);
+    assert_eq!(*a + *b, 3);
+}
@@ -0,0 +1,13 @@
+use noop_allocator::slot::{FilledSlot, ReusableSlot};
+
+fn main() {
+    let mut slot = ReusableSlot::new();
+    let mut previous: Option<&u32> = None;
+    for i in 0..2 {
+        let filled = slot.fill(i);
+        if let Some(previous) = previous {
+            assert_eq!(*previous + 1, *filled);
+        }
+        previous = Some(FilledSlot::leak(filled));
+    }
+}
@@ -0,0 +1,9 @@
+use noop_allocator::slot::ReusableSlot;
+
+fn main() {
+    let mut slot = ReusableSlot::new();
+    let filled = slot.fill(String::from("pinned"));
+    let moved = slot;
+    assert_eq!(filled.len(), 6);
+    drop(moved);
+}